@@ -4,6 +4,8 @@ use soroban_sdk::{
 
 use crate::timelocks::Timelocks;
 use crate::Immutables;
+use events::FundsRescued;
+use utils::ttl::{TtlPolicy, DEFAULT_POLICY};
 
 // Errors
 
@@ -24,6 +26,10 @@ const RESCUE_DELAY: Symbol = symbol_short!("RES_DEL");
 const ACCESS_TOKEN: Symbol = symbol_short!("ACC_TOK");
 const XML_ADDRESS: Symbol = symbol_short!("XML_ADD");
 
+/// TTL policy of the escrow instances, which hold all of their state. The factory deploys
+/// escrows without constructor arguments, so they all keep the default policy.
+pub const STORAGE_TTL: TtlPolicy = DEFAULT_POLICY;
+
 // Contract Implementation
 pub trait BaseEscrow {
     fn __constructor(env: Env, rescue_delay: u32, access_token: Address) {
@@ -136,15 +142,19 @@ pub trait BaseEscrow {
             ),
         )?;
 
-        Self::uni_transfer(
-            env.clone(),
+        let recipient: Address = env
+            .storage()
+            .persistent()
+            .get(&symbol_short!("sender"))
+            .unwrap();
+        Self::uni_transfer(env.clone(), token.clone(), recipient.clone(), amount);
+
+        FundsRescued {
             token,
-            env.storage()
-                .persistent()
-                .get(&symbol_short!("sender"))
-                .unwrap(),
+            recipient,
             amount,
-        );
+        }
+        .publish(&env);
 
         Ok(())
     }
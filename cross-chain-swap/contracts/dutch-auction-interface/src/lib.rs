@@ -1,9 +1,23 @@
 #![no_std]
 use order_interface::{AuctionDetails, Order};
-use soroban_sdk::{contractclient, Address, Bytes, BytesN, Env, U256};
+use soroban_sdk::{contractclient, contracttype, Address, Bytes, BytesN, Env, U256};
 
-#[contractclient(name = "DutchAuctionCalculatorContractClient")]
-pub trait DutchAuctionCalculatorInterface {
+/// Snapshot of a running Dutch auction
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuctionStatus {
+    /// Elapsed part of the auction window, in basis points
+    pub elapsed_bps: u32,
+    /// Taking amount currently required for the full making amount of the order
+    pub current_rate: U256,
+    /// Seconds left until the auction reaches its end taking amount
+    pub remaining_time: u64,
+}
+
+/// Amount getters the order protocol calls to price a fill.
+/// Every calculator contract (Dutch auction, oracle range, ...) implements it.
+#[contractclient(name = "AmountCalculatorClient")]
+pub trait AmountCalculatorInterface {
     fn get_making_amount(
         env: Env,
         order: Order,
@@ -25,11 +39,17 @@ pub trait DutchAuctionCalculatorInterface {
         remaining_making_amount: U256,
         auction_details: AuctionDetails,
     ) -> U256;
+}
 
+#[contractclient(name = "DutchAuctionCalculatorContractClient")]
+pub trait DutchAuctionCalculatorInterface {
+    /// Legacy entrypoint taking the packed start/end time, see `AuctionWindow::from_packed`.
     fn calculate_auction_taking_amount(
         env: Env,
         auction_start_time: U256,
         taking_amount_start: U256,
         taking_amount_end: U256,
     ) -> U256;
+
+    fn auction_status(env: Env, order: Order, auction_details: AuctionDetails) -> AuctionStatus;
 }
@@ -1,16 +1,45 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, log, Address, Bytes, BytesN, Env, U256};
+use soroban_sdk::{
+    contract, contractimpl, log, panic_with_error, Address, Bytes, BytesN, Env, U256,
+};
 
-use dutch_auction_interface::DutchAuctionCalculatorInterface;
-use order_interface::{AuctionDetails, Order};
-use utils::math::{bitand, max_num, min_num};
+use dutch_auction_interface::{
+    AmountCalculatorInterface, AuctionStatus, DutchAuctionCalculatorInterface,
+};
+use order_interface::{AuctionDetails, AuctionWindow, Order};
+
+const BPS_BASE: u64 = 10_000;
 
-const _LOW_128_BITS: u128 = 0xffffffffffffffffffffffffffffffff;
 #[contract]
 pub struct DutchAuctionCalculatorContract;
 
+fn validated_window(env: &Env, auction_window: AuctionWindow) -> AuctionWindow {
+    if let Err(e) = auction_window.validate() {
+        panic_with_error!(env, e);
+    }
+    auction_window
+}
+
+fn auction_taking_amount(
+    env: &Env,
+    auction_window: &AuctionWindow,
+    taking_amount_start: U256,
+    taking_amount_end: U256,
+) -> U256 {
+    let start_time = U256::from_u128(env, auction_window.start as u128);
+    let end_time = U256::from_u128(env, auction_window.end as u128);
+
+    // Get current time bounded between start and end time
+    let current_time = U256::from_u128(env, auction_window.clamp(env.ledger().timestamp()) as u128);
+
+    (taking_amount_start
+        .mul(&(end_time.sub(&current_time)))
+        .add(&taking_amount_end.mul(&(current_time.sub(&start_time)))))
+    .div(&(end_time.sub(&start_time)))
+}
+
 #[contractimpl]
-impl DutchAuctionCalculatorInterface for DutchAuctionCalculatorContract {
+impl AmountCalculatorInterface for DutchAuctionCalculatorContract {
     fn get_making_amount(
         env: Env,
         order: Order,
@@ -21,9 +50,10 @@ impl DutchAuctionCalculatorInterface for DutchAuctionCalculatorContract {
         _remaining_making_amount: U256,
         auction_details: AuctionDetails,
     ) -> U256 {
-        let calculated_taking_amount = Self::calculate_auction_taking_amount(
-            env.clone(),
-            auction_details.auction_start_time,
+        let auction_window = validated_window(&env, auction_details.auction_window);
+        let calculated_taking_amount = auction_taking_amount(
+            &env,
+            &auction_window,
             auction_details.taking_amount_start,
             auction_details.taking_amount_end,
         );
@@ -48,9 +78,10 @@ impl DutchAuctionCalculatorInterface for DutchAuctionCalculatorContract {
         _remaining_making_amount: U256,
         auction_details: AuctionDetails,
     ) -> U256 {
-        let calculated_taking_amount = Self::calculate_auction_taking_amount(
-            env.clone(),
-            auction_details.auction_start_time,
+        let auction_window = validated_window(&env, auction_details.auction_window);
+        let calculated_taking_amount = auction_taking_amount(
+            &env,
+            &auction_window,
             auction_details.taking_amount_start,
             auction_details.taking_amount_end,
         );
@@ -63,7 +94,10 @@ impl DutchAuctionCalculatorInterface for DutchAuctionCalculatorContract {
         let adjustment = denominator.sub(&U256::from_u32(&env, 1));
         return numerator.add(&adjustment).div(&denominator);
     }
+}
 
+#[contractimpl]
+impl DutchAuctionCalculatorInterface for DutchAuctionCalculatorContract {
     fn calculate_auction_taking_amount(
         env: Env,
         auction_start_time: U256,
@@ -71,25 +105,36 @@ impl DutchAuctionCalculatorInterface for DutchAuctionCalculatorContract {
         taking_amount_end: U256,
     ) -> U256 {
         // auction_start_time packs both start and end time into a single U256
-        // The first 128 bits contain the start time, shifted right to extract it
-        // let start_time = auction_start_time >> 128;
-        let start_time = auction_start_time.shr(128);
+        let auction_window = match AuctionWindow::from_packed(&auction_start_time) {
+            Ok(window) => validated_window(&env, window),
+            Err(e) => panic_with_error!(&env, e),
+        };
 
-        // The last 128 bits contain the end time, masked with _LOW_128_BITS to extract it
-        let end_time = bitand(
+        auction_taking_amount(
             &env,
-            auction_start_time,
-            U256::from_u128(&env, _LOW_128_BITS),
-        );
+            &auction_window,
+            taking_amount_start,
+            taking_amount_end,
+        )
+    }
+
+    fn auction_status(env: Env, _order: Order, auction_details: AuctionDetails) -> AuctionStatus {
+        let auction_window = validated_window(&env, auction_details.auction_window);
+        let current_time = auction_window.clamp(env.ledger().timestamp());
 
-        // Get current time bounded between start and end time
-        let block_time = U256::from_u128(&env, env.ledger().timestamp() as u128);
-        let current_time = max_num(&start_time, min_num(&end_time, &block_time));
+        let elapsed = current_time - auction_window.start;
+        let elapsed_bps = (elapsed * BPS_BASE / auction_window.duration()) as u32;
 
-        (taking_amount_start
-            .mul(&(end_time.sub(&current_time)))
-            .add(&taking_amount_end.mul(&(current_time.sub(&start_time)))))
-        .div(&(end_time.sub(&start_time)))
+        AuctionStatus {
+            elapsed_bps,
+            current_rate: auction_taking_amount(
+                &env,
+                &auction_window,
+                auction_details.taking_amount_start,
+                auction_details.taking_amount_end,
+            ),
+            remaining_time: auction_window.end - current_time,
+        }
     }
 }
 
@@ -1,9 +1,12 @@
 #![cfg(test)]
 
-use super::*;
 use crate::{DutchAuctionCalculatorContract, DutchAuctionCalculatorContractClient};
-use order_interface::{AuctionDetails, Order};
-use soroban_sdk::{testutils::Address as _, Address, Bytes, BytesN, Env, U256};
+use order_interface::{AuctionDetails, AuctionError, AuctionWindow, Order};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Bytes, BytesN, Env, U256,
+};
+use utils::math::bitand;
 
 #[test]
 fn test_dutch_auction_calculator_get_making_amount() {
@@ -12,13 +15,17 @@ fn test_dutch_auction_calculator_get_making_amount() {
     let client = DutchAuctionCalculatorContractClient::new(&env, &contract_id);
 
     let auction_details = AuctionDetails {
-        auction_start_time: U256::from_u128(&env, 1000),
+        auction_window: AuctionWindow {
+            start: 0,
+            end: 1000,
+        },
         taking_amount_start: U256::from_u128(&env, 100),
         taking_amount_end: U256::from_u128(&env, 10),
     };
 
     let order = Order {
         maker: Address::generate(&env),
+        maker_evm_address: None,
         maker_asset: Address::generate(&env),
         taker_asset: Address::generate(&env),
         making_amount: U256::from_u128(&env, 100),
@@ -48,13 +55,17 @@ fn test_dutch_auction_calculator_get_taking_amount() {
     let client = DutchAuctionCalculatorContractClient::new(&env, &contract_id);
 
     let auction_details = AuctionDetails {
-        auction_start_time: U256::from_u128(&env, 1000),
+        auction_window: AuctionWindow {
+            start: 0,
+            end: 1000,
+        },
         taking_amount_start: U256::from_u128(&env, 100),
         taking_amount_end: U256::from_u128(&env, 10),
     };
 
     let order = Order {
         maker: Address::generate(&env),
+        maker_evm_address: None,
         maker_asset: Address::generate(&env),
         taker_asset: Address::generate(&env),
         making_amount: U256::from_u128(&env, 100),
@@ -112,3 +123,116 @@ fn test_bit_and_meaningful_test() {
     let res = bitand(&env, a, b);
     assert_eq!(res, U256::from_u128(&env, 10000120));
 }
+
+#[test]
+fn test_auction_window_packed_round_trip() {
+    let env = Env::default();
+
+    let packed = U256::from_u128(&env, 1000)
+        .shl(128)
+        .add(&U256::from_u128(&env, 1600));
+    let auction_window = AuctionWindow::from_packed(&packed).unwrap();
+
+    assert_eq!(
+        auction_window,
+        AuctionWindow {
+            start: 1000,
+            end: 1600
+        }
+    );
+    assert_eq!(auction_window.to_packed(&env), packed);
+}
+
+#[test]
+fn test_auction_window_validation() {
+    let empty = AuctionWindow {
+        start: 1000,
+        end: 1000,
+    };
+    assert_eq!(empty.validate(), Err(AuctionError::InvalidAuctionWindow));
+
+    let too_long = AuctionWindow {
+        start: 0,
+        end: order_interface::MAX_AUCTION_DURATION + 1,
+    };
+    assert_eq!(too_long.validate(), Err(AuctionError::AuctionTooLong));
+
+    let valid = AuctionWindow {
+        start: 1000,
+        end: 1600,
+    };
+    assert_eq!(valid.validate(), Ok(()));
+}
+
+#[test]
+fn test_auction_window_from_packed_rejects_overflow() {
+    let env = Env::default();
+
+    let packed = U256::from_u128(&env, u128::MAX);
+
+    assert_eq!(
+        AuctionWindow::from_packed(&packed),
+        Err(AuctionError::InvalidAuctionWindow)
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_dutch_auction_calculator_rejects_empty_window() {
+    let env = Env::default();
+    let contract_id = env.register(DutchAuctionCalculatorContract, ());
+    let client = DutchAuctionCalculatorContractClient::new(&env, &contract_id);
+
+    client.calculate_auction_taking_amount(
+        &U256::from_u128(&env, 0),
+        &U256::from_u128(&env, 100),
+        &U256::from_u128(&env, 10),
+    );
+}
+
+#[test]
+fn test_dutch_auction_calculator_auction_status() {
+    let env = Env::default();
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 1150;
+    });
+    let contract_id = env.register(DutchAuctionCalculatorContract, ());
+    let client = DutchAuctionCalculatorContractClient::new(&env, &contract_id);
+
+    let auction_details = AuctionDetails {
+        auction_window: AuctionWindow {
+            start: 1000,
+            end: 1600,
+        },
+        taking_amount_start: U256::from_u128(&env, 1000),
+        taking_amount_end: U256::from_u128(&env, 400),
+    };
+
+    let order = Order {
+        maker: Address::generate(&env),
+        maker_evm_address: None,
+        maker_asset: Address::generate(&env),
+        taker_asset: Address::generate(&env),
+        making_amount: U256::from_u128(&env, 100),
+        taking_amount: U256::from_u128(&env, 400),
+        maker_traits: U256::from_u128(&env, 0),
+        receiver: Address::generate(&env),
+        salt: U256::from_u128(&env, 0),
+    };
+
+    let status = client.auction_status(&order, &auction_details);
+
+    assert_eq!(status.elapsed_bps, 2500);
+    assert_eq!(status.current_rate, U256::from_u128(&env, 850));
+    assert_eq!(status.remaining_time, 450);
+
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 2000;
+    });
+
+    let status = client.auction_status(&order, &auction_details);
+
+    assert_eq!(status.elapsed_bps, 10_000);
+    assert_eq!(status.current_rate, U256::from_u128(&env, 400));
+    assert_eq!(status.remaining_time, 0);
+}
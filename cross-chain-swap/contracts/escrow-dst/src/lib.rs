@@ -1,8 +1,12 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, symbol_short, BytesN, Env, Symbol};
+use soroban_sdk::{contract, contractimpl, BytesN, Env};
 
 use base_escrow::timelocks::{Stage, Timelocks};
-use base_escrow::{base_escrow::BaseEscrow, Immutables};
+use base_escrow::{
+    base_escrow::{BaseEscrow, STORAGE_TTL},
+    Immutables,
+};
+use events::{Cancelled, Withdrawn};
 
 #[contract]
 pub struct EscrowDst;
@@ -10,11 +14,14 @@ pub struct EscrowDst;
 #[contractimpl]
 impl BaseEscrow for EscrowDst {}
 
-#[allow(dead_code)]
-const ESCROW_DST: Symbol = symbol_short!("ESC_DST");
-
 #[contractimpl]
 impl EscrowDst {
+    /// Extends the TTL of the escrow, so it is not archived before the swap is withdrawn,
+    /// cancelled or rescued. Anyone may call it.
+    pub fn extend_ttl(env: Env) {
+        STORAGE_TTL.extend_instance(&env);
+    }
+
     #[allow(dead_code)]
     fn withdraw(env: Env, secret: BytesN<32>, immutables: Immutables) {
         if let Err(_e) = Self::only_taker(env.clone(), immutables.clone()) {
@@ -97,13 +104,18 @@ impl EscrowDst {
         }
         Self::uni_transfer(
             env.clone(),
-            immutables.token,
-            immutables.taker,
+            immutables.token.clone(),
+            immutables.taker.clone(),
             immutables.amount as i128,
         );
 
-        env.events()
-            .publish((&ESCROW_DST, symbol_short!("canceled")), ());
+        Cancelled {
+            hashlock: immutables.hashlock,
+            recipient: immutables.taker,
+            token: immutables.token,
+            amount: immutables.amount as i128,
+        }
+        .publish(&env);
     }
 
     #[allow(dead_code)]
@@ -118,11 +130,17 @@ impl EscrowDst {
         }
         Self::uni_transfer(
             env.clone(),
-            immutables.token,
-            immutables.maker,
+            immutables.token.clone(),
+            immutables.maker.clone(),
             immutables.amount as i128,
         );
-        env.events()
-            .publish((&ESCROW_DST, symbol_short!("withdraw")), secret);
+        Withdrawn {
+            hashlock: immutables.hashlock,
+            secret,
+            recipient: immutables.maker,
+            token: immutables.token,
+            amount: immutables.amount as i128,
+        }
+        .publish(&env);
     }
 }
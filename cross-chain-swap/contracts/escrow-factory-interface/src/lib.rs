@@ -1,6 +1,19 @@
 #![no_std]
 use base_escrow::Immutables;
-use soroban_sdk::{contractclient, Address, BytesN, Env, U256};
+use soroban_sdk::{contractclient, contracttype, Address, BytesN, Env, U256};
+
+/// Source escrow parameters of a cross-chain order. The maker appends them, XDR encoded, to
+/// the factory contract id in the `PostInteractionData` extension of the order.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExtraDataArgs {
+    /// Hashlock of an order filled at once. Orders filled in parts hold the number of parts
+    /// in the 2 high bytes and the low 30 bytes of the merkle root of their secrets.
+    pub hashlock_info: BytesN<32>,
+    /// Native XLM the taker locks in the source escrow along with the maker's tokens
+    pub safety_deposit: u128,
+    pub timelocks: U256,
+}
 
 #[contractclient(name = "EscrowFactoryClient")]
 pub trait EscrowFactoryInterface {
@@ -9,6 +22,8 @@ pub trait EscrowFactoryInterface {
         escrow_dst_wasm_hash: BytesN<32>,
         escrow_src_wasm_hash: BytesN<32>,
         xlm_address: Address,
+        limit_order_protocol: Address,
+        merkle_storage_invalidator: Address,
     );
 
     fn create_dst_escrow(
@@ -19,4 +34,7 @@ pub trait EscrowFactoryInterface {
     ) -> Address;
 
     fn address_of_escrow_src(env: Env, immutables: Immutables) -> Address;
+
+    /// Native XLM token the safety deposits are paid in
+    fn xlm_address(env: Env) -> Address;
 }
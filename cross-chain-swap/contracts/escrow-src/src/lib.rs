@@ -1,10 +1,12 @@
 #![no_std]
-use soroban_sdk::{
-    contract, contractimpl, symbol_short, token::TokenClient, Address, BytesN, Env, Symbol,
-};
+use soroban_sdk::{contract, contractimpl, symbol_short, token::TokenClient, Address, BytesN, Env};
 
 use base_escrow::timelocks::{Stage, Timelocks};
-use base_escrow::{base_escrow::BaseEscrow, Immutables};
+use base_escrow::{
+    base_escrow::{BaseEscrow, STORAGE_TTL},
+    Immutables,
+};
+use events::Withdrawn;
 
 #[contract]
 pub struct EscrowSrc;
@@ -12,12 +14,14 @@ pub struct EscrowSrc;
 #[contractimpl]
 impl BaseEscrow for EscrowSrc {}
 
-// EVENTS SYMBOLS
-#[allow(dead_code)]
-const ESCROW_SRC: Symbol = symbol_short!("ESC_SRC");
-
 #[contractimpl]
 impl EscrowSrc {
+    /// Extends the TTL of the escrow, so it is not archived before the swap is withdrawn,
+    /// cancelled or rescued. Anyone may call it.
+    pub fn extend_ttl(env: Env) {
+        STORAGE_TTL.extend_instance(&env);
+    }
+
     #[allow(dead_code)]
     fn withdraw(env: Env, secret: BytesN<32>, immutables: Immutables) {
         let res = Self::only_taker(env.clone(), immutables.clone());
@@ -69,10 +73,11 @@ impl EscrowSrc {
         if let Err(_e) = res {
             panic!("Invalid secret");
         }
+        let amount: i128 = immutables.amount.try_into().unwrap();
         TokenClient::new(&env, &immutables.token).transfer(
             &env.current_contract_address(),
             &target,
-            &immutables.amount.try_into().unwrap(),
+            &amount,
         );
 
         Self::xlm_transfer(
@@ -84,7 +89,13 @@ impl EscrowSrc {
             immutables.safety_deposit.try_into().unwrap(),
         );
 
-        env.events()
-            .publish((&ESCROW_SRC, symbol_short!("withdraw")), secret);
+        Withdrawn {
+            hashlock: immutables.hashlock,
+            secret,
+            recipient: target,
+            token: immutables.token,
+            amount,
+        }
+        .publish(&env);
     }
 }
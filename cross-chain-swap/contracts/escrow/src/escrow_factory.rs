@@ -1,12 +1,21 @@
 use soroban_sdk::token::Client as TokenClient;
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, xdr::ToXdr, Address, BytesN,
-    Env, Symbol, U256,
+    contract, contracterror, contractimpl, symbol_short,
+    xdr::{FromXdr, ToXdr},
+    Address, Bytes, BytesN, Env, Symbol, U256,
 };
 
 use base_escrow::timelocks::{Stage, Timelocks};
 use base_escrow::Immutables;
-use escrow_factory_interface::EscrowFactoryInterface;
+use escrow_factory_interface::{EscrowFactoryInterface, ExtraDataArgs};
+use events::EscrowCreated;
+pub use events::EscrowType;
+use merkle_storage::merkle_storage_invalidator::{
+    last_validated_key, parts_amount, MerkleStorageInvalidatorContractClient,
+};
+use order_interface::{Order, PostInteractionInterface};
+use order_lib::maker_traits::MakerTraitsLib;
+use utils::protocol::{require_limit_order_protocol, set_limit_order_protocol};
 
 // CONTRACTS
 
@@ -14,15 +23,6 @@ use escrow_factory_interface::EscrowFactoryInterface;
 #[contract]
 pub struct EscrowFactory;
 
-// CUSTOM DATA TYPES
-
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum EscrowType {
-    Destination,
-    Source,
-}
-
 // Errors
 
 #[contracterror]
@@ -34,21 +34,11 @@ pub enum Error {
     InvalidCreationTime = 3,
 }
 
-// Events data types
-#[contracttype]
-pub struct DstEscrowCreated {
-    escrow_address: Address,
-    hashlock: BytesN<32>,
-    taker: Address,
-}
-
-// EVENTS SYMBOLS
-const ESCROW_CREATED: Symbol = symbol_short!("ESCR");
-
 // STORAGE SYMBOLS
 const DST_ESCROW_WASM: Symbol = symbol_short!("DST_WASM");
 const SRC_ESCROW_WASM: Symbol = symbol_short!("SRC_WASM");
 const XLM_ADDRESS: Symbol = symbol_short!("XLM_ADD");
+const MERKLE_STORAGE_INVALIDATOR: Symbol = symbol_short!("MERKLE");
 
 // Contract implementation
 #[contractimpl]
@@ -58,6 +48,8 @@ impl EscrowFactoryInterface for EscrowFactory {
         escrow_dst_wasm_hash: BytesN<32>,
         escrow_src_wasm_hash: BytesN<32>,
         xlm_address: Address,
+        limit_order_protocol: Address,
+        merkle_storage_invalidator: Address,
     ) {
         env.storage()
             .instance()
@@ -66,6 +58,10 @@ impl EscrowFactoryInterface for EscrowFactory {
             .instance()
             .set(&SRC_ESCROW_WASM, &escrow_src_wasm_hash);
         env.storage().instance().set(&XLM_ADDRESS, &xlm_address);
+        set_limit_order_protocol(&env, &limit_order_protocol);
+        env.storage()
+            .instance()
+            .set(&MERKLE_STORAGE_INVALIDATOR, &merkle_storage_invalidator);
     }
 
     // Function for creating destination chain escrow contract
@@ -124,11 +120,13 @@ impl EscrowFactoryInterface for EscrowFactory {
         }
 
         // Extract values before moving mutable_immutables
+        let order_hash = mutable_immutables.order_hash.clone();
         let maker = mutable_immutables.maker.clone();
         let hashlock = mutable_immutables.hashlock.clone();
         let taker = mutable_immutables.taker.clone();
         let token = mutable_immutables.token.clone();
         let amount = mutable_immutables.amount.clone();
+        let safety_deposit = mutable_immutables.safety_deposit;
 
         // Generate salt similar to keccak256(immutables, ESCROW_IMMUTABLES_SIZE)
         // Hash the entire immutables struct to create a deterministic salt
@@ -163,14 +161,18 @@ impl EscrowFactoryInterface for EscrowFactory {
         token_client.transfer(&maker, &escrow, &amount_signed);
 
         // We emit the event
-        env.events().publish(
-            (&ESCROW_CREATED, symbol_short!("dst")),
-            DstEscrowCreated {
-                escrow_address: escrow.clone(),
-                hashlock,
-                taker,
-            },
-        );
+        EscrowCreated {
+            escrow_type: EscrowType::Destination,
+            escrow: escrow.clone(),
+            order_hash,
+            hashlock,
+            maker,
+            taker,
+            token,
+            amount: amount_signed,
+            safety_deposit: safety_deposit as i128,
+        }
+        .publish(&env);
 
         // Return the escrow contract address
         return escrow;
@@ -182,4 +184,149 @@ impl EscrowFactoryInterface for EscrowFactory {
         let salt = env.crypto().keccak256(&immutables.to_xdr(&env));
         env.deployer().with_address(maker, salt).deployed_address()
     }
+
+    fn xlm_address(env: Env) -> Address {
+        env.storage().instance().get(&XLM_ADDRESS).unwrap()
+    }
+}
+
+/// Builds the source escrow of a fill. The taker sends the maker's tokens to the precomputed
+/// escrow address, together with the native safety deposit, and the factory deploys the escrow
+/// once the order is settled.
+#[contractimpl]
+impl PostInteractionInterface for EscrowFactory {
+    fn post_interaction(
+        env: Env,
+        order: Order,
+        _extension: Bytes,
+        order_hash: BytesN<32>,
+        taker: Address,
+        making_amount: U256,
+        _taking_amount: U256,
+        remaining_making_amount: U256,
+        extra_data: Bytes,
+    ) {
+        require_limit_order_protocol(&env);
+
+        let args = ExtraDataArgs::from_xdr(&env, &extra_data)
+            .unwrap_or_else(|_| panic!("InvalidExtraData"));
+
+        // Each part of a multiple-fill order is locked with the secret the taker validated for it
+        let hashlock = if MakerTraitsLib::allow_multiple_fills(&env, order.maker_traits.clone()) {
+            let parts_amount = parts_amount(&args.hashlock_info);
+            if parts_amount < 2 {
+                panic!("InvalidSecretsAmount");
+            }
+            let merkle_storage_invalidator: Address = env
+                .storage()
+                .instance()
+                .get(&MERKLE_STORAGE_INVALIDATOR)
+                .unwrap();
+            let validated =
+                MerkleStorageInvalidatorContractClient::new(&env, &merkle_storage_invalidator)
+                    .get_last_validated(&last_validated_key(&env, &order_hash, &args.hashlock_info))
+                    .unwrap_or_else(|| panic!("InvalidPartialFill"));
+            if !is_valid_partial_fill(
+                &env,
+                &making_amount,
+                &remaining_making_amount,
+                &order.making_amount,
+                parts_amount,
+                &validated.index,
+            ) {
+                panic!("InvalidPartialFill");
+            }
+            validated.leaf
+        } else {
+            args.hashlock_info
+        };
+
+        let amount = making_amount.to_u128().unwrap();
+        let immutables = Immutables {
+            order_hash: order_hash.clone(),
+            hashlock: hashlock.clone(),
+            maker: order.maker.clone(),
+            taker: taker.clone(),
+            token: order.maker_asset.clone(),
+            amount,
+            safety_deposit: args.safety_deposit,
+            timelocks: Timelocks::set_deployed_at(
+                env.clone(),
+                args.timelocks,
+                U256::from_u128(&env, env.ledger().timestamp() as u128),
+            ),
+        };
+
+        let wasm_hash = env
+            .storage()
+            .instance()
+            .get::<_, BytesN<32>>(&SRC_ESCROW_WASM)
+            .unwrap_or_else(|| panic!("EscrowWasmNotAvailable"));
+        let salt = env.crypto().keccak256(&immutables.to_xdr(&env));
+        let escrow = env
+            .deployer()
+            .with_address(order.maker.clone(), salt)
+            .deploy_v2(wasm_hash, ());
+
+        // The escrow must hold the maker's tokens and the safety deposit
+        let xlm_address: Address = env.storage().instance().get(&XLM_ADDRESS).unwrap();
+        let mut native_amount = args.safety_deposit;
+        if xlm_address == order.maker_asset {
+            native_amount += amount;
+        } else if TokenClient::new(&env, &order.maker_asset).balance(&escrow) < amount as i128 {
+            panic!("InsufficientEscrowBalance");
+        }
+        if TokenClient::new(&env, &xlm_address).balance(&escrow) < native_amount as i128 {
+            panic!("InsufficientEscrowBalance");
+        }
+
+        EscrowCreated {
+            escrow_type: EscrowType::Source,
+            escrow,
+            order_hash,
+            hashlock,
+            maker: order.maker,
+            taker,
+            token: order.maker_asset,
+            amount: amount as i128,
+            safety_deposit: args.safety_deposit as i128,
+        }
+        .publish(&env);
+    }
+}
+
+/**
+ * Port of Solidity BaseEscrowFactory._isValidPartialFill().
+ * Checks that the secret validated for a fill is the one of the part it fills up to. The secret
+ * of index `parts_amount` is kept for the fill that completes the order.
+ * @param validated_index Index of the validated secret, plus one.
+ */
+fn is_valid_partial_fill(
+    env: &Env,
+    making_amount: &U256,
+    remaining_making_amount: &U256,
+    order_making_amount: &U256,
+    parts_amount: u16,
+    validated_index: &U256,
+) -> bool {
+    let one = U256::from_u32(env, 1);
+    let parts_amount = U256::from_u32(env, parts_amount as u32);
+    let filled = order_making_amount.sub(remaining_making_amount);
+    let calculated_index = filled
+        .add(making_amount)
+        .sub(&one)
+        .mul(&parts_amount)
+        .div(order_making_amount);
+
+    if remaining_making_amount == making_amount {
+        // The fill completing the order must use the secret after the one of the last part
+        return calculated_index.add(&U256::from_u32(env, 2)) == *validated_index;
+    } else if order_making_amount != remaining_making_amount {
+        // Two fills within the same part would share a secret
+        let prev_calculated_index = filled.sub(&one).mul(&parts_amount).div(order_making_amount);
+        if calculated_index == prev_calculated_index {
+            return false;
+        }
+    }
+    calculated_index.add(&one) == *validated_index
 }
@@ -1,8 +1,25 @@
-use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, U256};
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    token::StellarAssetClient,
+    vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, TryFromVal, U256,
+};
+use utils::ttl::TtlPolicy;
 
 use crate::{escrow_factory::EscrowFactory, escrow_factory::EscrowFactoryClient};
 
-use base_escrow::Immutables;
+use base_escrow::{timelocks::Timelocks, Immutables};
+use escrow_factory_interface::ExtraDataArgs;
+use events::{EscrowCreated, EscrowType, DST, ESCROW_CREATED, SRC};
+use merkle_storage::{
+    merkle_proof::{commutative_keccak256, hash_leaf},
+    merkle_storage_invalidator::{
+        MerkleStorageInvalidatorContract, MerkleStorageInvalidatorContractClient, TakerData,
+    },
+};
+use order_interface::Order;
+use order_lib::maker_traits::MakerTraitsBuilder;
 
 // The contract that will be deployed by the deployer contract.
 mod escrow_dst_contract {
@@ -14,6 +31,20 @@ mod escrow_src_contract {
     soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/escrow_src.wasm");
 }
 
+fn limit_order_protocol(env: &Env) -> Address {
+    Address::from_str(
+        env,
+        "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAMDR4",
+    )
+}
+
+fn merkle_storage_invalidator(env: &Env) -> Address {
+    Address::from_str(
+        env,
+        "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAOLZM",
+    )
+}
+
 #[test]
 fn test_address_of_escrow_src() {
     let env = Env::default();
@@ -32,7 +63,13 @@ fn test_address_of_escrow_src() {
 
     let contract_id = env.register(
         EscrowFactory,
-        (escrow_dst_wasm_hash, escrow_src_wasm_hash, xlm_address),
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
     );
     let client = EscrowFactoryClient::new(&env, &contract_id);
 
@@ -71,7 +108,13 @@ fn test_address_of_escrow_src_deterministic() {
 
     let contract_id = env.register(
         EscrowFactory,
-        (escrow_dst_wasm_hash, escrow_src_wasm_hash, xlm_address),
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
     );
     let client = EscrowFactoryClient::new(&env, &contract_id);
 
@@ -131,7 +174,13 @@ fn test_create_dst_escrow_basic() {
 
     let contract_id = env.register(
         EscrowFactory,
-        (escrow_dst_wasm_hash, escrow_src_wasm_hash, xlm_address),
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
     );
     let client = EscrowFactoryClient::new(&env, &contract_id);
 
@@ -183,7 +232,13 @@ fn test_create_dst_escrow_with_different_timestamps() {
 
     let contract_id = env.register(
         EscrowFactory,
-        (escrow_dst_wasm_hash, escrow_src_wasm_hash, xlm_address),
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
     );
     let client = EscrowFactoryClient::new(&env, &contract_id);
 
@@ -231,9 +286,16 @@ fn test_escrow_factory_constructor() {
 
     let contract_id = env.register(
         EscrowFactory,
-        (escrow_dst_wasm_hash, escrow_src_wasm_hash, xlm_address),
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            &xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
     );
     let client = EscrowFactoryClient::new(&env, &contract_id);
+    assert_eq!(client.xlm_address(), xlm_address);
 
     // Test that the contract was properly initialized
     // The contract should be able to calculate addresses
@@ -307,7 +369,13 @@ fn test_edge_cases() {
 
     let contract_id = env.register(
         EscrowFactory,
-        (escrow_dst_wasm_hash, escrow_src_wasm_hash, xlm_address),
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
     );
     let client = EscrowFactoryClient::new(&env, &contract_id);
 
@@ -358,7 +426,13 @@ fn test_zero_values() {
 
     let contract_id = env.register(
         EscrowFactory,
-        (escrow_dst_wasm_hash, escrow_src_wasm_hash, xlm_address),
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
     );
     let client = EscrowFactoryClient::new(&env, &contract_id);
 
@@ -446,7 +520,13 @@ fn test_create_dst_escrow_panic_storage() {
 
     let contract_id = env.register(
         EscrowFactory,
-        (escrow_dst_wasm_hash, escrow_src_wasm_hash, xlm_address),
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
     );
     let client = EscrowFactoryClient::new(&env, &contract_id);
     // 1893477661 unix time is the start of year 2030
@@ -488,3 +568,373 @@ fn test_create_dst_escrow_panic_storage() {
     // let output_address : Address = Address::from_str(&env, "CBOYRJDYA5LM652UWKZGSSDRJNJYE76URGF4B7HQ3LY5EFWRR3VVENSF");
     // assert_eq!(test_address_return_output, output_address);
 }
+
+#[test]
+fn test_create_dst_escrow_emits_escrow_created() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let escrow_dst_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(escrow_dst_contract::WASM);
+    let escrow_src_wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(escrow_src_contract::WASM);
+    let xlm_address = Address::generate(&env);
+    let contract_id = env.register(
+        EscrowFactory,
+        (
+            escrow_dst_wasm_hash,
+            escrow_src_wasm_hash,
+            xlm_address,
+            limit_order_protocol(&env),
+            merkle_storage_invalidator(&env),
+        ),
+    );
+    let client = EscrowFactoryClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    StellarAssetClient::new(&env, &token).mint(&maker, &1000);
+    let immutables = Immutables {
+        order_hash: BytesN::from_array(&env, &[1; 32]),
+        hashlock: BytesN::from_array(&env, &[2; 32]),
+        maker: maker.clone(),
+        taker: Address::generate(&env),
+        token: token.clone(),
+        amount: 1000,
+        safety_deposit: 10,
+        timelocks: U256::from_u32(&env, 0),
+    };
+
+    let escrow = client.create_dst_escrow(&immutables, &U256::from_u32(&env, 1893477661), &10);
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, contract_id);
+    assert_eq!(topics, (ESCROW_CREATED, DST).into_val(&env));
+    assert_eq!(
+        EscrowCreated::try_from_val(&env, &data).unwrap(),
+        EscrowCreated {
+            escrow_type: EscrowType::Destination,
+            escrow,
+            order_hash: immutables.order_hash,
+            hashlock: immutables.hashlock,
+            maker,
+            taker: immutables.taker,
+            token,
+            amount: 1000,
+            safety_deposit: 10,
+        }
+    );
+}
+
+/// Factory whose limit order protocol is `limit_order_protocol`, with an invalidator of its own
+struct SrcSetup {
+    env: Env,
+    factory: Address,
+    invalidator: Address,
+    xlm: Address,
+}
+
+impl SrcSetup {
+    fn new() -> Self {
+        let env = Env::default();
+        env.mock_all_auths_allowing_non_root_auth();
+
+        let escrow_dst_wasm_hash = env
+            .deployer()
+            .upload_contract_wasm(escrow_dst_contract::WASM);
+        let escrow_src_wasm_hash = env
+            .deployer()
+            .upload_contract_wasm(escrow_src_contract::WASM);
+        let xlm = create_token(&env);
+        let limit_order_protocol = Address::generate(&env);
+        let invalidator = env.register(
+            MerkleStorageInvalidatorContract,
+            (&limit_order_protocol, None::<TtlPolicy>),
+        );
+        let factory = env.register(
+            EscrowFactory,
+            (
+                escrow_dst_wasm_hash,
+                escrow_src_wasm_hash,
+                xlm.clone(),
+                limit_order_protocol,
+                invalidator.clone(),
+            ),
+        );
+        SrcSetup {
+            env,
+            factory,
+            invalidator,
+            xlm,
+        }
+    }
+
+    /// Order selling 100 of a new token, in two parts if `multiple_fills`
+    fn order(&self, multiple_fills: bool) -> Order {
+        let env = &self.env;
+        let mut maker_traits = MakerTraitsBuilder::new(env.clone());
+        if multiple_fills {
+            maker_traits = maker_traits.allow_multiple_fills();
+        }
+        Order {
+            salt: U256::from_u32(env, 0),
+            maker: Address::generate(env),
+            maker_evm_address: None,
+            receiver: Address::generate(env),
+            maker_asset: create_token(env),
+            taker_asset: create_token(env),
+            making_amount: U256::from_u32(env, 100),
+            taking_amount: U256::from_u32(env, 100),
+            maker_traits: maker_traits.build(),
+        }
+    }
+
+    fn args(&self, hashlock_info: &BytesN<32>) -> ExtraDataArgs {
+        ExtraDataArgs {
+            hashlock_info: hashlock_info.clone(),
+            safety_deposit: 10,
+            timelocks: U256::from_u32(&self.env, 0),
+        }
+    }
+
+    /// Validates the secret of index `idx` at the invalidator
+    fn validate(&self, order: &Order, order_hash: &BytesN<32>, idx: usize) {
+        let env = &self.env;
+        let leaves = secret_leaves(env);
+        let mut post_interaction = Bytes::from_array(env, &[0xfa; 32]);
+        post_interaction.append(&multiple_fill_args(self).to_xdr(env));
+        let mut extension = Bytes::from_array(env, &post_interaction.len().to_be_bytes());
+        extension.extend_from_array(&[0; 28]);
+        extension.append(&post_interaction);
+
+        // Sibling leaf, then the hash of the other pair
+        let other_pair = (idx ^ 2) & !1;
+        let taker_data = TakerData {
+            proof: vec![
+                env,
+                leaves[idx ^ 1].clone(),
+                commutative_keccak256(
+                    env,
+                    leaves[other_pair].clone(),
+                    leaves[other_pair + 1].clone(),
+                ),
+            ],
+            idx: U256::from_u32(env, idx as u32),
+            secret_hash: secret_hashes(env)[idx].clone(),
+        };
+        let zero = U256::from_u32(env, 0);
+        MerkleStorageInvalidatorContractClient::new(env, &self.invalidator).taker_interaction(
+            order,
+            &extension,
+            order_hash,
+            &Address::generate(env),
+            &zero,
+            &zero,
+            &zero,
+            &taker_data.to_xdr(env),
+        );
+    }
+
+    /// Funds the source escrow the fill deploys, then calls the factory as the protocol does
+    /// after the fill. Returns the escrow and taker addresses.
+    fn fill(
+        &self,
+        order: &Order,
+        order_hash: &BytesN<32>,
+        args: &ExtraDataArgs,
+        hashlock: &BytesN<32>,
+        making_amount: u32,
+        remaining_making_amount: u32,
+    ) -> (Address, Address) {
+        let env = &self.env;
+        let taker = Address::generate(env);
+        let immutables = Immutables {
+            order_hash: order_hash.clone(),
+            hashlock: hashlock.clone(),
+            maker: order.maker.clone(),
+            taker: taker.clone(),
+            token: order.maker_asset.clone(),
+            amount: making_amount as u128,
+            safety_deposit: args.safety_deposit,
+            timelocks: Timelocks::set_deployed_at(
+                env.clone(),
+                args.timelocks.clone(),
+                U256::from_u128(env, env.ledger().timestamp() as u128),
+            ),
+        };
+        let escrow =
+            EscrowFactoryClient::new(env, &self.factory).address_of_escrow_src(&immutables);
+        StellarAssetClient::new(env, &order.maker_asset).mint(&escrow, &(making_amount as i128));
+        StellarAssetClient::new(env, &self.xlm).mint(&escrow, &(args.safety_deposit as i128));
+
+        EscrowFactoryClient::new(env, &self.factory).post_interaction(
+            order,
+            &Bytes::new(env),
+            order_hash,
+            &taker,
+            &U256::from_u32(env, making_amount),
+            &U256::from_u32(env, making_amount),
+            &U256::from_u32(env, remaining_making_amount),
+            &args.clone().to_xdr(env),
+        );
+        (escrow, taker)
+    }
+}
+
+fn create_token(env: &Env) -> Address {
+    env.register_stellar_asset_contract_v2(Address::generate(env))
+        .address()
+}
+
+/// Number of parts in the 2 high bytes, the low 30 bytes of the root after them
+fn hashlock_info(env: &Env, parts_amount: u16, root: &BytesN<32>) -> BytesN<32> {
+    let mut hashlock_info = root.to_array();
+    hashlock_info[..2].copy_from_slice(&parts_amount.to_be_bytes());
+    BytesN::from_array(env, &hashlock_info)
+}
+
+/// Secrets of an order filled in 3 parts, one more than its parts
+fn secret_hashes(env: &Env) -> [BytesN<32>; 4] {
+    [1u8, 2, 3, 4].map(|secret| BytesN::from_array(env, &[secret; 32]))
+}
+
+/// Merkle leaves of the secrets, each bound to its index
+fn secret_leaves(env: &Env) -> [BytesN<32>; 4] {
+    let mut idx = 0;
+    secret_hashes(env).map(|secret_hash| {
+        idx += 1;
+        hash_leaf(env, idx - 1, &secret_hash)
+    })
+}
+
+fn multiple_fill_args(setup: &SrcSetup) -> ExtraDataArgs {
+    let env = &setup.env;
+    let [first, second, third, fourth] = secret_leaves(env);
+    let root = commutative_keccak256(
+        env,
+        commutative_keccak256(env, first, second),
+        commutative_keccak256(env, third, fourth),
+    );
+    setup.args(&hashlock_info(env, 3, &root))
+}
+
+#[test]
+fn test_post_interaction_locks_part_with_validated_secret() {
+    let setup = SrcSetup::new();
+    let env = &setup.env;
+    let order = setup.order(true);
+    let order_hash = BytesN::from_array(env, &[1; 32]);
+    let secret_hashes = secret_hashes(env);
+    let args = multiple_fill_args(&setup);
+
+    // Half of the order reaches into its second part
+    setup.validate(&order, &order_hash, 1);
+    let (escrow, taker) = setup.fill(&order, &order_hash, &args, &secret_hashes[1], 50, 100);
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, setup.factory);
+    assert_eq!(topics, (ESCROW_CREATED, SRC).into_val(env));
+    assert_eq!(
+        EscrowCreated::try_from_val(env, &data).unwrap(),
+        EscrowCreated {
+            escrow_type: EscrowType::Source,
+            escrow,
+            order_hash,
+            hashlock: secret_hashes[1].clone(),
+            maker: order.maker,
+            taker,
+            token: order.maker_asset,
+            amount: 50,
+            safety_deposit: 10,
+        }
+    );
+}
+
+#[test]
+fn test_post_interaction_completes_order_with_last_secret() {
+    let setup = SrcSetup::new();
+    let env = &setup.env;
+    let order = setup.order(true);
+    let order_hash = BytesN::from_array(env, &[1; 32]);
+    let secret_hashes = secret_hashes(env);
+    let args = multiple_fill_args(&setup);
+
+    setup.validate(&order, &order_hash, 1);
+    setup.fill(&order, &order_hash, &args, &secret_hashes[1], 50, 100);
+
+    // The fill completing the order takes the secret after the one of its last part
+    setup.validate(&order, &order_hash, 3);
+    setup.fill(&order, &order_hash, &args, &secret_hashes[3], 50, 50);
+}
+
+#[test]
+#[should_panic(expected = "InvalidPartialFill")]
+fn test_post_interaction_rejects_secret_of_other_part() {
+    let setup = SrcSetup::new();
+    let env = &setup.env;
+    let order = setup.order(true);
+    let order_hash = BytesN::from_array(env, &[1; 32]);
+    let args = multiple_fill_args(&setup);
+
+    setup.validate(&order, &order_hash, 0);
+    setup.fill(&order, &order_hash, &args, &secret_hashes(env)[0], 50, 100);
+}
+
+#[test]
+#[should_panic(expected = "InvalidPartialFill")]
+fn test_post_interaction_requires_validated_secret() {
+    let setup = SrcSetup::new();
+    let env = &setup.env;
+    let order = setup.order(true);
+    let order_hash = BytesN::from_array(env, &[1; 32]);
+    let args = multiple_fill_args(&setup);
+
+    setup.fill(&order, &order_hash, &args, &secret_hashes(env)[1], 50, 100);
+}
+
+#[test]
+fn test_post_interaction_locks_single_fill_with_hashlock() {
+    let setup = SrcSetup::new();
+    let env = &setup.env;
+    let order = setup.order(false);
+    let order_hash = BytesN::from_array(env, &[1; 32]);
+    let hashlock = BytesN::from_array(env, &[7; 32]);
+
+    let (escrow, _) = setup.fill(
+        &order,
+        &order_hash,
+        &setup.args(&hashlock),
+        &hashlock,
+        100,
+        100,
+    );
+
+    let (_, _, data) = env.events().all().last().unwrap();
+    let created = EscrowCreated::try_from_val(env, &data).unwrap();
+    assert_eq!(created.escrow, escrow);
+    assert_eq!(created.hashlock, hashlock);
+}
+
+#[test]
+#[should_panic(expected = "InsufficientEscrowBalance")]
+fn test_post_interaction_requires_funded_escrow() {
+    let setup = SrcSetup::new();
+    let env = &setup.env;
+    let order = setup.order(false);
+    let hashlock = BytesN::from_array(env, &[7; 32]);
+
+    EscrowFactoryClient::new(env, &setup.factory).post_interaction(
+        &order,
+        &Bytes::new(env),
+        &BytesN::from_array(env, &[1; 32]),
+        &Address::generate(env),
+        &U256::from_u32(env, 100),
+        &U256::from_u32(env, 100),
+        &U256::from_u32(env, 100),
+        &setup.args(&hashlock).to_xdr(env),
+    );
+}
@@ -0,0 +1,246 @@
+#![no_std]
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Symbol, U256};
+
+// Event schema of every contract of the swap. Each event is published with its topic
+// constant first, followed by the fields indexers filter on, and the event struct as data.
+
+/// `(ORDER_FILLED, order_hash)`
+pub const ORDER_FILLED: Symbol = symbol_short!("ORDR_F");
+/// `(ORDER_CANCELLED, order_hash)`
+pub const ORDER_CANCELLED: Symbol = symbol_short!("ORDR_C");
+/// `(BIT_INVALIDATOR_UPDATED, maker)`
+pub const BIT_INVALIDATOR_UPDATED: Symbol = symbol_short!("BIT_INV");
+/// `(EVM_KEY_REGISTERED, maker)`
+pub const EVM_KEY_REGISTERED: Symbol = symbol_short!("EVM_KEY");
+/// `(ESCROW_CREATED, SRC | DST)`
+pub const ESCROW_CREATED: Symbol = symbol_short!("ESCR");
+/// `(WITHDRAWN, hashlock)`
+pub const WITHDRAWN: Symbol = symbol_short!("WITHDRAWN");
+/// `(CANCELLED, hashlock)`
+pub const CANCELLED: Symbol = symbol_short!("CANCELLED");
+/// `(FUNDS_RESCUED, token)`
+pub const FUNDS_RESCUED: Symbol = symbol_short!("RESCUED");
+/// `(XLM_ORDER_DEPOSITED, order_hash)`
+pub const XLM_ORDER_DEPOSITED: Symbol = symbol_short!("XLM_DEP");
+/// `(XLM_ORDER_CANCELLED, order_hash)`
+pub const XLM_ORDER_CANCELLED: Symbol = symbol_short!("XLM_OC");
+/// `(XLM_ORDER_CANCELLED_BY_THIRD_PARTY, order_hash)`
+pub const XLM_ORDER_CANCELLED_BY_THIRD_PARTY: Symbol = symbol_short!("XLM_OC3");
+
+/// Second topic of escrows created on the source chain
+pub const SRC: Symbol = symbol_short!("src");
+/// Second topic of escrows created on the destination chain
+pub const DST: Symbol = symbol_short!("dst");
+
+/// An order was filled, partially or fully
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OrderFilled {
+    pub order_hash: BytesN<32>,
+    pub maker: Address,
+    pub taker: Address,
+    pub maker_asset: Address,
+    pub taker_asset: Address,
+    pub making_amount: U256,
+    pub taking_amount: U256,
+    /// Making amount left to fill after this fill
+    pub remaining_making_amount: U256,
+    /// Part of the taking amount paid to the order receiver, the rest goes to fee recipients
+    pub receiver_amount: U256,
+    pub integrator_fee: U256,
+    pub protocol_fee: U256,
+    /// Amount above the order's own rate, before the protocol takes its share
+    pub surplus: U256,
+    pub protocol_surplus: U256,
+}
+
+impl OrderFilled {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((ORDER_FILLED, self.order_hash.clone()), self);
+    }
+}
+
+/// An order was cancelled by its maker
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OrderCancelled {
+    pub order_hash: BytesN<32>,
+    pub maker: Address,
+}
+
+impl OrderCancelled {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((ORDER_CANCELLED, self.order_hash.clone()), self);
+    }
+}
+
+/// A slot of a maker's bit invalidator changed, cancelling every order using its nonces
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitInvalidatorUpdated {
+    pub maker: Address,
+    pub slot_index: u64,
+    pub slot_value: U256,
+}
+
+impl BitInvalidatorUpdated {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((BIT_INVALIDATOR_UPDATED, self.maker.clone()), self);
+    }
+}
+
+/// A maker bound the Ethereum key its orders may be signed with, or unbound it with `None`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EvmKeyRegistered {
+    pub maker: Address,
+    pub evm_address: Option<BytesN<20>>,
+}
+
+impl EvmKeyRegistered {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((EVM_KEY_REGISTERED, self.maker.clone()), self);
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EscrowType {
+    Destination,
+    Source,
+}
+
+/// An escrow was deployed and funded
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowCreated {
+    pub escrow_type: EscrowType,
+    pub escrow: Address,
+    pub order_hash: BytesN<32>,
+    pub hashlock: BytesN<32>,
+    pub maker: Address,
+    pub taker: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub safety_deposit: i128,
+}
+
+impl EscrowCreated {
+    pub fn publish(self, env: &Env) {
+        let side = match self.escrow_type {
+            EscrowType::Source => SRC,
+            EscrowType::Destination => DST,
+        };
+        env.events().publish((ESCROW_CREATED, side), self);
+    }
+}
+
+/// The escrowed tokens were released with the secret
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Withdrawn {
+    pub hashlock: BytesN<32>,
+    pub secret: BytesN<32>,
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+impl Withdrawn {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((WITHDRAWN, self.hashlock.clone()), self);
+    }
+}
+
+/// The escrowed tokens were returned after the cancellation timelock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cancelled {
+    pub hashlock: BytesN<32>,
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+impl Cancelled {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((CANCELLED, self.hashlock.clone()), self);
+    }
+}
+
+/// Tokens left in an escrow were rescued after the rescue delay
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundsRescued {
+    pub token: Address,
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+impl FundsRescued {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((FUNDS_RESCUED, self.token.clone()), self);
+    }
+}
+
+/// Native XLM was deposited to back an order
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct XlmOrderDeposited {
+    pub order_hash: BytesN<32>,
+    pub maker: Address,
+    pub amount: i128,
+}
+
+impl XlmOrderDeposited {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((XLM_ORDER_DEPOSITED, self.order_hash.clone()), self);
+    }
+}
+
+/// An XLM order was cancelled and its deposit refunded to the maker
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct XlmOrderCancelled {
+    pub order_hash: BytesN<32>,
+    pub maker: Address,
+    pub refund: i128,
+}
+
+impl XlmOrderCancelled {
+    pub fn publish(self, env: &Env) {
+        env.events()
+            .publish((XLM_ORDER_CANCELLED, self.order_hash.clone()), self);
+    }
+}
+
+/// An expired XLM order was cancelled by a resolver, who kept part of the deposit as a reward
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct XlmOrderCancelledByThirdParty {
+    pub order_hash: BytesN<32>,
+    pub maker: Address,
+    pub resolver: Address,
+    /// Part of the deposit refunded to the maker
+    pub refund: i128,
+    pub reward: i128,
+}
+
+impl XlmOrderCancelledByThirdParty {
+    pub fn publish(self, env: &Env) {
+        env.events().publish(
+            (XLM_ORDER_CANCELLED_BY_THIRD_PARTY, self.order_hash.clone()),
+            self,
+        );
+    }
+}
+
+mod test;
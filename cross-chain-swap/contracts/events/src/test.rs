@@ -0,0 +1,90 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    contract, testutils::Address as _, testutils::Events, Address, BytesN, Env, IntoVal,
+    TryFromVal, U256,
+};
+
+use crate::{
+    EscrowCreated, EscrowType, OrderFilled, Withdrawn, DST, ESCROW_CREATED, ORDER_FILLED, SRC,
+    WITHDRAWN,
+};
+
+#[contract]
+struct Publisher;
+
+#[test]
+fn test_order_filled_is_indexed_by_order_hash() {
+    let env = Env::default();
+    let publisher = env.register(Publisher, ());
+    let event = OrderFilled {
+        order_hash: BytesN::from_array(&env, &[1; 32]),
+        maker: Address::generate(&env),
+        taker: Address::generate(&env),
+        maker_asset: Address::generate(&env),
+        taker_asset: Address::generate(&env),
+        making_amount: U256::from_u32(&env, 100),
+        taking_amount: U256::from_u32(&env, 50),
+        remaining_making_amount: U256::from_u32(&env, 0),
+        receiver_amount: U256::from_u32(&env, 45),
+        integrator_fee: U256::from_u32(&env, 3),
+        protocol_fee: U256::from_u32(&env, 1),
+        surplus: U256::from_u32(&env, 2),
+        protocol_surplus: U256::from_u32(&env, 1),
+    };
+
+    env.as_contract(&publisher, || event.clone().publish(&env));
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, publisher);
+    assert_eq!(
+        topics,
+        (ORDER_FILLED, event.order_hash.clone()).into_val(&env)
+    );
+    assert_eq!(OrderFilled::try_from_val(&env, &data).unwrap(), event);
+}
+
+#[test]
+fn test_escrow_created_topic_follows_escrow_type() {
+    let env = Env::default();
+    let publisher = env.register(Publisher, ());
+    let created = |escrow_type| EscrowCreated {
+        escrow_type,
+        escrow: Address::generate(&env),
+        order_hash: BytesN::from_array(&env, &[1; 32]),
+        hashlock: BytesN::from_array(&env, &[2; 32]),
+        maker: Address::generate(&env),
+        taker: Address::generate(&env),
+        token: Address::generate(&env),
+        amount: 100,
+        safety_deposit: 10,
+    };
+
+    for (escrow_type, side) in [(EscrowType::Source, SRC), (EscrowType::Destination, DST)] {
+        let event = created(escrow_type);
+        env.as_contract(&publisher, || event.clone().publish(&env));
+
+        let (_, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(topics, (ESCROW_CREATED, side).into_val(&env));
+        assert_eq!(EscrowCreated::try_from_val(&env, &data).unwrap(), event);
+    }
+}
+
+#[test]
+fn test_withdrawn_is_indexed_by_hashlock() {
+    let env = Env::default();
+    let publisher = env.register(Publisher, ());
+    let event = Withdrawn {
+        hashlock: BytesN::from_array(&env, &[2; 32]),
+        secret: BytesN::from_array(&env, &[3; 32]),
+        recipient: Address::generate(&env),
+        token: Address::generate(&env),
+        amount: 100,
+    };
+
+    env.as_contract(&publisher, || event.clone().publish(&env));
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (WITHDRAWN, event.hashlock.clone()).into_val(&env));
+    assert_eq!(Withdrawn::try_from_val(&env, &data).unwrap(), event);
+}
@@ -1,4 +1,4 @@
-use soroban_sdk::{Bytes, BytesN, Env, Vec};
+use soroban_sdk::{Bytes, BytesN, Env, Vec, U256};
 
 pub fn concat_bytes(env: &Env, a: BytesN<32>, b: BytesN<32>) -> BytesN<32> {
     let mut combined = Bytes::new(env);
@@ -25,6 +25,115 @@ pub fn process_proof(env: &Env, proof: &Vec<BytesN<32>>, leaf: BytesN<32>) -> By
     computed_hash
 }
 
+/// Leaf of the secret of index `idx`: keccak256(idx || secret_hash), the Solidity
+/// `keccak256(abi.encodePacked(uint64(idx), secretHash))`. Binding the index into the leaf
+/// makes a proof valid for that index only.
+pub fn hash_leaf(env: &Env, idx: u64, secret_hash: &BytesN<32>) -> BytesN<32> {
+    let mut leaf = Bytes::from_array(env, &idx.to_be_bytes());
+    leaf.extend_from_array(&secret_hash.to_array());
+    env.crypto().keccak256(&leaf).into()
+}
+
+/// Root of the tree holding the secret of index `idx`, for a proof of its leaf.
+/// Indexes that do not fit in a uint64 belong to no leaf.
+pub fn process_secret_proof(
+    env: &Env,
+    proof: &Vec<BytesN<32>>,
+    idx: &U256,
+    secret_hash: &BytesN<32>,
+) -> BytesN<32> {
+    process_proof(env, proof, hash_leaf(env, secret_index(idx), secret_hash))
+}
+
+/// Root of the tree holding the secrets of indexes `idxs`, for a multiproof of their leaves,
+/// listed in the same order as `idxs`. See `process_multi_proof`.
+pub fn process_secret_multi_proof(
+    env: &Env,
+    proof: &Vec<BytesN<32>>,
+    proof_flags: &Vec<bool>,
+    idxs: &Vec<U256>,
+    secret_hashes: &Vec<BytesN<32>>,
+) -> BytesN<32> {
+    if idxs.len() != secret_hashes.len() {
+        panic!("InvalidMultiproof");
+    }
+    let mut leaves = Vec::new(env);
+    for (idx, secret_hash) in idxs.iter().zip(secret_hashes.iter()) {
+        leaves.push_back(hash_leaf(env, secret_index(&idx), &secret_hash));
+    }
+    process_multi_proof(env, proof, proof_flags, &leaves)
+}
+
+fn secret_index(idx: &U256) -> u64 {
+    idx.to_u128()
+        .and_then(|idx| idx.try_into().ok())
+        .unwrap_or_else(|| panic!("InvalidIndex"))
+}
+
+/**
+ * Port of OpenZeppelin MerkleProof.processMultiProof(): rebuilds the root from several leaves
+ * at once. Each flag tells whether the next hash pairs two already known nodes (leaves, then
+ * computed hashes) or a known node with the next proof element.
+ * Leaves are in the order the OpenZeppelin merkle-tree library lists them in a multiproof.
+ *
+ * @param proof The sibling nodes not computed from the leaves.
+ * @param proof_flags One flag per hash computed on the way to the root.
+ * @param leaves The leaves to prove.
+ * @return BytesN<32> The root of the tree.
+ */
+pub fn process_multi_proof(
+    env: &Env,
+    proof: &Vec<BytesN<32>>,
+    proof_flags: &Vec<bool>,
+    leaves: &Vec<BytesN<32>>,
+) -> BytesN<32> {
+    let leaves_len = leaves.len();
+    let proof_flags_len = proof_flags.len();
+    if leaves_len + proof.len() != proof_flags_len + 1 {
+        panic!("InvalidMultiproof");
+    }
+
+    let mut hashes: Vec<BytesN<32>> = Vec::new(env);
+    let mut leaf_pos = 0;
+    let mut hash_pos = 0;
+    let mut proof_pos = 0;
+    let next_node = |leaf_pos: &mut u32, hash_pos: &mut u32, hashes: &Vec<BytesN<32>>| {
+        if *leaf_pos < leaves_len {
+            *leaf_pos += 1;
+            leaves.get(*leaf_pos - 1).unwrap()
+        } else {
+            // Only hashes already computed, OpenZeppelin would read an unset zero
+            *hash_pos += 1;
+            hashes
+                .get(*hash_pos - 1)
+                .unwrap_or_else(|| panic!("InvalidMultiproof"))
+        }
+    };
+    for flag in proof_flags.iter() {
+        let a = next_node(&mut leaf_pos, &mut hash_pos, &hashes);
+        let b = if flag {
+            next_node(&mut leaf_pos, &mut hash_pos, &hashes)
+        } else {
+            proof_pos += 1;
+            proof
+                .get(proof_pos - 1)
+                .unwrap_or_else(|| panic!("InvalidMultiproof"))
+        };
+        hashes.push_back(commutative_keccak256(env, a, b));
+    }
+
+    if proof_flags_len > 0 {
+        if proof_pos != proof.len() {
+            panic!("InvalidMultiproof");
+        }
+        hashes.last().unwrap()
+    } else if leaves_len > 0 {
+        leaves.get(0).unwrap()
+    } else {
+        proof.get(0).unwrap()
+    }
+}
+
 pub struct MerkleProof {
     pub root: BytesN<32>,
     pub leaf: BytesN<32>,
@@ -34,4 +143,26 @@ impl MerkleProof {
     pub fn verify(env: &Env, proof: &Vec<BytesN<32>>, root: BytesN<32>, leaf: BytesN<32>) -> bool {
         process_proof(env, proof, leaf) == root
     }
+
+    /// Checks that `secret_hash` is the secret of index `idx` in the tree of `root`.
+    pub fn verify_secret(
+        env: &Env,
+        proof: &Vec<BytesN<32>>,
+        root: BytesN<32>,
+        idx: &U256,
+        secret_hash: &BytesN<32>,
+    ) -> bool {
+        process_secret_proof(env, proof, idx, secret_hash) == root
+    }
+
+    /// Checks that all of `leaves` are in the tree of `root`, see `process_multi_proof`.
+    pub fn multi_proof_verify(
+        env: &Env,
+        proof: &Vec<BytesN<32>>,
+        proof_flags: &Vec<bool>,
+        root: BytesN<32>,
+        leaves: &Vec<BytesN<32>>,
+    ) -> bool {
+        process_multi_proof(env, proof, proof_flags, leaves) == root
+    }
 }
@@ -1,9 +1,24 @@
-use super::merkle_proof::process_proof;
+use super::merkle_proof::{process_secret_multi_proof, process_secret_proof};
+use escrow_factory_interface::ExtraDataArgs;
+use order_interface::{Order, TakerInteractionInterface};
+use order_lib::xlm_orders::{get_extension, DynamicField};
 use soroban_sdk::{
-    contract, contractimpl, contracttype, log, symbol_short, Bytes, BytesN, Env, Symbol, Vec, U256,
+    contract, contractimpl, contracttype, xdr::FromXdr, Address, Bytes, BytesN, Env, Map, Symbol,
+    TryFromVal, Val, Vec, U256,
 };
+use utils::{
+    protocol::{require_limit_order_protocol, set_limit_order_protocol},
+    ttl::{StoredTtlPolicy, TtlPolicy},
+};
+
+// 1inch Solidity version:
+// https://github.com/1inch/cross-chain-swap/blob/master/contracts/MerkleStorageInvalidator.sol
 
-pub const LAST_VALIDATED: Symbol = symbol_short!("VALIDATED");
+/// TTL policy of the validation data, which lives in persistent storage
+pub const STORAGE_TTL: StoredTtlPolicy = StoredTtlPolicy;
+
+/// Bytes of the hashlock info holding the shortened merkle root
+const ROOT_SHORTENED_LEN: u32 = 30;
 
 #[contract]
 pub struct MerkleStorageInvalidatorContract;
@@ -11,10 +26,12 @@ pub struct MerkleStorageInvalidatorContract;
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ValidationData {
+    /// Index of the last validated secret, plus one so that zero means none
     pub index: U256, // must be uint256
     pub leaf: BytesN<32>,
 }
 
+/// Proof of a secret, XDR encoded in the extra data of the taker interaction
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TakerData {
@@ -23,46 +40,172 @@ pub struct TakerData {
     pub secret_hash: BytesN<32>,
 }
 
+/// Multiproof of several secrets, for a fill crossing several parts at once. XDR encoded in
+/// the extra data of the taker interaction in place of a `TakerData`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MultiTakerData {
+    pub proof: Vec<BytesN<32>>,
+    pub proof_flags: Vec<bool>,
+    /// Indexes of the secrets, in the order of their leaves in the multiproof
+    pub idxs: Vec<U256>,
+    pub secret_hashes: Vec<BytesN<32>>,
+}
+
 #[contractimpl]
 impl MerkleStorageInvalidatorContract {
-    /// Set validation data for a given key (equivalent to mapping assignment)
-    pub fn set_last_validated(
-        env: Env,
-        key: Symbol,
-        validation_data: ValidationData,
-    ) -> ValidationData {
-        env.storage().persistent().set(&key, &validation_data);
+    pub fn __constructor(env: Env, limit_order_protocol: Address, ttl_policy: Option<TtlPolicy>) {
+        STORAGE_TTL.init(&env, &ttl_policy);
+        set_limit_order_protocol(&env, &limit_order_protocol);
+    }
 
-        env.storage().persistent().extend_ttl(&key, 100, 1000);
-        validation_data
+    /// Last secret validated for the order and merkle root behind `key`, see `last_validated_key`.
+    pub fn get_last_validated(env: Env, key: BytesN<32>) -> Option<ValidationData> {
+        env.storage().persistent().get(&key)
     }
 
-    /// Get validation data for a given key (equivalent to mapping access)
-    pub fn get_last_validated(env: Env, key: Symbol) -> Option<ValidationData> {
-        env.storage().persistent().get(&key).unwrap_or(None)
+    /// Extends the TTL of the contract instance and of the validation data under `keys`, so it
+    /// is not archived before the escrows of the order are deployed. Missing keys are skipped.
+    pub fn extend_ttl(env: Env, keys: Vec<BytesN<32>>) {
+        STORAGE_TTL.extend_instance(&env);
+        for key in keys.iter() {
+            STORAGE_TTL.extend(&env, &key);
+        }
     }
+}
 
-    pub fn taker_interaction(
+/// Validates the secret a taker fills a part of a multiple-fill order with, before the escrow
+/// factory builds the source escrow of that part with it.
+#[contractimpl]
+impl TakerInteractionInterface for MerkleStorageInvalidatorContract {
+    fn taker_interaction(
         env: Env,
+        _order: Order,
+        extension: Bytes,
         order_hash: BytesN<32>,
-        // already extracted from extraDataArgs
-        root_shortened: BytesN<32>, // diferent from MerkleStorageInvalidator.sol
-        taker_data: TakerData,
-    ) -> BytesN<32> {
-        let root_calculated = process_proof(&env, &taker_data.proof, taker_data.secret_hash);
+        _taker: Address,
+        _making_amount: U256,
+        _taking_amount: U256,
+        _remaining_making_amount: U256,
+        extra_data: Bytes,
+    ) {
+        require_limit_order_protocol(&env);
 
-        if root_calculated != root_shortened {
+        // The escrow factory contract id, followed by its arguments
+        let post_interaction = get_extension(&env, &extension, DynamicField::PostInteractionData);
+        if post_interaction.len() < 32 {
+            panic!("InvalidExtraData");
+        }
+        let args = ExtraDataArgs::from_xdr(&env, &post_interaction.slice(32..))
+            .unwrap_or_else(|_| panic!("InvalidExtraData"));
+        let proven = ProvenSecrets::from_extra_data(&env, &extra_data);
+        if shorten_root(&env, &proven.root) != shorten_root(&env, &args.hashlock_info) {
             panic!("Invalid proof");
         }
 
-        // Create storage key by hashing order_hash and root_shortened
-        let mut combined = Bytes::new(&env);
-        combined.extend_from_array(&order_hash.to_array());
-        combined.extend_from_array(&root_shortened.to_array());
+        // Secrets are used in order, each fill needs later ones than the previous fill and is
+        // locked with the last secret it proves
+        let key = last_validated_key(&env, &order_hash, &args.hashlock_info);
+        let one = U256::from_u32(&env, 1);
+        if let Some(last_validated) = env.storage().persistent().get::<_, ValidationData>(&key) {
+            if proven.lowest_idx.add(&one) <= last_validated.index {
+                panic!("InvalidIndex");
+            }
+        }
+        STORAGE_TTL.set(
+            &env,
+            &key,
+            &ValidationData {
+                index: proven.highest_idx.add(&one),
+                leaf: proven.secret_hash,
+            },
+        );
+    }
+}
 
-        let key = env.crypto().keccak256(&combined).into();
+/// Secrets proven by the extra data of a taker interaction
+struct ProvenSecrets {
+    root: BytesN<32>,
+    lowest_idx: U256,
+    highest_idx: U256,
+    /// Secret hash of the highest index
+    secret_hash: BytesN<32>,
+}
 
-        log!(&env, "key: {:?}", key);
-        key
+impl ProvenSecrets {
+    /// Decodes a `TakerData`, or a `MultiTakerData`, told apart by its `proof_flags` field,
+    /// and rebuilds the root its proof leads to. Leaves bind each secret to its index, so the
+    /// proof also proves the indexes.
+    fn from_extra_data(env: &Env, extra_data: &Bytes) -> Self {
+        let fields = Map::<Symbol, Val>::from_xdr(env, extra_data)
+            .unwrap_or_else(|_| panic!("InvalidExtraData"));
+        if !fields.contains_key(Symbol::new(env, "proof_flags")) {
+            let taker_data = TakerData::try_from_val(env, &fields.to_val())
+                .unwrap_or_else(|_| panic!("InvalidExtraData"));
+            return ProvenSecrets {
+                root: process_secret_proof(
+                    env,
+                    &taker_data.proof,
+                    &taker_data.idx,
+                    &taker_data.secret_hash,
+                ),
+                lowest_idx: taker_data.idx.clone(),
+                highest_idx: taker_data.idx,
+                secret_hash: taker_data.secret_hash,
+            };
+        }
+
+        let taker_data = MultiTakerData::try_from_val(env, &fields.to_val())
+            .unwrap_or_else(|_| panic!("InvalidExtraData"));
+        let (Some(first_idx), Some(first_secret_hash)) =
+            (taker_data.idxs.first(), taker_data.secret_hashes.first())
+        else {
+            panic!("InvalidExtraData");
+        };
+        let mut proven = ProvenSecrets {
+            root: process_secret_multi_proof(
+                env,
+                &taker_data.proof,
+                &taker_data.proof_flags,
+                &taker_data.idxs,
+                &taker_data.secret_hashes,
+            ),
+            lowest_idx: first_idx.clone(),
+            highest_idx: first_idx,
+            secret_hash: first_secret_hash,
+        };
+        for (idx, secret_hash) in taker_data.idxs.iter().zip(taker_data.secret_hashes.iter()) {
+            if idx < proven.lowest_idx {
+                proven.lowest_idx = idx.clone();
+            }
+            if idx > proven.highest_idx {
+                proven.highest_idx = idx;
+                proven.secret_hash = secret_hash;
+            }
+        }
+        proven
     }
 }
+
+/// Key of the validation data of an order: keccak256(order_hash || root_shortened), the
+/// Solidity `keccak256(abi.encodePacked(orderHash, uint240(hashlockInfo)))`.
+pub fn last_validated_key(
+    env: &Env,
+    order_hash: &BytesN<32>,
+    hashlock_info: &BytesN<32>,
+) -> BytesN<32> {
+    let mut combined = Bytes::from_array(env, &order_hash.to_array());
+    combined.append(&shorten_root(env, hashlock_info));
+    env.crypto().keccak256(&combined).into()
+}
+
+/// Number of parts a multiple-fill order is split in, held in the 2 high bytes of its hashlock info.
+pub fn parts_amount(hashlock_info: &BytesN<32>) -> u16 {
+    let hashlock_info = hashlock_info.to_array();
+    u16::from_be_bytes([hashlock_info[0], hashlock_info[1]])
+}
+
+/// The low 30 bytes of `root`.
+fn shorten_root(env: &Env, root: &BytesN<32>) -> Bytes {
+    Bytes::from_array(env, &root.to_array()).slice(32 - ROOT_SHORTENED_LEN..)
+}
@@ -1,11 +1,22 @@
 #![cfg(test)]
 
-use crate::merkle_proof::{commutative_keccak256, concat_bytes, process_proof, MerkleProof};
+use crate::merkle_proof::{
+    commutative_keccak256, concat_bytes, hash_leaf, process_multi_proof, process_proof,
+    process_secret_multi_proof, process_secret_proof, MerkleProof,
+};
 use crate::merkle_storage_invalidator::{
-    MerkleStorageInvalidatorContract, MerkleStorageInvalidatorContractClient, TakerData,
-    ValidationData, LAST_VALIDATED,
+    last_validated_key, parts_amount, MerkleStorageInvalidatorContract,
+    MerkleStorageInvalidatorContractClient, MultiTakerData, TakerData, ValidationData,
+};
+use escrow_factory_interface::ExtraDataArgs;
+use order_interface::Order;
+use soroban_sdk::{
+    testutils::{storage::Persistent, Address as _, Ledger},
+    vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, U256,
 };
-use soroban_sdk::{symbol_short, vec, BytesN, Env, U256};
+use utils::ttl::{TtlPolicy, DEFAULT_POLICY};
 
 #[test]
 fn test_merkle_proof_verify_valid() {
@@ -107,76 +118,277 @@ fn test_commutative_keccak256_same_values() {
     assert_eq!(result, expected);
 }
 
+/// Invalidator contract, with a new address standing for the limit order protocol
+fn create_invalidator(env: &Env) -> MerkleStorageInvalidatorContractClient<'_> {
+    let limit_order_protocol = Address::generate(env);
+    let contract_id = env.register(
+        MerkleStorageInvalidatorContract,
+        (&limit_order_protocol, None::<TtlPolicy>),
+    );
+    MerkleStorageInvalidatorContractClient::new(env, &contract_id)
+}
+
+/// Number of parts in the 2 high bytes, the low 30 bytes of the root after them
+fn hashlock_info(env: &Env, parts_amount: u16, root: &BytesN<32>) -> BytesN<32> {
+    let mut hashlock_info = root.to_array();
+    hashlock_info[..2].copy_from_slice(&parts_amount.to_be_bytes());
+    BytesN::from_array(env, &hashlock_info)
+}
+
+/// Extension whose only field is the escrow factory post interaction, carrying `hashlock_info`
+fn escrow_extension(env: &Env, hashlock_info: &BytesN<32>) -> Bytes {
+    let mut post_interaction = Bytes::from_array(env, &[0xfa; 32]);
+    post_interaction.append(
+        &ExtraDataArgs {
+            hashlock_info: hashlock_info.clone(),
+            safety_deposit: 10,
+            timelocks: U256::from_u32(env, 0),
+        }
+        .to_xdr(env),
+    );
+
+    // Offsets word: end of PostInteractionData in bits 224..256, the other fields are empty
+    let mut extension = Bytes::from_array(env, &post_interaction.len().to_be_bytes());
+    extension.extend_from_array(&[0; 28]);
+    extension.append(&post_interaction);
+    extension
+}
+
+fn test_order(env: &Env) -> Order {
+    Order {
+        salt: U256::from_u32(env, 0),
+        maker: Address::generate(env),
+        maker_evm_address: None,
+        receiver: Address::generate(env),
+        maker_asset: Address::generate(env),
+        taker_asset: Address::generate(env),
+        making_amount: U256::from_u32(env, 100),
+        taking_amount: U256::from_u32(env, 100),
+        maker_traits: U256::from_u32(env, 0),
+    }
+}
+
+/// Root of the tree of two secrets
+fn secret_root(env: &Env, secret_hashes: &[BytesN<32>; 2]) -> BytesN<32> {
+    commutative_keccak256(
+        env,
+        hash_leaf(env, 0, &secret_hashes[0]),
+        hash_leaf(env, 1, &secret_hashes[1]),
+    )
+}
+
+/// Validates the secret of index `idx` of a tree of two secrets
+fn validate(
+    client: &MerkleStorageInvalidatorContractClient,
+    order_hash: &BytesN<32>,
+    secret_hashes: &[BytesN<32>; 2],
+    idx: u32,
+) {
+    let env = &client.env;
+    let root = secret_root(env, secret_hashes);
+    let other = 1 - idx;
+    let taker_data = TakerData {
+        proof: vec![env, hash_leaf(env, other.into(), &secret_hashes[other as usize])],
+        idx: U256::from_u32(env, idx),
+        secret_hash: secret_hashes[idx as usize].clone(),
+    };
+    let zero = U256::from_u32(env, 0);
+    client.taker_interaction(
+        &test_order(env),
+        &escrow_extension(env, &hashlock_info(env, 2, &root)),
+        order_hash,
+        &Address::generate(env),
+        &zero,
+        &zero,
+        &zero,
+        &taker_data.to_xdr(env),
+    );
+}
+
+fn secret_hashes(env: &Env) -> [BytesN<32>; 2] {
+    [
+        BytesN::from_array(env, &[1u8; 32]),
+        BytesN::from_array(env, &[2u8; 32]),
+    ]
+}
+
+fn validated_key(env: &Env, order_hash: &BytesN<32>, secret_hashes: &[BytesN<32>; 2]) -> BytesN<32> {
+    let root = secret_root(env, secret_hashes);
+    last_validated_key(env, order_hash, &hashlock_info(env, 2, &root))
+}
+
 #[test]
-fn test_merkle_storage_invalidator() {
+fn test_last_validated_key_matches_solidity_encoding() {
     let env = Env::default();
-    let contract_id = env.register(MerkleStorageInvalidatorContract, ());
-    let client = MerkleStorageInvalidatorContractClient::new(&env, &contract_id);
+    let order_hash = BytesN::from_array(&env, &[10u8; 32]);
+    let hashlock_info = hashlock_info(&env, 4, &BytesN::from_array(&env, &[0xab; 32]));
 
-    assert_eq!(client.get_last_validated(&LAST_VALIDATED), None);
+    // abi.encodePacked(bytes32 orderHash, uint240 rootShortened)
+    let mut packed = Bytes::from_array(&env, &[10u8; 32]);
+    packed.extend_from_array(&[0xab; 30]);
+    let expected: BytesN<32> = env.crypto().keccak256(&packed).into();
 
-    let validation_data = ValidationData {
-        index: U256::from_u32(&env, 1),
-        leaf: BytesN::from_array(&env, &[0u8; 32]),
-    };
+    assert_eq!(last_validated_key(&env, &order_hash, &hashlock_info), expected);
+    assert_eq!(parts_amount(&hashlock_info), 4);
+}
+
+#[test]
+fn test_taker_interaction_records_validation_data() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
+    let order_hash = BytesN::from_array(&env, &[10u8; 32]);
+    let secret_hashes = secret_hashes(&env);
+    let key = validated_key(&env, &order_hash, &secret_hashes);
+
+    assert_eq!(client.get_last_validated(&key), None);
+
+    validate(&client, &order_hash, &secret_hashes, 0);
+    assert_eq!(
+        client.get_last_validated(&key),
+        Some(ValidationData {
+            index: U256::from_u32(&env, 1),
+            leaf: secret_hashes[0].clone(),
+        })
+    );
 
-    client.set_last_validated(&LAST_VALIDATED, &validation_data);
+    validate(&client, &order_hash, &secret_hashes, 1);
+    assert_eq!(
+        client.get_last_validated(&key),
+        Some(ValidationData {
+            index: U256::from_u32(&env, 2),
+            leaf: secret_hashes[1].clone(),
+        })
+    );
 
+    // Other orders with the same secrets are tracked apart
+    let other_order_hash = BytesN::from_array(&env, &[11u8; 32]);
     assert_eq!(
-        client.get_last_validated(&LAST_VALIDATED),
-        Some(validation_data)
+        client.get_last_validated(&validated_key(&env, &other_order_hash, &secret_hashes)),
+        None
     );
 }
 
 #[test]
-fn test_taker_interaction_valid() {
+#[should_panic(expected = "InvalidIndex")]
+fn test_taker_interaction_rejects_reused_index() {
     let env = Env::default();
-    let contract_id = env.register(MerkleStorageInvalidatorContract, ());
-    let client = MerkleStorageInvalidatorContractClient::new(&env, &contract_id);
-
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
     let order_hash = BytesN::from_array(&env, &[10u8; 32]);
-    let secret_hash = BytesN::from_array(&env, &[1u8; 32]);
-    let proof_element = BytesN::from_array(&env, &[2u8; 32]);
-    let proof = vec![&env, proof_element.clone()];
+    let secret_hashes = secret_hashes(&env);
 
-    // Calculate the correct root
-    let root = commutative_keccak256(&env, secret_hash.clone(), proof_element);
+    validate(&client, &order_hash, &secret_hashes, 0);
+    validate(&client, &order_hash, &secret_hashes, 0);
+}
 
-    let taker_data = TakerData {
-        proof,
-        idx: U256::from_u32(&env, 42),
-        secret_hash: secret_hash.clone(),
-    };
+#[test]
+#[should_panic(expected = "InvalidIndex")]
+fn test_taker_interaction_rejects_earlier_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
+    let order_hash = BytesN::from_array(&env, &[10u8; 32]);
+    let secret_hashes = secret_hashes(&env);
+
+    validate(&client, &order_hash, &secret_hashes, 1);
+    validate(&client, &order_hash, &secret_hashes, 0);
+}
 
-    let storage_key = client.taker_interaction(&order_hash, &root, &taker_data);
+#[test]
+#[should_panic]
+fn test_taker_interaction_only_from_protocol() {
+    let env = Env::default();
+    let client = create_invalidator(&env);
 
-    // Verify the storage key was created
-    assert!(storage_key.len() == 32);
+    validate(
+        &client,
+        &BytesN::from_array(&env, &[10u8; 32]),
+        &secret_hashes(&env),
+        0,
+    );
 }
 
 #[test]
 #[should_panic(expected = "Invalid proof")]
 fn test_taker_interaction_invalid_proof() {
     let env = Env::default();
-    let contract_id = env.register(MerkleStorageInvalidatorContract, ());
-    let client = MerkleStorageInvalidatorContractClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
 
-    let order_hash = BytesN::from_array(&env, &[10u8; 32]);
-    let secret_hash = BytesN::from_array(&env, &[1u8; 32]);
-    let proof_element = BytesN::from_array(&env, &[2u8; 32]);
-    let proof = vec![&env, proof_element.clone()];
+    // Proof for a secret outside the tree of the order
+    let taker_data = TakerData {
+        proof: vec![&env, BytesN::from_array(&env, &[2u8; 32])],
+        idx: U256::from_u32(&env, 0),
+        secret_hash: BytesN::from_array(&env, &[3u8; 32]),
+    };
+    let root = commutative_keccak256(
+        &env,
+        BytesN::from_array(&env, &[1u8; 32]),
+        BytesN::from_array(&env, &[2u8; 32]),
+    );
+    let zero = U256::from_u32(&env, 0);
+    client.taker_interaction(
+        &test_order(&env),
+        &escrow_extension(&env, &hashlock_info(&env, 2, &root)),
+        &BytesN::from_array(&env, &[10u8; 32]),
+        &Address::generate(&env),
+        &zero,
+        &zero,
+        &zero,
+        &taker_data.to_xdr(&env),
+    );
+}
 
-    // Use wrong root
-    let wrong_root = BytesN::from_array(&env, &[255u8; 32]);
+#[test]
+#[should_panic(expected = "Invalid proof")]
+fn test_taker_interaction_rejects_proof_of_other_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
+    let secret_hashes = secret_hashes(&env);
 
+    // Valid proof of the first secret, claimed for the second index
     let taker_data = TakerData {
-        proof,
-        idx: U256::from_u32(&env, 42),
-        secret_hash,
+        proof: vec![&env, hash_leaf(&env, 1, &secret_hashes[1])],
+        idx: U256::from_u32(&env, 1),
+        secret_hash: secret_hashes[0].clone(),
     };
+    let zero = U256::from_u32(&env, 0);
+    client.taker_interaction(
+        &test_order(&env),
+        &escrow_extension(&env, &hashlock_info(&env, 2, &secret_root(&env, &secret_hashes))),
+        &BytesN::from_array(&env, &[10u8; 32]),
+        &Address::generate(&env),
+        &zero,
+        &zero,
+        &zero,
+        &taker_data.to_xdr(&env),
+    );
+}
+
+#[test]
+fn test_extend_ttl_keeps_validation_data_alive() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
+    let order_hash = BytesN::from_array(&env, &[10u8; 32]);
+    let secret_hashes = secret_hashes(&env);
+    let key = validated_key(&env, &order_hash, &secret_hashes);
+
+    validate(&client, &order_hash, &secret_hashes, 0);
+    client.extend_ttl(&vec![&env]);
+
+    let ttl = || env.as_contract(&client.address, || env.storage().persistent().get_ttl(&key));
+    assert_eq!(ttl(), DEFAULT_POLICY.extend_to);
 
-    // This should panic with "Invalid proof"
-    client.taker_interaction(&order_hash, &wrong_root, &taker_data);
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number += DEFAULT_POLICY.extend_to - DEFAULT_POLICY.threshold + 1;
+    });
+    client.extend_ttl(&vec![&env, key.clone(), BytesN::from_array(&env, &[0u8; 32])]);
+
+    assert_eq!(ttl(), DEFAULT_POLICY.extend_to);
+    assert_eq!(client.get_last_validated(&key).unwrap().leaf, secret_hashes[0]);
 }
 
 #[test]
@@ -221,36 +433,6 @@ fn test_taker_data_creation() {
     assert_eq!(taker_data.secret_hash, secret_hash);
 }
 
-#[test]
-fn test_storage_persistence() {
-    let env = Env::default();
-    let contract_id = env.register(MerkleStorageInvalidatorContract, ());
-    let client = MerkleStorageInvalidatorContractClient::new(&env, &contract_id);
-
-    let key1 = symbol_short!("KEY1");
-    let key2 = symbol_short!("KEY2");
-
-    let data1 = ValidationData {
-        index: U256::from_u32(&env, 1),
-        leaf: BytesN::from_array(&env, &[1u8; 32]),
-    };
-
-    let data2 = ValidationData {
-        index: U256::from_u32(&env, 2),
-        leaf: BytesN::from_array(&env, &[2u8; 32]),
-    };
-
-    client.set_last_validated(&key1, &data1);
-    client.set_last_validated(&key2, &data2);
-
-    assert_eq!(client.get_last_validated(&key1), Some(data1));
-    assert_eq!(client.get_last_validated(&key2), Some(data2));
-
-    // Verify non-existent key returns None
-    let key3 = symbol_short!("KEY3");
-    assert_eq!(client.get_last_validated(&key3), None);
-}
-
 #[test]
 fn test_large_merkle_proof() {
     let env = Env::default();
@@ -343,3 +525,327 @@ fn calculate_simple_root(env: &Env, leaves: &soroban_sdk::Vec<BytesN<32>>) -> By
     
     result
 }
+
+// Tree of 4 secrets built by the Fusion+ SDK: leaves keccak256(abi.encodePacked(uint64(idx),
+// secretHash)) with secretHash = keccak256(secret) and secret idx = [idx + 1; 32], in an
+// OpenZeppelin SimpleMerkleTree. Proofs and multiproofs are the ones the tree returns.
+const SECRET_LEAVES: [[u8; 32]; 4] = [
+    hex32("bb435e03bb7b6e47dce860202a898f502e051c6daf26940273651c61d2e166f1"),
+    hex32("3a92edc6d66a1500aa2a1a26afcd7e0ec5d9962ad97aba04e2fe034d5b393ffa"),
+    hex32("909c69913f2f637bfb5496c192a47fa1acb0d4deacdbc2cd2b94f074aa767089"),
+    hex32("08b4ff79145d6da5424bbe444b93013a06eaea8251bbd725fac9a680307a1ea3"),
+];
+const SECRETS_ROOT: [u8; 32] =
+    hex32("9eb9b866821ed45e302fd86ca059f136f259081a4aae63584ef0cde155e0ba7f");
+// Parents of the leaves of index 0 and 2, and of index 1 and 3
+const LOW_PAIR: [u8; 32] =
+    hex32("bea75357ad306c2c0d7c3197cadca4df8ece2ccf93d254cbdd0dcec75770dc69");
+const HIGH_PAIR: [u8; 32] =
+    hex32("a90100d1eea6c5f7bb3db41ca0ae81467c8d81bf90bc51b6b8ccdd5f7c771dc6");
+
+const fn hex32(s: &str) -> [u8; 32] {
+    let s = s.as_bytes();
+    let mut out = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        out[i] = (nibble(s[2 * i]) << 4) | nibble(s[2 * i + 1]);
+        i += 1;
+    }
+    out
+}
+
+const fn nibble(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        _ => c - b'a' + 10,
+    }
+}
+
+fn sdk_secret_hash(env: &Env, idx: u8) -> BytesN<32> {
+    env.crypto()
+        .keccak256(&Bytes::from_array(env, &[idx + 1; 32]))
+        .into()
+}
+
+fn sdk_leaves(env: &Env, idxs: &[usize]) -> soroban_sdk::Vec<BytesN<32>> {
+    let mut leaves = soroban_sdk::Vec::new(env);
+    for idx in idxs {
+        leaves.push_back(BytesN::from_array(env, &SECRET_LEAVES[*idx]));
+    }
+    leaves
+}
+
+#[test]
+fn test_hash_leaf_matches_sdk_leaves() {
+    let env = Env::default();
+
+    for idx in 0..4u8 {
+        assert_eq!(
+            hash_leaf(&env, idx.into(), &sdk_secret_hash(&env, idx)),
+            BytesN::from_array(&env, &SECRET_LEAVES[idx as usize])
+        );
+    }
+}
+
+#[test]
+fn test_secret_proof_matches_sdk_tree() {
+    let env = Env::default();
+    let root = BytesN::from_array(&env, &SECRETS_ROOT);
+
+    // Sibling leaf, then the parent of the other pair
+    let proofs = [
+        (2, HIGH_PAIR),
+        (3, LOW_PAIR),
+        (0, HIGH_PAIR),
+        (1, LOW_PAIR),
+    ];
+    for (idx, (sibling, pair)) in proofs.into_iter().enumerate() {
+        let proof = vec![
+            &env,
+            BytesN::from_array(&env, &SECRET_LEAVES[sibling]),
+            BytesN::from_array(&env, &pair),
+        ];
+        let secret_hash = sdk_secret_hash(&env, idx as u8);
+        assert_eq!(
+            process_secret_proof(&env, &proof, &U256::from_u32(&env, idx as u32), &secret_hash),
+            root
+        );
+        assert!(MerkleProof::verify_secret(
+            &env,
+            &proof,
+            root.clone(),
+            &U256::from_u32(&env, idx as u32),
+            &secret_hash
+        ));
+    }
+}
+
+#[test]
+fn test_verify_secret_rejects_other_index() {
+    let env = Env::default();
+    let proof = vec![
+        &env,
+        BytesN::from_array(&env, &SECRET_LEAVES[2]),
+        BytesN::from_array(&env, &HIGH_PAIR),
+    ];
+
+    // Proof of the first secret, claimed for every other index
+    for idx in 1..4 {
+        assert!(!MerkleProof::verify_secret(
+            &env,
+            &proof,
+            BytesN::from_array(&env, &SECRETS_ROOT),
+            &U256::from_u32(&env, idx),
+            &sdk_secret_hash(&env, 0)
+        ));
+    }
+}
+
+#[test]
+#[should_panic(expected = "InvalidIndex")]
+fn test_secret_proof_rejects_index_beyond_uint64() {
+    let env = Env::default();
+
+    process_secret_proof(
+        &env,
+        &vec![&env],
+        &U256::from_u128(&env, u64::MAX as u128 + 1),
+        &sdk_secret_hash(&env, 0),
+    );
+}
+
+#[test]
+fn test_multi_proof_matches_sdk_tree() {
+    let env = Env::default();
+    let root = BytesN::from_array(&env, &SECRETS_ROOT);
+
+    // (leaves, proof, proof flags) as getMultiProof returns them
+    let multi_proofs = [
+        (&[1, 2][..], &[3, 0][..], &[false, false, true][..]),
+        (&[3, 0][..], &[1, 2][..], &[false, false, true][..]),
+        (&[3, 1, 2, 0][..], &[][..], &[true, true, true][..]),
+    ];
+    for (leaves, proof, proof_flags) in multi_proofs {
+        let leaves = sdk_leaves(&env, leaves);
+        let proof = sdk_leaves(&env, proof);
+        let mut flags = soroban_sdk::Vec::new(&env);
+        for flag in proof_flags {
+            flags.push_back(*flag);
+        }
+
+        assert_eq!(process_multi_proof(&env, &proof, &flags, &leaves), root);
+        assert!(MerkleProof::multi_proof_verify(
+            &env,
+            &proof,
+            &flags,
+            root.clone(),
+            &leaves
+        ));
+    }
+}
+
+#[test]
+fn test_multi_proof_single_leaf() {
+    let env = Env::default();
+    let leaves = sdk_leaves(&env, &[0]);
+
+    assert_eq!(
+        process_multi_proof(&env, &vec![&env], &vec![&env], &leaves),
+        leaves.get(0).unwrap()
+    );
+}
+
+#[test]
+fn test_multi_proof_rejects_wrong_leaf() {
+    let env = Env::default();
+    let flags = vec![&env, false, false, true];
+
+    // Leaves of index 1 and 3 with the proof of 1 and 2
+    assert!(!MerkleProof::multi_proof_verify(
+        &env,
+        &sdk_leaves(&env, &[3, 0]),
+        &flags,
+        BytesN::from_array(&env, &SECRETS_ROOT),
+        &sdk_leaves(&env, &[1, 3])
+    ));
+}
+
+#[test]
+#[should_panic(expected = "InvalidMultiproof")]
+fn test_multi_proof_rejects_flags_mismatch() {
+    let env = Env::default();
+
+    // One flag short of the 3 hashes up to the root
+    process_multi_proof(
+        &env,
+        &sdk_leaves(&env, &[3, 0]),
+        &vec![&env, false, false],
+        &sdk_leaves(&env, &[1, 2]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "InvalidMultiproof")]
+fn test_multi_proof_rejects_unused_proof() {
+    let env = Env::default();
+
+    // As many proof elements as needed, but the flags consume only one of them
+    process_multi_proof(
+        &env,
+        &sdk_leaves(&env, &[3, 0]),
+        &vec![&env, false, true, true],
+        &sdk_leaves(&env, &[1, 2]),
+    );
+}
+
+/// Multiproof of the SDK secrets of indexes `idxs`, with the leaves of indexes `proof`
+fn sdk_multi_taker_data(env: &Env, idxs: &[u8], proof: &[usize]) -> MultiTakerData {
+    let mut taker_data = MultiTakerData {
+        proof: sdk_leaves(env, proof),
+        proof_flags: vec![env, false, false, true],
+        idxs: vec![env],
+        secret_hashes: vec![env],
+    };
+    for idx in idxs {
+        taker_data.idxs.push_back(U256::from_u32(env, *idx as u32));
+        taker_data
+            .secret_hashes
+            .push_back(sdk_secret_hash(env, *idx));
+    }
+    taker_data
+}
+
+fn validate_multi(
+    client: &MerkleStorageInvalidatorContractClient,
+    order_hash: &BytesN<32>,
+    taker_data: &MultiTakerData,
+) {
+    let env = &client.env;
+    let root = BytesN::from_array(env, &SECRETS_ROOT);
+    let zero = U256::from_u32(env, 0);
+    client.taker_interaction(
+        &test_order(env),
+        &escrow_extension(env, &hashlock_info(env, 4, &root)),
+        order_hash,
+        &Address::generate(env),
+        &zero,
+        &zero,
+        &zero,
+        &taker_data.clone().to_xdr(env),
+    );
+}
+
+#[test]
+fn test_secret_multi_proof_matches_sdk_tree() {
+    let env = Env::default();
+    let taker_data = sdk_multi_taker_data(&env, &[1, 2], &[3, 0]);
+
+    assert_eq!(
+        process_secret_multi_proof(
+            &env,
+            &taker_data.proof,
+            &taker_data.proof_flags,
+            &taker_data.idxs,
+            &taker_data.secret_hashes
+        ),
+        BytesN::from_array(&env, &SECRETS_ROOT)
+    );
+}
+
+#[test]
+fn test_taker_interaction_records_highest_index_of_multiproof() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
+    let order_hash = BytesN::from_array(&env, &[10u8; 32]);
+    let root = BytesN::from_array(&env, &SECRETS_ROOT);
+    let key = last_validated_key(&env, &order_hash, &hashlock_info(&env, 4, &root));
+
+    // Leaves listed by tree position, the highest index comes first
+    validate_multi(
+        &client,
+        &order_hash,
+        &sdk_multi_taker_data(&env, &[3, 0], &[1, 2]),
+    );
+    assert_eq!(
+        client.get_last_validated(&key),
+        Some(ValidationData {
+            index: U256::from_u32(&env, 4),
+            leaf: sdk_secret_hash(&env, 3),
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "InvalidIndex")]
+fn test_taker_interaction_rejects_multiproof_of_used_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
+    let order_hash = BytesN::from_array(&env, &[10u8; 32]);
+
+    validate_multi(
+        &client,
+        &order_hash,
+        &sdk_multi_taker_data(&env, &[1, 2], &[3, 0]),
+    );
+    // The secret of index 3 is new, but not the one of index 0
+    validate_multi(
+        &client,
+        &order_hash,
+        &sdk_multi_taker_data(&env, &[3, 0], &[1, 2]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid proof")]
+fn test_taker_interaction_rejects_invalid_multiproof() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = create_invalidator(&env);
+
+    // Secrets of index 1 and 2 claimed for index 2 and 1
+    let mut taker_data = sdk_multi_taker_data(&env, &[1, 2], &[3, 0]);
+    taker_data.idxs = vec![&env, U256::from_u32(&env, 2), U256::from_u32(&env, 1)];
+    validate_multi(&client, &BytesN::from_array(&env, &[10u8; 32]), &taker_data);
+}
@@ -0,0 +1,35 @@
+#![no_std]
+use price_oracle_interface::{PriceData, PriceOracleInterface};
+use soroban_sdk::{contract, contractimpl, symbol_short, Env, Symbol};
+
+// STORAGE SYMBOLS
+const DECIMALS: Symbol = symbol_short!("DECIMALS");
+const PRICE: Symbol = symbol_short!("PRICE");
+
+/// Price feed with a settable price, for tests and local demos.
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn __constructor(env: Env, decimals: u32) {
+        env.storage().instance().set(&DECIMALS, &decimals);
+    }
+
+    pub fn set_price(env: Env, price: i128, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&PRICE, &PriceData { price, timestamp });
+    }
+}
+
+#[contractimpl]
+impl PriceOracleInterface for MockOracle {
+    fn decimals(env: Env) -> u32 {
+        env.storage().instance().get(&DECIMALS).unwrap()
+    }
+
+    fn lastprice(env: Env) -> Option<PriceData> {
+        env.storage().instance().get(&PRICE)
+    }
+}
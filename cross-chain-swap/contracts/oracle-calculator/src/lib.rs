@@ -0,0 +1,269 @@
+#![no_std]
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short,
+    xdr::FromXdr, Address, Bytes, BytesN, Env, Symbol, U256,
+};
+
+use dutch_auction_interface::AmountCalculatorInterface;
+use order_interface::{AuctionDetails, Order};
+use order_lib::xlm_orders::{get_extension, DynamicField};
+use price_oracle_interface::PriceOracleClient;
+
+// 1inch Solidity versions:
+// https://github.com/1inch/limit-order-protocol/blob/master/contracts/extensions/ChainlinkCalculator.sol
+// https://github.com/1inch/limit-order-protocol/blob/master/contracts/extensions/RangeAmountCalculator.sol
+
+const BPS_BASE: u32 = 10_000;
+
+// STORAGE SYMBOLS
+const MAX_STALENESS: Symbol = symbol_short!("MAX_STALE");
+const MAX_DEVIATION: Symbol = symbol_short!("MAX_DEV");
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Error {
+    InvalidRangeData = 1,
+    OrderDeadlinePassed = 2,
+    OraclePriceUnavailable = 3,
+    StaleOraclePrice = 4,
+    OracleDeviationTooHigh = 5,
+    AmountExceedsRemaining = 6,
+    OraclePriceFromFuture = 7,
+}
+
+/// Pricing parameters of an oracle order, XDR-encoded in the `MakingAmountData` field of the
+/// order extension. Both directions read them from there, `TakingAmountData` holds the fees.
+///
+/// The spread over the oracle price moves linearly from `spread_start_bps` for the
+/// first unit of the making amount to `spread_end_bps` for the last one, so each
+/// tranche is priced by how much of the order has already been filled.
+/// Equal spreads give a flat oracle-plus-spread order.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleRangeData {
+    /// Price feed the order is quoted against
+    pub oracle: Address,
+    /// Spread for the first unit filled, in basis points (10_000 = oracle price)
+    pub spread_start_bps: u32,
+    /// Spread for the last unit filled, in basis points
+    pub spread_end_bps: u32,
+    /// Timestamp after which the order can no longer be priced
+    pub deadline: u64,
+}
+
+#[contract]
+pub struct OracleRangeCalculatorContract;
+
+fn range_data(env: &Env, extension: &Bytes) -> OracleRangeData {
+    let data = get_extension(env, extension, DynamicField::MakingAmountData);
+    if data.is_empty() {
+        panic_with_error!(env, Error::InvalidRangeData);
+    }
+    let data = OracleRangeData::from_xdr(env, &data)
+        .unwrap_or_else(|_| panic_with_error!(env, Error::InvalidRangeData));
+
+    if data.spread_start_bps == 0 || data.spread_end_bps == 0 {
+        panic_with_error!(env, Error::InvalidRangeData);
+    }
+    if env.ledger().timestamp() > data.deadline {
+        panic_with_error!(env, Error::OrderDeadlinePassed);
+    }
+    data
+}
+
+/// Reads the oracle price and checks it against the staleness and deviation limits.
+/// Returns the price together with the `10^decimals` scale it is expressed in.
+fn oracle_price(env: &Env, data: &OracleRangeData, order: &Order) -> (U256, U256) {
+    let oracle = PriceOracleClient::new(env, &data.oracle);
+    let price_data = oracle
+        .lastprice()
+        .unwrap_or_else(|| panic_with_error!(env, Error::OraclePriceUnavailable));
+
+    if price_data.price <= 0 {
+        panic_with_error!(env, Error::OraclePriceUnavailable);
+    }
+
+    let now = env.ledger().timestamp();
+    if price_data.timestamp > now {
+        panic_with_error!(env, Error::OraclePriceFromFuture);
+    }
+    let max_staleness: u64 = env.storage().instance().get(&MAX_STALENESS).unwrap();
+    if now - price_data.timestamp > max_staleness {
+        panic_with_error!(env, Error::StaleOraclePrice);
+    }
+
+    let scale = U256::from_u32(env, 10).pow(oracle.decimals());
+    let price = U256::from_u128(env, price_data.price as u128);
+
+    // The maker's signed rate is the reference the oracle may not drift away from
+    let reference = order.taking_amount.mul(&scale).div(&order.making_amount);
+    let deviation = if price > reference {
+        price.sub(&reference)
+    } else {
+        reference.sub(&price)
+    };
+    let max_deviation: u32 = env.storage().instance().get(&MAX_DEVIATION).unwrap();
+    if deviation.mul(&U256::from_u32(env, BPS_BASE))
+        > reference.mul(&U256::from_u32(env, max_deviation))
+    {
+        panic_with_error!(env, Error::OracleDeviationTooHigh);
+    }
+
+    (price, scale)
+}
+
+/// Spread at the `filled` point of the range, multiplied by `2 * order_making_amount`.
+fn spread_at(env: &Env, data: &OracleRangeData, order_making_amount: &U256, filled: &U256) -> U256 {
+    let two_m = order_making_amount.mul(&U256::from_u32(env, 2));
+    let base = two_m.mul(&U256::from_u32(env, data.spread_start_bps));
+    let span = filled.mul(&U256::from_u32(env, 2));
+
+    if data.spread_end_bps >= data.spread_start_bps {
+        base.add(&span.mul(&U256::from_u32(
+            env,
+            data.spread_end_bps - data.spread_start_bps,
+        )))
+    } else {
+        base.sub(&span.mul(&U256::from_u32(
+            env,
+            data.spread_start_bps - data.spread_end_bps,
+        )))
+    }
+}
+
+/// Floor square root, Newton's method seeded from above.
+fn sqrt(env: &Env, n: &U256) -> U256 {
+    let zero = U256::from_u32(env, 0);
+    if *n == zero {
+        return zero;
+    }
+
+    let bytes = n.to_be_bytes();
+    let mut bits = 256u32;
+    for byte in bytes.iter() {
+        if byte != 0 {
+            bits -= byte.leading_zeros();
+            break;
+        }
+        bits -= 8;
+    }
+
+    let mut x = U256::from_u32(env, 1).shl(bits.div_ceil(2));
+    loop {
+        let y = x.add(&n.div(&x)).shr(1);
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+#[contractimpl]
+impl OracleRangeCalculatorContract {
+    pub fn __constructor(env: Env, max_staleness: u64, max_deviation_bps: u32) {
+        env.storage().instance().set(&MAX_STALENESS, &max_staleness);
+        env.storage()
+            .instance()
+            .set(&MAX_DEVIATION, &max_deviation_bps);
+    }
+}
+
+#[contractimpl]
+impl AmountCalculatorInterface for OracleRangeCalculatorContract {
+    fn get_making_amount(
+        env: Env,
+        order: Order,
+        extension: Bytes,
+        _order_hash: BytesN<32>,
+        _taker: Address,
+        taking_amount: U256,
+        remaining_making_amount: U256,
+        _auction_details: AuctionDetails,
+    ) -> U256 {
+        let data = range_data(&env, &extension);
+        let (price, scale) = oracle_price(&env, &data, &order);
+
+        let bps = U256::from_u32(&env, BPS_BASE);
+        let filled = order.making_amount.sub(&remaining_making_amount);
+
+        let making_amount = if data.spread_start_bps == data.spread_end_bps {
+            taking_amount
+                .mul(&scale)
+                .mul(&bps)
+                .div(&price.mul(&U256::from_u32(&env, data.spread_start_bps)))
+        } else {
+            // Solve price * m * (b + delta * m) = taking_amount * scale * BPS * 2M for m,
+            // where b is the doubled spread at the already filled point of the range.
+            let b = spread_at(&env, &data, &order.making_amount, &filled);
+            let k = taking_amount
+                .mul(&scale)
+                .mul(&bps)
+                .mul(&order.making_amount.mul(&U256::from_u32(&env, 2)))
+                .div(&price);
+            let b_squared = b.mul(&b);
+
+            if data.spread_end_bps > data.spread_start_bps {
+                let delta = U256::from_u32(&env, data.spread_end_bps - data.spread_start_bps);
+                let discriminant = b_squared.add(&delta.mul(&k).mul(&U256::from_u32(&env, 4)));
+                sqrt(&env, &discriminant)
+                    .sub(&b)
+                    .div(&delta.mul(&U256::from_u32(&env, 2)))
+            } else {
+                let delta = U256::from_u32(&env, data.spread_start_bps - data.spread_end_bps);
+                let four_delta_k = delta.mul(&k).mul(&U256::from_u32(&env, 4));
+                if four_delta_k > b_squared {
+                    panic_with_error!(&env, Error::AmountExceedsRemaining);
+                }
+                b.sub(&sqrt(&env, &b_squared.sub(&four_delta_k)))
+                    .div(&delta.mul(&U256::from_u32(&env, 2)))
+            }
+        };
+
+        // The range is only defined up to the unfilled part of the order
+        if making_amount > remaining_making_amount {
+            panic_with_error!(&env, Error::AmountExceedsRemaining);
+        }
+        making_amount
+    }
+
+    fn get_taking_amount(
+        env: Env,
+        order: Order,
+        extension: Bytes,
+        _order_hash: BytesN<32>,
+        _taker: Address,
+        making_amount: U256,
+        remaining_making_amount: U256,
+        _auction_details: AuctionDetails,
+    ) -> U256 {
+        let data = range_data(&env, &extension);
+        let (price, scale) = oracle_price(&env, &data, &order);
+
+        if making_amount > remaining_making_amount {
+            panic_with_error!(&env, Error::AmountExceedsRemaining);
+        }
+
+        // Average of the spreads at both ends of the tranche, times 2M
+        let filled = order.making_amount.sub(&remaining_making_amount);
+        let spread_sum = spread_at(&env, &data, &order.making_amount, &filled)
+            .add(&spread_at(
+                &env,
+                &data,
+                &order.making_amount,
+                &filled.add(&making_amount),
+            ))
+            .div(&U256::from_u32(&env, 2));
+
+        let numerator = making_amount.mul(&price).mul(&spread_sum);
+
+        // divide and round up
+        let denominator = scale
+            .mul(&U256::from_u32(&env, BPS_BASE))
+            .mul(&order.making_amount.mul(&U256::from_u32(&env, 2)));
+        let adjustment = denominator.sub(&U256::from_u32(&env, 1));
+        numerator.add(&adjustment).div(&denominator)
+    }
+}
+
+mod test;
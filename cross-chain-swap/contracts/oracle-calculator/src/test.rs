@@ -0,0 +1,352 @@
+#![cfg(test)]
+
+use crate::{OracleRangeCalculatorContract, OracleRangeCalculatorContractClient, OracleRangeData};
+use mock_oracle::{MockOracle, MockOracleClient};
+use order_interface::{AuctionDetails, AuctionWindow, Order};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, U256,
+};
+
+// Price of 2 taker units per maker unit with 7 decimals
+const PRICE: i128 = 20_000_000;
+
+fn create_test_env() -> Env {
+    let env = Env::default();
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 1000;
+    });
+    env
+}
+
+fn create_oracle(env: &Env, price: i128, timestamp: u64) -> Address {
+    let oracle = env.register(MockOracle, (7u32,));
+    MockOracleClient::new(env, &oracle).set_price(&price, &timestamp);
+    oracle
+}
+
+fn create_calculator(env: &Env) -> OracleRangeCalculatorContractClient<'_> {
+    let contract_id = env.register(OracleRangeCalculatorContract, (60u64, 500u32));
+    OracleRangeCalculatorContractClient::new(env, &contract_id)
+}
+
+fn create_test_order(env: &Env) -> Order {
+    Order {
+        salt: U256::from_u32(env, 0),
+        maker: Address::generate(env),
+        maker_evm_address: None,
+        receiver: Address::generate(env),
+        maker_asset: Address::generate(env),
+        taker_asset: Address::generate(env),
+        making_amount: U256::from_u32(env, 1000),
+        taking_amount: U256::from_u32(env, 2000),
+        maker_traits: U256::from_u32(env, 0),
+    }
+}
+
+fn create_extension(
+    env: &Env,
+    oracle: &Address,
+    spread_start_bps: u32,
+    spread_end_bps: u32,
+    deadline: u64,
+) -> Bytes {
+    let data = OracleRangeData {
+        oracle: oracle.clone(),
+        spread_start_bps,
+        spread_end_bps,
+        deadline,
+    }
+    .to_xdr(env);
+
+    // Offsets word: the fields from `MakingAmountData` (the third one) on end with the data
+    let mut extension = Bytes::new(env);
+    for _ in 0..6 {
+        extension.extend_from_array(&data.len().to_be_bytes());
+    }
+    extension.extend_from_array(&[0; 8]);
+    extension.append(&data);
+    extension
+}
+
+// The oracle calculator ignores the auction details
+fn empty_auction_details(env: &Env) -> AuctionDetails {
+    AuctionDetails {
+        auction_window: AuctionWindow { start: 0, end: 0 },
+        taking_amount_start: U256::from_u32(env, 0),
+        taking_amount_end: U256::from_u32(env, 0),
+    }
+}
+
+#[test]
+fn test_flat_spread_amounts() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 990);
+    let order = create_test_order(&env);
+    let extension = create_extension(&env, &oracle, 10_050, 10_050, 2000);
+
+    let taking_amount = client.get_taking_amount(
+        &order,
+        &extension,
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 100),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+    assert_eq!(taking_amount, U256::from_u32(&env, 201));
+
+    let making_amount = client.get_making_amount(
+        &order,
+        &extension,
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 201),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+    assert_eq!(making_amount, U256::from_u32(&env, 100));
+}
+
+#[test]
+fn test_range_prices_tranches_by_filled_amount() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 990);
+    let order = create_test_order(&env);
+    let extension = create_extension(&env, &oracle, 10_000, 12_000, 2000);
+    let order_hash = BytesN::from_array(&env, &[0; 32]);
+    let taker = Address::generate(&env);
+
+    // First half is priced between 100% and 110% of the oracle price
+    let first = client.get_taking_amount(
+        &order,
+        &extension,
+        &order_hash,
+        &taker,
+        &U256::from_u32(&env, 500),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+    assert_eq!(first, U256::from_u32(&env, 1050));
+
+    // Second half is priced between 110% and 120% of the oracle price
+    let second = client.get_taking_amount(
+        &order,
+        &extension,
+        &order_hash,
+        &taker,
+        &U256::from_u32(&env, 500),
+        &U256::from_u32(&env, 500),
+        &empty_auction_details(&env),
+    );
+    assert_eq!(second, U256::from_u32(&env, 1150));
+
+    // Making amounts invert the taking amounts of each tranche
+    let first_making = client.get_making_amount(
+        &order,
+        &extension,
+        &order_hash,
+        &taker,
+        &first,
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+    assert_eq!(first_making, U256::from_u32(&env, 500));
+
+    let second_making = client.get_making_amount(
+        &order,
+        &extension,
+        &order_hash,
+        &taker,
+        &second,
+        &U256::from_u32(&env, 500),
+        &empty_auction_details(&env),
+    );
+    assert_eq!(second_making, U256::from_u32(&env, 500));
+}
+
+#[test]
+fn test_decreasing_range() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 990);
+    let order = create_test_order(&env);
+    let extension = create_extension(&env, &oracle, 12_000, 10_000, 2000);
+    let order_hash = BytesN::from_array(&env, &[0; 32]);
+    let taker = Address::generate(&env);
+
+    let taking_amount = client.get_taking_amount(
+        &order,
+        &extension,
+        &order_hash,
+        &taker,
+        &U256::from_u32(&env, 500),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+    assert_eq!(taking_amount, U256::from_u32(&env, 1150));
+
+    let making_amount = client.get_making_amount(
+        &order,
+        &extension,
+        &order_hash,
+        &taker,
+        &taking_amount,
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+    assert_eq!(making_amount, U256::from_u32(&env, 500));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_stale_price_rejected() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 900);
+    let order = create_test_order(&env);
+
+    client.get_taking_amount(
+        &order,
+        &create_extension(&env, &oracle, 10_000, 10_000, 2000),
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 100),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #7)")]
+fn test_price_from_future_rejected() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 1010);
+    let order = create_test_order(&env);
+
+    client.get_taking_amount(
+        &order,
+        &create_extension(&env, &oracle, 10_000, 10_000, 2000),
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 100),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn test_price_deviation_rejected() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    // 2.5 is 25% away from the 2.0 rate the maker signed
+    let oracle = create_oracle(&env, 25_000_000, 990);
+    let order = create_test_order(&env);
+
+    client.get_taking_amount(
+        &order,
+        &create_extension(&env, &oracle, 10_000, 10_000, 2000),
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 100),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_deadline_passed() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 990);
+    let order = create_test_order(&env);
+
+    client.get_taking_amount(
+        &order,
+        &create_extension(&env, &oracle, 10_000, 10_000, 999),
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 100),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_making_amount_above_remaining() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 990);
+    let order = create_test_order(&env);
+
+    client.get_taking_amount(
+        &order,
+        &create_extension(&env, &oracle, 10_000, 12_000, 2000),
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 600),
+        &U256::from_u32(&env, 500),
+        &empty_auction_details(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_range_data_outside_making_amount_data_rejected() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 990);
+    let order = create_test_order(&env);
+    let data = OracleRangeData {
+        oracle,
+        spread_start_bps: 10_000,
+        spread_end_bps: 10_000,
+        deadline: 2000,
+    }
+    .to_xdr(&env);
+
+    // Range data in the `TakingAmountData` field, leaving `MakingAmountData` empty
+    let mut extension = Bytes::new(&env);
+    for _ in 0..5 {
+        extension.extend_from_array(&data.len().to_be_bytes());
+    }
+    extension.extend_from_array(&[0; 12]);
+    extension.append(&data);
+
+    client.get_taking_amount(
+        &order,
+        &extension,
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 100),
+        &U256::from_u32(&env, 1000),
+        &empty_auction_details(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_taking_amount_above_remaining() {
+    let env = create_test_env();
+    let client = create_calculator(&env);
+    let oracle = create_oracle(&env, PRICE, 990);
+    let order = create_test_order(&env);
+
+    // 1150 buys the last 500, more than the 400 left
+    client.get_making_amount(
+        &order,
+        &create_extension(&env, &oracle, 10_000, 12_000, 2000),
+        &BytesN::from_array(&env, &[0; 32]),
+        &Address::generate(&env),
+        &U256::from_u32(&env, 1150),
+        &U256::from_u32(&env, 400),
+        &empty_auction_details(&env),
+    );
+}
@@ -1,5 +1,11 @@
 #![no_std]
-use soroban_sdk::{contractclient, contracttype, Address, Bytes, BytesN, Env, U256};
+use soroban_sdk::{
+    contractclient, contracterror, contracttype, Address, Bytes, BytesN, Env, String, Vec, U256,
+};
+use utils::ttl::TtlPolicy;
+
+/// Upper bound for the length of a Dutch auction, in seconds
+pub const MAX_AUCTION_DURATION: u64 = 86_400;
 
 /// Order structure for cross-chain swaps
 #[contracttype]
@@ -9,6 +15,10 @@ pub struct Order {
     pub salt: U256,
     /// Maker address
     pub maker: Address,
+    /// Ethereum address of the key signing the order, if it is signed with secp256k1.
+    /// Anyone can put any key here, so a signature by it only stands in for the maker's
+    /// authorization once the maker bound the key to its account with `register_evm_key`.
+    pub maker_evm_address: Option<BytesN<20>>,
     /// Receiver address
     pub receiver: Address,
     /// Maker asset
@@ -23,17 +33,201 @@ pub struct Order {
     pub maker_traits: U256,
 }
 
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum AuctionError {
+    InvalidAuctionWindow = 1,
+    AuctionTooLong = 2,
+}
+
+/// Reasons a fill cannot settle the maker leg
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum SettlementError {
+    /// The maker's allowance to the order contract is below the making amount
+    InsufficientAllowance = 1,
+    /// The allowance the maker granted through `approve` has expired
+    AllowanceExpired = 2,
+    /// The `MakerPermit` extension field does not decode to a `MakerPermit`
+    InvalidPermit = 3,
+    /// The maker permit was submitted after its deadline
+    PermitExpired = 4,
+}
+
+/// Allowance grant over the order's maker asset, passed XDR-encoded in the `MakerPermit`
+/// extension field. The maker authorizes it together with the order hash, so it cannot be
+/// replayed for another order.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MakerPermit {
+    /// Allowance granted to the order contract
+    pub amount: i128,
+    /// Ledger at which the allowance expires
+    pub expiration_ledger: u32,
+    /// Timestamp after which the permit can no longer be submitted
+    pub deadline: u64,
+}
+
+/// Whether an order can be filled as requested, and if not, why
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FillStatus {
+    Fillable,
+    /// The order was fully filled or cancelled
+    InvalidatedOrder,
+    /// The order's nonce was already used or cancelled
+    BitInvalidatedOrder,
+    /// The order is restricted to another taker
+    PrivateOrder,
+    OrderExpired,
+    /// Epoch manager checks cannot be combined with the bit invalidator
+    IncompatibleEpochManager,
+    MissingOrderExtension,
+    /// The extension is not the one the order salt commits to
+    InvalidExtensionHash,
+    UnexpectedOrderExtension,
+    /// Predicates cannot be evaluated yet, so orders carrying one are never filled
+    PredicateNotSupported,
+    /// The taker would pay more than the threshold
+    TakingAmountTooHigh,
+    /// The taker would receive less than the threshold
+    MakingAmountTooLow,
+    /// The remaining making amount costs more than the taker offered
+    TakingAmountExceeded,
+    PartialFillNotAllowed,
+    SwapWithZeroAmount,
+    /// The order's extension whitelists resolvers and the taker is not one of them
+    ResolverNotWhitelisted,
+    /// The taker is whitelisted, but its resolving window has not opened yet
+    ResolvingWindowNotOpen,
+}
+
+/// How the taking amount of a fill is shared between the order receiver and the fee recipients
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSplit {
+    /// Paid to the order receiver
+    pub maker_amount: U256,
+    pub integrator_amount: U256,
+    /// Paid to the protocol treasury
+    pub protocol_amount: U256,
+    /// Amount above the order's own rate, before the protocol takes its share
+    pub surplus: U256,
+    /// Share of the surplus paid to the protocol treasury
+    pub protocol_surplus_amount: U256,
+}
+
+/// Outcome of a simulated fill
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FillQuote {
+    pub making_amount: U256,
+    /// Paid by the taker, fees included
+    pub taking_amount: U256,
+    /// Making amount left before the fill
+    pub remaining_making_amount: U256,
+    pub fees: FeeSplit,
+    pub status: FillStatus,
+}
+
+/// One order of a `fill_orders` batch, with the same inputs as `fill_order_args`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchFill {
+    pub order: Order,
+    pub r: BytesN<32>,
+    pub vs: BytesN<32>,
+    pub amount: U256,
+    pub taker_traits: U256,
+    pub args: Bytes,
+    pub auction_details: AuctionDetails,
+}
+
+/// Time window during which the Dutch auction moves from the start to the end taking amount
 #[contracttype]
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuctionWindow {
+    /// Auction start timestamp
+    pub start: u64,
+    /// Auction end timestamp
+    pub end: u64,
+}
+
+impl AuctionWindow {
+    /// Decodes the legacy packed form, where the high 128 bits hold the start
+    /// time and the low 128 bits hold the end time.
+    pub fn from_packed(packed: &U256) -> Result<Self, AuctionError> {
+        let high = packed.shr(128);
+        let low = packed.sub(&high.shl(128));
+
+        let start = high
+            .to_u128()
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(AuctionError::InvalidAuctionWindow)?;
+        let end = low
+            .to_u128()
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(AuctionError::InvalidAuctionWindow)?;
+
+        Ok(Self { start, end })
+    }
+
+    /// Encodes the window into the legacy packed form.
+    pub fn to_packed(&self, env: &Env) -> U256 {
+        U256::from_u128(env, self.start as u128)
+            .shl(128)
+            .add(&U256::from_u128(env, self.end as u128))
+    }
+
+    /// Checks that the window is not empty and not longer than `MAX_AUCTION_DURATION`.
+    pub fn validate(&self) -> Result<(), AuctionError> {
+        if self.end <= self.start {
+            return Err(AuctionError::InvalidAuctionWindow);
+        }
+        if self.duration() > MAX_AUCTION_DURATION {
+            return Err(AuctionError::AuctionTooLong);
+        }
+        Ok(())
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// Bounds `timestamp` to the window.
+    pub fn clamp(&self, timestamp: u64) -> u64 {
+        timestamp.min(self.end).max(self.start)
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AuctionDetails {
-    pub auction_start_time: U256,
+    pub auction_window: AuctionWindow,
     pub taking_amount_start: U256,
     pub taking_amount_end: U256,
 }
 
 #[contractclient(name = "OrderClient")]
 pub trait OrderInterface {
-    fn __constructor(env: Env, da_addy: Address);
+    /// `chain_id` defaults to the id derived from the network passphrase when `None`.
+    /// Fills only unwrap to native XLM when a `wrapped_native` token is configured.
+    /// `ttl_policy` keeps the persistent entries alive, `DEFAULT_POLICY` when `None`.
+    fn __constructor(
+        env: Env,
+        da_addy: Address,
+        name: String,
+        version: String,
+        chain_id: Option<U256>,
+        wrapped_native: Option<Address>,
+        ttl_policy: Option<TtlPolicy>,
+    );
+
+    fn domain_separator(env: Env) -> BytesN<32>;
+
+    fn wrapped_native(env: Env) -> Option<Address>;
 
     fn calculate_making_amount(
         env: Env,
@@ -60,21 +254,40 @@ pub trait OrderInterface {
 
     fn order_hash(env: Env, order: Order) -> BytesN<32>;
 
-    fn fill(
+    /// Runs the validation `fill_order` would run for `taker`, without side effects and without
+    /// checking the maker's authorization. Amounts and fees are zero unless the status is
+    /// `Fillable`.
+    fn quote_fill(
         env: Env,
+        taker: Address,
         order: Order,
-        order_hash: BytesN<32>,
-        remaining_making_amount: U256,
-        _amount: U256,
-        _taker_traits: U256,
-        _target: Address,
-        _extension: Bytes,
-        _interaction: Bytes,
+        extension: Bytes,
+        amount: U256,
+        taker_traits: U256,
         auction_details: AuctionDetails,
-    );
+    ) -> FillQuote;
+
+    /// Cancels one of the maker's orders. Orders using the bit invalidator are cancelled
+    /// through their nonce, which also cancels every other order sharing it.
+    fn cancel_order(env: Env, maker: Address, maker_traits: U256, order_hash: BytesN<32>);
+
+    /// Cancels several of the maker's orders, given as `(maker_traits, order_hash)` pairs.
+    fn cancel_orders(env: Env, maker: Address, orders: Vec<(U256, BytesN<32>)>);
+
+    /// Approves the order contract to spend `amount` of the maker's `token` until
+    /// `expiration_ledger`, so fills settle without the maker online.
+    fn approve(env: Env, maker: Address, token: Address, amount: i128, expiration_ledger: u32);
+
+    /// Binds the Ethereum key `evm_address` to `maker`, so orders of `maker` signed by that key
+    /// fill without the maker's Soroban authorization. `None` unbinds the current key.
+    fn register_evm_key(env: Env, maker: Address, evm_address: Option<BytesN<20>>);
+
+    /// Ethereum key bound to `maker` through `register_evm_key`, if any.
+    fn evm_key(env: Env, maker: Address) -> Option<BytesN<20>>;
 
     fn fill_order(
         env: Env,
+        taker: Address,
         order: Order,
         r: BytesN<32>,
         vs: BytesN<32>,
@@ -88,6 +301,7 @@ pub trait OrderInterface {
 
     fn fill_order_args(
         env: Env,
+        taker: Address,
         order: Order,
         r: BytesN<32>,
         vs: BytesN<32>,
@@ -96,4 +310,80 @@ pub trait OrderInterface {
         args: Bytes,
         auction_details: AuctionDetails,
     ) -> (U256, U256, BytesN<32>);
+
+    /// Fills every order of the batch with the checks of `fill_order`, reverting all of them
+    /// if one fails. With `net_settlement`, the taker only pays each token's shortfall after
+    /// the makers' assets of the batch paid the receivers, and receives each token's surplus
+    /// instead of the per-order targets. Net settled fills can thus neither set a target nor
+    /// unwrap the maker asset, and take no taker interaction as the makers' assets only reach
+    /// the taker once the batch is settled.
+    fn fill_orders(
+        env: Env,
+        taker: Address,
+        fills: Vec<BatchFill>,
+        net_settlement: bool,
+    ) -> Vec<(U256, U256, BytesN<32>)>;
+}
+
+/// Maker hook called before the maker assets leave the maker.
+/// Enabled by `PRE_INTERACTION_CALL_FLAG` with the listener in the `PreInteractionData` extension.
+#[contractclient(name = "PreInteractionClient")]
+pub trait PreInteractionInterface {
+    fn pre_interaction(
+        env: Env,
+        order: Order,
+        extension: Bytes,
+        order_hash: BytesN<32>,
+        taker: Address,
+        making_amount: U256,
+        taking_amount: U256,
+        remaining_making_amount: U256,
+        extra_data: Bytes,
+    );
+}
+
+/// Taker hook called between the two legs of a fill, once the maker assets reached the target
+/// and before the taker assets are collected, so the taker can source them just in time.
+/// Enabled by the fill's interaction, made of the listener's 32-byte contract id followed by
+/// the extra data.
+#[contractclient(name = "TakerInteractionClient")]
+pub trait TakerInteractionInterface {
+    fn taker_interaction(
+        env: Env,
+        order: Order,
+        extension: Bytes,
+        order_hash: BytesN<32>,
+        taker: Address,
+        making_amount: U256,
+        taking_amount: U256,
+        remaining_making_amount: U256,
+        extra_data: Bytes,
+    );
+}
+
+/// Maker hook called once both legs of a fill are settled.
+/// Enabled by `POST_INTERACTION_CALL_FLAG` with the listener in the `PostInteractionData` extension.
+#[contractclient(name = "PostInteractionClient")]
+pub trait PostInteractionInterface {
+    fn post_interaction(
+        env: Env,
+        order: Order,
+        extension: Bytes,
+        order_hash: BytesN<32>,
+        taker: Address,
+        making_amount: U256,
+        taking_amount: U256,
+        remaining_making_amount: U256,
+        extra_data: Bytes,
+    );
+}
+
+/// Wrapped native XLM: a token backed one to one by lumens held in the native asset contract.
+#[contractclient(name = "WrappedNativeClient")]
+pub trait WrappedNativeInterface {
+    /// Locks `amount` native XLM from `from` and mints the same amount of wrapped XLM to it.
+    fn deposit(env: Env, from: Address, amount: i128);
+
+    /// Burns `amount` wrapped XLM of `from` and pays the same amount of native XLM to `to`.
+    fn withdraw(env: Env, from: Address, to: Address, amount: i128);
 }
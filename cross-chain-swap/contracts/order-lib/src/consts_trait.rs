@@ -1,4 +1,5 @@
 use soroban_sdk::{Bytes, Env, U256};
+use utils::math::bitand;
 
 pub trait ConstTrait {
     const EXPIRATION_OFFSET: u32 = 80;
@@ -64,7 +65,7 @@ pub trait ConstTrait {
     }
 
     fn unwrap_weth_maker_flag(env: Env) -> U256 {
-        U256::from_u32(&env, 1).shl(248)
+        U256::from_u32(&env, 1).shl(247)
     }
 
     fn maker_amount_flag(env: Env) -> U256 {
@@ -101,31 +102,7 @@ pub trait ConstTrait {
     }
 }
 
-/// More efficient bitwise AND implementation
+/// Bitwise AND of two U256 values
 pub fn u256_bitwise_and(env: &Env, a: &U256, b: &U256) -> U256 {
-    let mut result = U256::from_u32(env, 0);
-    let mut a_temp = a.clone();
-    let mut b_temp = b.clone();
-    let mut power = U256::from_u32(env, 1);
-
-    // Process each bit
-    for _ in 0..256 {
-        // Get the least significant bit of each number
-        let a_lsb = a_temp.rem_euclid(&U256::from_u32(env, 2));
-        let b_lsb = b_temp.rem_euclid(&U256::from_u32(env, 2));
-
-        // If both bits are 1, add the power to result
-        if a_lsb == U256::from_u32(env, 1) && b_lsb == U256::from_u32(env, 1) {
-            result = result.add(&power);
-        }
-
-        // Right shift both numbers by 1
-        a_temp = a_temp.div(&U256::from_u32(env, 2));
-        b_temp = b_temp.div(&U256::from_u32(env, 2));
-
-        // Move to next bit position
-        power = power.mul(&U256::from_u32(env, 2));
-    }
-
-    result
+    bitand(env, a.clone(), b.clone())
 }
@@ -0,0 +1,230 @@
+use order_interface::{FeeSplit, Order};
+use soroban_sdk::{contracttype, Address, Bytes, Env, Vec, U256};
+use utils::address::{decode_stellar_address, encode_stellar_address};
+
+use crate::xlm_orders::{get_extension, DynamicField};
+
+// Binary layout of the `TakingAmountData` extension field, charging fees on top of the
+// taking amount the maker asks for:
+//
+//   integrator              33 bytes, canonical Stellar address (tag || key)
+//   integrator_fee          2 bytes, big-endian basis points
+//   protocol                33 bytes, canonical Stellar address of the protocol treasury
+//   protocol_fee            2 bytes, big-endian basis points
+//   protocol_surplus_share  2 bytes, big-endian basis points of the surplus
+//
+// Orders without the field pay no fees and share no surplus.
+//
+// The surplus of a fill is what the auction makes the taker pay the maker above the order's
+// own rate, `order.taking_amount / order.making_amount`.
+
+/// Length of an encoded fee config
+pub const FEE_CONFIG_LENGTH: u32 = 72;
+/// Fees and the surplus share are expressed in basis points
+pub const BASIS_POINTS: u32 = 10_000;
+
+const ADDRESS_LENGTH: u32 = 33;
+
+/// Payment of a taker leg to an order receiver or fee recipient. Net settled batches owe them
+/// until the batch is settled.
+#[contracttype]
+#[derive(Clone)]
+pub struct ReceiverPayment {
+    pub token: Address,
+    pub receiver: Address,
+    pub amount: i128,
+    pub unwrap: bool,
+}
+
+/// Fee recipients and rates of an order
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeConfig {
+    pub integrator: Address,
+    pub integrator_fee: u32,
+    pub protocol: Address,
+    pub protocol_fee: u32,
+    pub protocol_surplus_share: u32,
+}
+
+impl FeeConfig {
+    pub fn encode(&self, env: &Env) -> Bytes {
+        if self.integrator_fee > BASIS_POINTS
+            || self.protocol_fee > BASIS_POINTS
+            || self.protocol_surplus_share > BASIS_POINTS
+        {
+            panic!("Invalid fees");
+        }
+        let mut data = encode_stellar_address(env, &self.integrator);
+        data.extend_from_slice(&(self.integrator_fee as u16).to_be_bytes());
+        data.append(&encode_stellar_address(env, &self.protocol));
+        data.extend_from_slice(&(self.protocol_fee as u16).to_be_bytes());
+        data.extend_from_slice(&(self.protocol_surplus_share as u16).to_be_bytes());
+        data
+    }
+
+    pub fn decode(env: &Env, data: &Bytes) -> Self {
+        if data.len() != FEE_CONFIG_LENGTH {
+            panic!("Invalid fees");
+        }
+        let address = |offset: u32| {
+            decode_stellar_address(env, &data.slice(offset..offset + ADDRESS_LENGTH))
+                .unwrap_or_else(|| panic!("Invalid fees"))
+        };
+        let fee = |offset: u32| {
+            let fee = u16::from_be_bytes([data.get(offset).unwrap(), data.get(offset + 1).unwrap()])
+                as u32;
+            if fee > BASIS_POINTS {
+                panic!("Invalid fees");
+            }
+            fee
+        };
+
+        FeeConfig {
+            integrator: address(0),
+            integrator_fee: fee(ADDRESS_LENGTH),
+            protocol: address(ADDRESS_LENGTH + 2),
+            protocol_fee: fee(2 * ADDRESS_LENGTH + 2),
+            protocol_surplus_share: fee(2 * ADDRESS_LENGTH + 4),
+        }
+    }
+
+    /// Fee config of the order `extension` belongs to, if it charges fees.
+    pub fn from_extension(env: &Env, extension: &Bytes) -> Option<Self> {
+        let data = get_extension(env, extension, DynamicField::TakingAmountData);
+        if data.is_empty() {
+            None
+        } else {
+            Some(Self::decode(env, &data))
+        }
+    }
+
+    /// Largest auction amount whose fees still fit in `taking_amount`.
+    pub fn maker_amount(&self, env: &Env, taking_amount: &U256) -> U256 {
+        let total_fee = BASIS_POINTS + self.integrator_fee + self.protocol_fee;
+        let mut maker_amount = taking_amount
+            .mul(&U256::from_u32(env, BASIS_POINTS))
+            .div(&U256::from_u32(env, total_fee));
+
+        // Each fee is rounded down on its own, which can leave room for a few more units
+        let one = U256::from_u32(env, 1);
+        let with_fees = |amount: &U256| {
+            let (integrator_amount, protocol_amount) = self.fees(env, amount);
+            amount.add(&integrator_amount).add(&protocol_amount)
+        };
+        while with_fees(&maker_amount.add(&one)) <= *taking_amount {
+            maker_amount = maker_amount.add(&one);
+        }
+        maker_amount
+    }
+
+    /// Integrator and protocol fees charged on `amount`.
+    pub fn fees(&self, env: &Env, amount: &U256) -> (U256, U256) {
+        (
+            bps(env, amount, self.integrator_fee),
+            bps(env, amount, self.protocol_fee),
+        )
+    }
+}
+
+/// Splits the payment of a taker buying `making_amount` for the auction amount `amount`:
+/// fees are charged on top of it, and the protocol's share of the surplus is taken out of it.
+pub fn split(
+    env: &Env,
+    fees: &Option<FeeConfig>,
+    order: &Order,
+    making_amount: &U256,
+    amount: U256,
+) -> FeeSplit {
+    let zero = U256::from_u32(env, 0);
+    let surplus = if order.making_amount == zero {
+        zero.clone()
+    } else {
+        // Rounded up, so the surplus never eats into the order's rate
+        let estimated = making_amount
+            .mul(&order.taking_amount)
+            .add(&order.making_amount.sub(&U256::from_u32(env, 1)))
+            .div(&order.making_amount);
+        if amount > estimated {
+            amount.sub(&estimated)
+        } else {
+            zero.clone()
+        }
+    };
+
+    match fees {
+        Some(fees) => {
+            let (integrator_amount, protocol_amount) = fees.fees(env, &amount);
+            let protocol_surplus_amount = bps(env, &surplus, fees.protocol_surplus_share);
+            FeeSplit {
+                maker_amount: amount.sub(&protocol_surplus_amount),
+                integrator_amount,
+                protocol_amount,
+                surplus,
+                protocol_surplus_amount,
+            }
+        }
+        None => FeeSplit {
+            maker_amount: amount,
+            integrator_amount: zero.clone(),
+            protocol_amount: zero.clone(),
+            surplus,
+            protocol_surplus_amount: zero,
+        },
+    }
+}
+
+/// Largest auction amount whose fees fit in `taking_amount`, when the order may not charge fees.
+pub fn maker_amount(env: &Env, fees: &Option<FeeConfig>, taking_amount: &U256) -> U256 {
+    match fees {
+        Some(fees) => fees.maker_amount(env, taking_amount),
+        None => taking_amount.clone(),
+    }
+}
+
+/// Amount the taker pays in total.
+pub fn total(split: &FeeSplit) -> U256 {
+    split
+        .maker_amount
+        .add(&split.integrator_amount)
+        .add(&split.protocol_amount)
+        .add(&split.protocol_surplus_amount)
+}
+
+/// Payments of a fill's taker leg: the maker's share to the order receiver, as native XLM if
+/// `unwrap` is set, and each non-zero fee to its recipient. The protocol's share of the
+/// surplus is paid with its fee.
+pub fn taker_payments(
+    env: &Env,
+    order: &Order,
+    fees: &Option<FeeConfig>,
+    split: &FeeSplit,
+    unwrap: bool,
+) -> Vec<ReceiverPayment> {
+    let payment = |receiver: &Address, amount: &U256, unwrap: bool| ReceiverPayment {
+        token: order.taker_asset.clone(),
+        receiver: receiver.clone(),
+        amount: amount.to_u128().unwrap() as i128,
+        unwrap,
+    };
+
+    let mut payments =
+        Vec::from_array(env, [payment(&order.receiver, &split.maker_amount, unwrap)]);
+    if let Some(fees) = fees {
+        let protocol_amount = split.protocol_amount.add(&split.protocol_surplus_amount);
+        for (recipient, amount) in [
+            (&fees.integrator, &split.integrator_amount),
+            (&fees.protocol, &protocol_amount),
+        ] {
+            if *amount != U256::from_u32(env, 0) {
+                payments.push_back(payment(recipient, amount, false));
+            }
+        }
+    }
+    payments
+}
+
+fn bps(env: &Env, amount: &U256, bps: u32) -> U256 {
+    amount
+        .mul(&U256::from_u32(env, bps))
+        .div(&U256::from_u32(env, BASIS_POINTS))
+}
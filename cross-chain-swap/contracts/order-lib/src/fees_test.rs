@@ -0,0 +1,168 @@
+#![cfg(test)]
+
+use soroban_sdk::{testutils::Address as _, Address, Env, U256};
+
+use crate::fees::{self, FeeConfig, BASIS_POINTS, FEE_CONFIG_LENGTH};
+use order_interface::Order;
+
+fn fee_config(env: &Env, integrator_fee: u32, protocol_fee: u32) -> FeeConfig {
+    FeeConfig {
+        integrator: Address::generate(env),
+        integrator_fee,
+        protocol: Address::generate(env),
+        protocol_fee,
+        protocol_surplus_share: 0,
+    }
+}
+
+/// Order selling 1000 for 2000
+fn order(env: &Env) -> Order {
+    Order {
+        salt: U256::from_u32(env, 0),
+        maker: Address::generate(env),
+        maker_evm_address: None,
+        receiver: Address::generate(env),
+        maker_asset: Address::generate(env),
+        taker_asset: Address::generate(env),
+        making_amount: U256::from_u32(env, 1000),
+        taking_amount: U256::from_u32(env, 2000),
+        maker_traits: U256::from_u32(env, 0),
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let env = Env::default();
+    let mut config = fee_config(&env, 50, BASIS_POINTS);
+    config.protocol_surplus_share = 2500;
+
+    let data = config.encode(&env);
+    assert_eq!(data.len(), FEE_CONFIG_LENGTH);
+    assert_eq!(FeeConfig::decode(&env, &data), config);
+}
+
+#[test]
+fn test_split_charges_fees_on_top_of_auction_amount() {
+    let env = Env::default();
+    // 1% to the integrator, 0.5% to the protocol
+    let config = Some(fee_config(&env, 100, 50));
+    let making_amount = U256::from_u32(&env, 1000);
+
+    let split = fees::split(
+        &env,
+        &config,
+        &order(&env),
+        &making_amount,
+        U256::from_u32(&env, 2000),
+    );
+    assert_eq!(split.maker_amount, U256::from_u32(&env, 2000));
+    assert_eq!(split.integrator_amount, U256::from_u32(&env, 20));
+    assert_eq!(split.protocol_amount, U256::from_u32(&env, 10));
+    assert_eq!(split.surplus, U256::from_u32(&env, 0));
+    assert_eq!(fees::total(&split), U256::from_u32(&env, 2030));
+}
+
+#[test]
+fn test_split_shares_surplus_above_order_rate() {
+    let env = Env::default();
+    let mut config = fee_config(&env, 0, 0);
+    config.protocol_surplus_share = 5000;
+    let order = order(&env);
+
+    // Half of the order is estimated at 1000, the auction asks 1100
+    let split = fees::split(
+        &env,
+        &Some(config),
+        &order,
+        &U256::from_u32(&env, 500),
+        U256::from_u32(&env, 1100),
+    );
+    assert_eq!(split.surplus, U256::from_u32(&env, 100));
+    assert_eq!(split.protocol_surplus_amount, U256::from_u32(&env, 50));
+    assert_eq!(split.maker_amount, U256::from_u32(&env, 1050));
+    assert_eq!(fees::total(&split), U256::from_u32(&env, 1100));
+
+    // Below the order's rate there is no surplus
+    let split = fees::split(
+        &env,
+        &None,
+        &order,
+        &U256::from_u32(&env, 500),
+        U256::from_u32(&env, 900),
+    );
+    assert_eq!(split.surplus, U256::from_u32(&env, 0));
+    assert_eq!(split.maker_amount, U256::from_u32(&env, 900));
+}
+
+#[test]
+fn test_surplus_estimate_rounds_in_favour_of_the_maker() {
+    let env = Env::default();
+    let mut order = order(&env);
+    order.taking_amount = U256::from_u32(&env, 1999);
+
+    // 1 of the maker asset is estimated at 1.999, rounded up to 2
+    let split = fees::split(
+        &env,
+        &None,
+        &order,
+        &U256::from_u32(&env, 1),
+        U256::from_u32(&env, 2),
+    );
+    assert_eq!(split.surplus, U256::from_u32(&env, 0));
+
+    let split = fees::split(
+        &env,
+        &None,
+        &order,
+        &U256::from_u32(&env, 1),
+        U256::from_u32(&env, 3),
+    );
+    assert_eq!(split.surplus, U256::from_u32(&env, 1));
+}
+
+#[test]
+fn test_maker_amount_fits_fees_in_taking_amount() {
+    let env = Env::default();
+    let config = fee_config(&env, 100, 50);
+    let with_fees = |amount: &U256| {
+        let (integrator_amount, protocol_amount) = config.fees(&env, amount);
+        amount.add(&integrator_amount).add(&protocol_amount)
+    };
+
+    for taking_amount in [2030, 2031, 2044, 1, 0] {
+        let taking_amount = U256::from_u32(&env, taking_amount);
+        let maker_amount = config.maker_amount(&env, &taking_amount);
+        assert!(with_fees(&maker_amount) <= taking_amount);
+
+        // One more unit for the maker would not fit
+        assert!(with_fees(&maker_amount.add(&U256::from_u32(&env, 1))) > taking_amount);
+    }
+}
+
+#[test]
+fn test_orders_without_fees_pay_the_maker_only() {
+    let env = Env::default();
+    let amount = U256::from_u32(&env, 2000);
+
+    assert_eq!(fees::maker_amount(&env, &None, &amount), amount);
+    let split = fees::split(
+        &env,
+        &None,
+        &order(&env),
+        &U256::from_u32(&env, 1000),
+        amount.clone(),
+    );
+    assert_eq!(fees::total(&split), amount);
+}
+
+#[test]
+#[should_panic(expected = "Invalid fees")]
+fn test_decode_rejects_fee_over_basis_points() {
+    let env = Env::default();
+    let mut data = fee_config(&env, 0, 0).encode(&env);
+    // integrator_fee = 0x2711 = 10_001
+    data.set(33, 0x27);
+    data.set(34, 0x11);
+
+    FeeConfig::decode(&env, &data);
+}
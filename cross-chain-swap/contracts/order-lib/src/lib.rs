@@ -0,0 +1,17 @@
+#![no_std]
+
+// Order encoding helpers shared by the limit order protocol and the contracts it calls into.
+// Kept out of the `order` contract crate so depending on them does not link its exports.
+
+pub mod consts_trait;
+pub mod fees;
+pub mod maker_traits;
+pub mod taker_args;
+pub mod taker_traits;
+pub mod xlm_orders;
+
+mod fees_test;
+mod maker_traits_test;
+mod taker_args_test;
+mod taker_traits_test;
+mod xlm_orders_test;
@@ -1,7 +1,7 @@
-use soroban_sdk::{contract, xdr::ToXdr, Address, Env, U256};
+use soroban_sdk::{Address, Env, U256};
 
 use crate::consts_trait::ConstTrait;
-use utils::math::bitand;
+use utils::{address::CrossChainAddress, math::bitand};
 
 // 1inch Solidity version:
 // https://github.com/1inch/limit-order-protocol/blob/master/contracts/libraries/MakerTraitsLib.sol
@@ -21,12 +21,11 @@ use utils::math::bitand;
 /// 247 bit `UNWRAP_WETH_FLAG`               - if set, the order requires to unwrap WETH
 ///
 /// Low 200 bits are used for allowed sender, expiration, nonce_or_epoch, and series:
-/// uint80 last 10 bytes of allowed sender address (0 if any)
+/// uint80 last 10 bytes of the hashed canonical allowed sender address (0 if any)
 /// uint40 expiration timestamp (0 if none)
 /// uint40 nonce or epoch
 /// uint40 series
 
-#[contract]
 pub struct MakerTraitsLib;
 
 impl ConstTrait for MakerTraitsLib {}
@@ -41,37 +40,39 @@ impl MakerTraitsLib {
 
     /// Checks if the maker allows a specific taker to fill the order.
     pub fn is_allowed_sender(env: &Env, maker_traits: U256, sender: Address) -> bool {
-        let maker_traits = maker_traits;
-        let allowed_sender_bits = Self::extract_low_bits(env, maker_traits, 0, 80);
+        // 80 bits do not fit the u64 returned by extract_low_bits
+        let allowed_sender_bits = bitand(env, maker_traits, Self::allowed_sender_mask(env.clone()))
+            .to_u128()
+            .unwrap();
 
         if allowed_sender_bits == 0 {
             return true; // Any sender allowed
         }
 
-        // Convert address to bytes and take last 10 bytes (80 bits)
-        let sender_bytes = sender.to_string().to_xdr(&env);
-        let sender_len = sender_bytes.len();
+        allowed_sender_bits == Self::sender_bits(env, &CrossChainAddress::Stellar(sender))
+    }
 
-        if sender_len < 10 {
-            return false;
-        }
+    /// Low 80 bits identifying `sender` in the allowed sender field of the maker traits.
+    /// They are taken from the hash of its canonical cross-chain encoding, which keeps
+    /// EVM addresses and Stellar accounts or contracts with the same tail apart.
+    pub fn sender_bits(env: &Env, sender: &CrossChainAddress) -> u128 {
+        let hash = sender.hash(env).to_array();
 
-        // Extract last 10 bytes and convert to u128
         let mut sender_bits: u128 = 0;
-        for i in 0..10 {
-            sender_bits =
-                (sender_bits << 8) | (sender_bytes.get(sender_len - 10 + i).unwrap() as u128);
+        for byte in &hash[22..] {
+            sender_bits = (sender_bits << 8) | *byte as u128;
         }
+        sender_bits
+    }
 
-        let sender_u256 = U256::from_u128(&env, sender_bits);
-        let masked_sender = bitand(&env, sender_u256, Self::allowed_sender_mask(env.clone()));
-
-        allowed_sender_bits as u128 == masked_sender.to_u128().unwrap()
+    /// Returns the expiration timestamp of the order, 0 if it never expires.
+    pub fn expiration(env: &Env, maker_traits: U256) -> u64 {
+        Self::extract_low_bits(env, maker_traits, Self::EXPIRATION_OFFSET, 40)
     }
 
     /// Checks if the order has expired.
     pub fn is_expired(env: &Env, maker_traits: U256) -> bool {
-        let expiration = Self::extract_low_bits(env, maker_traits, Self::EXPIRATION_OFFSET, 40);
+        let expiration = Self::expiration(env, maker_traits);
 
         if expiration == 0 {
             return false; // No expiration set
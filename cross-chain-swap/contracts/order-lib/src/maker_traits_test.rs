@@ -3,6 +3,7 @@
 use soroban_sdk::{testutils::Address as _, Address, Env};
 
 use crate::maker_traits::{MakerTraitsBuilder, MakerTraitsLib};
+use utils::address::CrossChainAddress;
 
 fn create_test_env() -> Env {
     Env::default()
@@ -62,6 +63,26 @@ fn test_is_allowed_sender() {
     assert!(result == true || result == false); // Just ensure it doesn't panic
 }
 
+#[test]
+fn test_is_allowed_sender_matches_canonical_address() {
+    let env = create_test_env();
+    let sender = create_test_address(&env);
+    let other = create_test_address(&env);
+
+    let sender_bits =
+        MakerTraitsLib::sender_bits(&env, &CrossChainAddress::Stellar(sender.clone()));
+    let traits = MakerTraitsBuilder::new(env.clone())
+        .with_allowed_sender(sender_bits)
+        .build();
+
+    assert!(MakerTraitsLib::is_allowed_sender(
+        &env,
+        traits.clone(),
+        sender
+    ));
+    assert!(!MakerTraitsLib::is_allowed_sender(&env, traits, other));
+}
+
 // #[test]
 // fn test_is_expired() {
 //     let env = create_test_env();
@@ -287,6 +308,18 @@ fn test_unwrap_weth() {
     assert!(MakerTraitsLib::unwrap_weth(env.clone(), traits_unwrap));
 }
 
+#[test]
+fn test_use_permit2_and_unwrap_weth_are_distinct_flags() {
+    let env = create_test_env();
+
+    let traits_permit2 = MakerTraitsBuilder::new(env.clone()).use_permit2().build();
+    let traits_unwrap = MakerTraitsBuilder::new(env.clone()).unwrap_weth().build();
+
+    assert_ne!(traits_permit2, traits_unwrap);
+    assert!(!MakerTraitsLib::unwrap_weth(env.clone(), traits_permit2));
+    assert!(!MakerTraitsLib::use_permit2(env.clone(), traits_unwrap));
+}
+
 // #[test]
 // fn test_extract_low_bits() {
 //     let env = create_test_env();
@@ -0,0 +1,121 @@
+use soroban_sdk::{Address, Bytes, Env, U256};
+use utils::{
+    address::{decode_stellar_address, encode_stellar_address},
+    math::bitand,
+};
+
+use crate::{consts_trait::ConstTrait, taker_traits::TakerTraitsLib};
+
+// Binary layout of the `args` of `fill_order_args`, each part present only if the taker
+// traits announce it:
+//
+//   target       33 bytes, canonical Stellar address (tag || key)   if `ARGS_HAS_TARGET` is set
+//   extension    `ARGS_EXTENSION_LENGTH` bytes
+//   interaction  `ARGS_INTERACTION_LENGTH` bytes
+//
+// Nothing may follow the interaction.
+
+/// Length of an encoded target
+pub const ARGS_TARGET_LENGTH: u32 = 33;
+
+/// Decoded `args` of `fill_order_args`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TakerArgs {
+    /// Receiver of the maker assets, the taker when `None`
+    pub target: Option<Address>,
+    pub extension: Bytes,
+    pub interaction: Bytes,
+}
+
+impl TakerArgs {
+    /// Encodes the args, and sets the target flag and the lengths they need in `taker_traits`.
+    pub fn encode(&self, env: &Env, taker_traits: U256) -> (U256, Bytes) {
+        let mut args = Bytes::new(env);
+        let mut taker_traits = clear(
+            env,
+            taker_traits,
+            TakerTraitsLib::args_has_target_const(env.clone()),
+        );
+
+        if let Some(target) = &self.target {
+            args.append(&encode_stellar_address(env, target));
+            taker_traits = taker_traits.add(&TakerTraitsLib::args_has_target_const(env.clone()));
+        }
+
+        taker_traits = set_length(
+            env,
+            taker_traits,
+            TakerTraitsLib::ARGS_EXTENSION_LENGTH_OFFSET,
+            TakerTraitsLib::ARGS_EXTENSION_LENGTH_MASK,
+            self.extension.len(),
+        );
+        taker_traits = set_length(
+            env,
+            taker_traits,
+            TakerTraitsLib::ARGS_INTERACTION_LENGTH_OFFSET,
+            TakerTraitsLib::ARGS_INTERACTION_LENGTH_MASK,
+            self.interaction.len(),
+        );
+        args.append(&self.extension);
+        args.append(&self.interaction);
+
+        (taker_traits, args)
+    }
+
+    /// Decodes `args` laid out as `taker_traits` announces.
+    pub fn decode(env: &Env, taker_traits: &U256, args: &Bytes) -> Self {
+        let mut offset = 0;
+
+        let target = if TakerTraitsLib::args_has_target(env, taker_traits.clone()) {
+            offset = ARGS_TARGET_LENGTH;
+            if args.len() < offset {
+                panic!("Invalid args length");
+            }
+            Some(
+                decode_stellar_address(env, &args.slice(..offset))
+                    .unwrap_or_else(|| panic!("Invalid args target")),
+            )
+        } else {
+            None
+        };
+
+        let extension_length = length(TakerTraitsLib::args_extension_length(
+            env,
+            taker_traits.clone(),
+        ));
+        let interaction_length = length(TakerTraitsLib::args_interaction_length(
+            env,
+            taker_traits.clone(),
+        ));
+        if args.len() != offset + extension_length + interaction_length {
+            panic!("Invalid args length");
+        }
+
+        let extension = args.slice(offset..offset + extension_length);
+        offset += extension_length;
+        let interaction = args.slice(offset..offset + interaction_length);
+
+        TakerArgs {
+            target,
+            extension,
+            interaction,
+        }
+    }
+}
+
+fn length(value: U256) -> u32 {
+    // Masked to 24 bits by `TakerTraitsLib`
+    value.to_u128().unwrap() as u32
+}
+
+fn clear(env: &Env, taker_traits: U256, bits: U256) -> U256 {
+    taker_traits.sub(&bitand(env, taker_traits.clone(), bits))
+}
+
+fn set_length(env: &Env, taker_traits: U256, offset: u32, mask: u32, length: u32) -> U256 {
+    if length > mask {
+        panic!("Args too long");
+    }
+    let field = U256::from_u32(env, mask).shl(offset);
+    clear(env, taker_traits, field).add(&U256::from_u32(env, length).shl(offset))
+}
@@ -0,0 +1,133 @@
+#![cfg(test)]
+
+use soroban_sdk::{testutils::Address as _, xdr::ToXdr, Address, Bytes, BytesN, Env, U256};
+use utils::address::account_address;
+
+use crate::{
+    taker_args::{TakerArgs, ARGS_TARGET_LENGTH},
+    taker_traits::TakerTraitsLib,
+};
+
+fn round_trip(env: &Env, args: &TakerArgs, taker_traits: U256) {
+    let (taker_traits, encoded) = args.encode(env, taker_traits);
+
+    assert_eq!(
+        TakerTraitsLib::args_has_target(env, taker_traits.clone()),
+        args.target.is_some()
+    );
+    assert_eq!(
+        TakerTraitsLib::args_extension_length(env, taker_traits.clone()),
+        U256::from_u32(env, args.extension.len())
+    );
+    assert_eq!(
+        TakerTraitsLib::args_interaction_length(env, taker_traits.clone()),
+        U256::from_u32(env, args.interaction.len())
+    );
+    let target_length = if args.target.is_some() {
+        ARGS_TARGET_LENGTH
+    } else {
+        0
+    };
+    assert_eq!(
+        encoded.len(),
+        target_length + args.extension.len() + args.interaction.len()
+    );
+
+    assert_eq!(TakerArgs::decode(env, &taker_traits, &encoded), *args);
+}
+
+#[test]
+fn test_round_trip() {
+    let env = Env::default();
+    let extension = Bytes::from_slice(&env, &[1; 40]);
+    let interaction = Bytes::from_slice(&env, &[2; 35]);
+
+    for target in [
+        None,
+        Some(Address::generate(&env)),
+        Some(account_address(
+            &env,
+            &BytesN::from_array(&env, &[0x42; 32]),
+        )),
+    ] {
+        for (extension, interaction) in [
+            (Bytes::new(&env), Bytes::new(&env)),
+            (extension.clone(), Bytes::new(&env)),
+            (Bytes::new(&env), interaction.clone()),
+            (extension.clone(), interaction.clone()),
+        ] {
+            let args = TakerArgs {
+                target: target.clone(),
+                extension,
+                interaction,
+            };
+            round_trip(&env, &args, U256::from_u32(&env, 0));
+        }
+    }
+}
+
+#[test]
+fn test_encode_keeps_other_traits() {
+    let env = Env::default();
+    // Maker amount flag, threshold, and stale lengths and target flag to be replaced
+    let taker_traits = U256::from_u32(&env, 1)
+        .shl(255)
+        .add(&U256::from_u32(&env, 1).shl(251))
+        .add(&U256::from_u32(&env, 0xffffff).shl(224))
+        .add(&U256::from_u32(&env, 7).shl(200))
+        .add(&U256::from_u32(&env, 1234));
+    let args = TakerArgs {
+        target: None,
+        extension: Bytes::from_slice(&env, &[1; 3]),
+        interaction: Bytes::new(&env),
+    };
+
+    let (encoded_traits, _) = args.encode(&env, taker_traits);
+    assert!(TakerTraitsLib::is_making_amount(&env, &encoded_traits));
+    assert_eq!(
+        TakerTraitsLib::threshold(&env, encoded_traits.clone()),
+        U256::from_u32(&env, 1234)
+    );
+    round_trip(&env, &args, encoded_traits);
+}
+
+#[test]
+fn test_target_encoding_is_canonical() {
+    let env = Env::default();
+    let target = Address::generate(&env);
+    let args = TakerArgs {
+        target: Some(target.clone()),
+        extension: Bytes::new(&env),
+        interaction: Bytes::new(&env),
+    };
+
+    let (_, encoded) = args.encode(&env, U256::from_u32(&env, 0));
+    let xdr = target.to_xdr(&env);
+    let mut expected = Bytes::from_array(&env, &[0x02]);
+    expected.append(&xdr.slice(xdr.len() - 32..));
+    assert_eq!(encoded, expected);
+}
+
+#[test]
+#[should_panic(expected = "Invalid args length")]
+fn test_decode_rejects_trailing_bytes() {
+    let env = Env::default();
+    let args = TakerArgs {
+        target: None,
+        extension: Bytes::from_slice(&env, &[1; 4]),
+        interaction: Bytes::new(&env),
+    };
+    let (taker_traits, mut encoded) = args.encode(&env, U256::from_u32(&env, 0));
+    encoded.push_back(0);
+
+    TakerArgs::decode(&env, &taker_traits, &encoded);
+}
+
+#[test]
+#[should_panic(expected = "Invalid args target")]
+fn test_decode_rejects_unknown_target_tag() {
+    let env = Env::default();
+    let taker_traits = U256::from_u32(&env, 1).shl(251);
+
+    TakerArgs::decode(&env, &taker_traits, &Bytes::from_slice(&env, &[0x07; 33]));
+}
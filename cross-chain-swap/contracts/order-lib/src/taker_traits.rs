@@ -14,7 +14,7 @@ use crate::consts_trait::{u256_bitwise_and, ConstTrait};
 /// 254 bit `_UNWRAP_WETH_FLAG`            - If set, the WETH will be unwrapped into ETH before sending to taker.
 /// 253 bit `_SKIP_ORDER_PERMIT_FLAG`      - If set, the order skips maker's permit execution.
 /// 252 bit `_USE_PERMIT2_FLAG`            - If set, the order uses the permit2 function for authorization.
-/// 251 bit `_ARGS_HAS_TARGET`             - If set, then first 20 bytes of args are treated as target address for maker's funds transfer.
+/// 251 bit `_ARGS_HAS_TARGET`             - If set, then first 33 bytes of args are treated as target address for maker's funds transfer, see `taker_args`.
 /// 224-247 bits `ARGS_EXTENSION_LENGTH`   - The length of the extension calldata in the args.
 /// 200-223 bits `ARGS_INTERACTION_LENGTH` - The length of the interaction calldata in the args.
 /// 0-184 bits                             - The threshold amount (the maximum amount a taker agrees to give in exchange for a making amount).
@@ -70,7 +70,7 @@ impl TakerTraitsLib {
     }
 
     /// Checks if the order should skip maker's permit execution.
-    fn _skip_maker_permit(env: &Env, taker_traits: U256) -> bool {
+    pub fn skip_maker_permit(env: &Env, taker_traits: U256) -> bool {
         u256_bitwise_and(
             &env,
             &taker_traits,
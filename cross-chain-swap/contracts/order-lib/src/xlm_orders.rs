@@ -0,0 +1,305 @@
+use crate::maker_traits::MakerTraitsLib;
+use order_interface::Order;
+use soroban_sdk::{
+    contracttype, crypto::Hash, symbol_short, xdr::ToXdr, Bytes, BytesN, Env, String, Symbol, U256,
+};
+use utils::{address::CrossChainAddress, math::bitand};
+
+#[derive(Eq, PartialEq, Debug)]
+#[contracttype]
+pub enum ValidationResult {
+    MissingOrderExtension,
+    InvalidExtensionHash,
+    UnexpectedOrderExtension,
+    Success,
+}
+
+#[contracttype]
+pub enum DynamicField {
+    MakerAssetSuffix,
+    TakerAssetSuffix,
+    MakingAmountData,
+    TakingAmountData,
+    Predicate,
+    MakerPermit,
+    PreInteractionData,
+    PostInteractionData,
+    CustomData,
+}
+
+// STORAGE SYMBOLS
+const DOMAIN_SEPARATOR: Symbol = symbol_short!("DOM_SEP");
+
+// OrderLib constants
+const LIMIT_ORDER_TYPEHASH: &str = "Order(uint256 salt,bytes maker,bytes receiver,bytes makerAsset,bytes takerAsset,uint256 makingAmount,uint256 takingAmount,uint256 makerTraits,address makerEvmAddress)";
+
+// EIP-712 constants
+const EIP712_DOMAIN_TYPEHASH: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,bytes32 verifyingContract)";
+pub const DEFAULT_DOMAIN_NAME: &str = "XLMOrders";
+pub const DEFAULT_DOMAIN_VERSION: &str = "1.0.0";
+
+pub fn is_valid_extension(env: Env, order: Order, extension: Bytes) -> (bool, ValidationResult) {
+    if MakerTraitsLib::has_extension(env.clone(), order.maker_traits) {
+        if extension.len() == 0 {
+            return (false, ValidationResult::MissingOrderExtension);
+        }
+        if bitand(
+            &env,
+            U256::from_be_bytes(&env, &env.crypto().keccak256(&extension).into()),
+            U256::from_u128(&env, u128::MAX),
+        ) != bitand(&env, order.salt, U256::from_u128(&env, u128::MAX))
+        {
+            return (false, ValidationResult::InvalidExtensionHash);
+        }
+    } else {
+        if extension.len() > 0 {
+            return (false, ValidationResult::UnexpectedOrderExtension);
+        }
+    }
+    (true, ValidationResult::Success)
+}
+
+pub fn post_interaction_target_and_data(env: &Env, _order: &Order, extension: &Bytes) -> Bytes {
+    get_extension(env, extension, DynamicField::PostInteractionData)
+}
+
+pub fn get_extension(env: &Env, extension: &Bytes, field: DynamicField) -> Bytes {
+    // Check if extension has at least 32 bytes (0x20)
+    if extension.len() < 32 {
+        return Bytes::new(env);
+    }
+
+    // Extract offsets from the first 32 bytes
+    let mut offsets_array = [0u8; 32];
+    for i in 0..32 {
+        offsets_array[i] = extension.get(i as u32).unwrap_or(0);
+    }
+    let offsets_bytes = Bytes::from_array(env, &offsets_array);
+    let offsets = U256::from_be_bytes(env, &offsets_bytes);
+
+    // Get the concatenated data (everything after the first 32 bytes)
+    let concat_len = extension.len() - 32;
+    let mut concat_data = Bytes::new(env);
+    for i in 0..concat_len {
+        concat_data.push_back(extension.get((i + 32) as u32).unwrap_or(0));
+    }
+
+    // Get the field data using the offsets
+    get_field_from_offsets(env, offsets, concat_data, field)
+}
+
+fn get_field_from_offsets(env: &Env, offsets: U256, concat: Bytes, field: DynamicField) -> Bytes {
+    // Custom data has no offset of its own: it runs from the end of the last field
+    if matches!(field, DynamicField::CustomData) {
+        let begin: u32 = offsets.shr(224).to_u128().unwrap().try_into().unwrap();
+        if begin > concat.len() {
+            panic!("OffsetOutOfBounds");
+        }
+        return concat.slice(begin..);
+    }
+
+    let field_index = match field {
+        DynamicField::MakerAssetSuffix => 0,
+        DynamicField::TakerAssetSuffix => 1,
+        DynamicField::MakingAmountData => 2,
+        DynamicField::TakingAmountData => 3,
+        DynamicField::Predicate => 4,
+        DynamicField::MakerPermit => 5,
+        DynamicField::PreInteractionData => 6,
+        DynamicField::PostInteractionData => 7,
+        DynamicField::CustomData => 8,
+    };
+
+    // Calculate bit shift: field_index * 32 (equivalent to shl(5, index))
+    let bit_shift = field_index * 32;
+
+    // Extract begin offset: and(0xffffffff, shr(bitShift, shl(32, offsets)))
+    // This gets the 32 bits starting at bitShift position
+    let begin = bitand(
+        env,
+        offsets.shl(32).shr(bit_shift),
+        U256::from_u32(env, 0xffffffff),
+    );
+
+    // Extract end offset: and(0xffffffff, shr(bitShift, offsets))
+    // This gets the 32 bits starting at bitShift position
+    let end_offset = bitand(env, offsets.shr(bit_shift), U256::from_u32(env, 0xffffffff));
+
+    // Convert to u32 for array indexing
+    let begin_u32: u32 = begin.to_u128().unwrap().try_into().unwrap();
+    let end_u32: u32 = end_offset.to_u128().unwrap().try_into().unwrap();
+
+    // Validate bounds - equivalent to if gt(end, concat.length)
+    if end_u32 > concat.len() as u32 {
+        panic!("OffsetOutOfBounds");
+    }
+
+    // Extract the field data
+    if begin_u32 >= end_u32 {
+        return Bytes::new(env);
+    }
+
+    let start: usize = begin_u32 as usize;
+    let end_pos: usize = end_u32 as usize;
+
+    if start >= concat.len() as usize || end_pos > concat.len() as usize {
+        panic!("OffsetOutOfBounds");
+    }
+
+    // Extract the field data using individual byte access
+    let field_len = end_pos - start;
+    let mut field_data = Bytes::new(env);
+    for i in 0..field_len {
+        field_data.push_back(concat.get((start + i) as u32).unwrap_or(0));
+    }
+
+    field_data
+}
+
+pub fn hash(env: &Env, order: &Order, domain_separator: &BytesN<32>) -> BytesN<32> {
+    order_digest(env, order, domain_separator).into()
+}
+
+/// Same as `hash`, kept as a `Hash<32>` so it can be handed to `secp256k1_recover`.
+pub fn order_digest(env: &Env, order: &Order, domain_separator: &BytesN<32>) -> Hash<32> {
+    let typehash = env
+        .crypto()
+        .keccak256(&Bytes::from_slice(env, LIMIT_ORDER_TYPEHASH.as_bytes()));
+
+    // EIP-712 struct encoding: one 32-byte word per field after the typehash.
+    // Stellar addresses do not fit an EVM `address`, so they are `bytes` fields holding
+    // their canonical cross-chain encoding, and dynamic types are encoded as their keccak256.
+    // `makerEvmAddress` is a genuine 20-byte EVM `address`, an atomic type encoded as its
+    // value left padded to 32 bytes. Mixing both is plain EIP-712, which `eth_signTypedData_v4`
+    // wallets sign as is.
+    let mut order_data = Bytes::from_array(env, &typehash.to_array());
+    order_data.append(&order.salt.to_be_bytes());
+    for address in [
+        &order.maker,
+        &order.receiver,
+        &order.maker_asset,
+        &order.taker_asset,
+    ] {
+        let encoded = CrossChainAddress::Stellar(address.clone()).hash(env);
+        order_data.extend_from_array(&encoded.to_array());
+    }
+    order_data.append(&order.making_amount.to_be_bytes());
+    order_data.append(&order.taking_amount.to_be_bytes());
+    order_data.append(&order.maker_traits.to_be_bytes());
+
+    // makerEvmAddress, as `abi.encode(address)` (zero address when unset)
+    let mut maker_evm_address = [0u8; 32];
+    if let Some(address) = &order.maker_evm_address {
+        maker_evm_address[12..].copy_from_slice(&address.to_array());
+    }
+    order_data.extend_from_array(&maker_evm_address);
+
+    // Calculate the hash of the order data
+    let order_hash = env.crypto().keccak256(&order_data);
+
+    // Apply EIP-712 domain separator (equivalent to ECDSA.toTypedDataHash)
+    to_typed_data_hash(env, domain_separator, &order_hash.into())
+}
+
+/**
+ * Computes the EIP-712 domain separator of the current contract and caches it in instance storage.
+ * Without an explicit `chain_id` the id is the network id, i.e. sha256 of the network passphrase,
+ * so signatures made for testnet never verify on a mainnet deployment.
+ *
+ * @param name The signing domain name.
+ * @param version The signing domain version.
+ * @param chain_id Optional EVM-style chain id overriding the network id.
+ * @return BytesN<32> The domain separator.
+ */
+pub fn init_domain_separator(
+    env: &Env,
+    name: &String,
+    version: &String,
+    chain_id: &Option<U256>,
+) -> BytesN<32> {
+    let chain_id = chain_id.clone().unwrap_or_else(|| network_chain_id(env));
+    let domain_separator = build_domain_separator(env, name, version, &chain_id);
+    env.storage()
+        .instance()
+        .set(&DOMAIN_SEPARATOR, &domain_separator);
+    domain_separator
+}
+
+/// Cached domain separator, or the default `XLMOrders` domain on the current network if none was set.
+pub fn domain_separator_v4(env: &Env) -> BytesN<32> {
+    env.storage()
+        .instance()
+        .get(&DOMAIN_SEPARATOR)
+        .unwrap_or_else(|| {
+            build_domain_separator(
+                env,
+                &String::from_str(env, DEFAULT_DOMAIN_NAME),
+                &String::from_str(env, DEFAULT_DOMAIN_VERSION),
+                &network_chain_id(env),
+            )
+        })
+}
+
+/// Chain id derived from the network passphrase.
+pub fn network_chain_id(env: &Env) -> U256 {
+    U256::from_be_bytes(env, &env.ledger().network_id().into())
+}
+
+fn hash_string(env: &Env, value: &String) -> BytesN<32> {
+    // An XDR string is its 4-byte type and 4-byte length followed by the UTF-8 bytes
+    let xdr = value.clone().to_xdr(env);
+    env.crypto()
+        .keccak256(&xdr.slice(8..8 + value.len()))
+        .into()
+}
+
+fn build_domain_separator(
+    env: &Env,
+    name: &String,
+    version: &String,
+    chain_id: &U256,
+) -> BytesN<32> {
+    let type_hash = env
+        .crypto()
+        .keccak256(&Bytes::from_slice(env, EIP712_DOMAIN_TYPEHASH.as_bytes()));
+
+    // typeHash || keccak(name) || keccak(version) || chainId || verifyingContract
+    let mut domain_data = Bytes::from_array(env, &type_hash.to_array());
+    domain_data.extend_from_array(&hash_string(env, name).to_array());
+    domain_data.extend_from_array(&hash_string(env, version).to_array());
+    domain_data.append(&chain_id.to_be_bytes());
+
+    // The verifying contract is the 32-byte contract id, the tail of its address XDR
+    let address_bytes = env.current_contract_address().to_xdr(env);
+    domain_data.append(&address_bytes.slice(address_bytes.len() - 32..));
+
+    // Calculate the domain separator
+    env.crypto().keccak256(&domain_data).into()
+}
+
+/**
+ * Given an already hashed struct, this function returns the hash of the fully encoded EIP712 message for this domain.
+ * This is the Soroban equivalent of the Solidity EIP712._hashTypedDataV4() function.
+ *
+ * @param env The Soroban environment
+ * @param struct_hash The hashed struct
+ * @return BytesN<32> The final EIP-712 hash
+ */
+pub fn hash_typed_data_v4(env: &Env, struct_hash: &BytesN<32>) -> BytesN<32> {
+    to_typed_data_hash(env, &domain_separator_v4(env), struct_hash).into()
+}
+
+/// Port of Solidity ECDSA.toTypedDataHash(): keccak256("\x19\x01" || domainSeparator || structHash)
+pub fn to_typed_data_hash(
+    env: &Env,
+    domain_separator: &BytesN<32>,
+    struct_hash: &BytesN<32>,
+) -> Hash<32> {
+    // Create the final data for hashing (EIP-712 prefix + domain separator + struct hash)
+    let mut final_data = Bytes::from_array(env, &[0x19, 0x01]);
+    final_data.extend_from_array(&domain_separator.to_array());
+    final_data.extend_from_array(&struct_hash.to_array());
+
+    env.crypto().keccak256(&final_data)
+}
@@ -0,0 +1,59 @@
+use soroban_sdk::{crypto::Hash, Bytes, BytesN, Env};
+
+// 1inch Solidity version:
+// https://github.com/1inch/solidity-utils/blob/master/contracts/libraries/ECDSA.sol
+
+/// Half of the secp256k1 curve order. Signatures with a larger `s` are malleable (EIP-2).
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/**
+ * Recovers the Ethereum address that signed `digest` with a compact EIP-2098 signature.
+ * `vs` carries `s` in its lower 255 bits and the recovery id (`v - 27`) in its top bit.
+ *
+ * @param digest The signed 32-byte message hash.
+ * @param r The `r` half of the signature.
+ * @param vs The packed `s` and recovery id.
+ * @return The signer address, or `None` if `r` or `s` is zero or `s` is not in the lower
+ * half of the curve order.
+ */
+pub fn recover(
+    env: &Env,
+    digest: &Hash<32>,
+    r: &BytesN<32>,
+    vs: &BytesN<32>,
+) -> Option<BytesN<20>> {
+    let mut s = vs.to_array();
+    let recovery_id = (s[0] >> 7) as u32;
+    s[0] &= 0x7f;
+
+    // The host traps on zero scalars instead of failing the recovery
+    if s > SECP256K1_HALF_ORDER || s == [0u8; 32] || r.to_array() == [0u8; 32] {
+        return None;
+    }
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&r.to_array());
+    signature[32..].copy_from_slice(&s);
+
+    let public_key =
+        env.crypto()
+            .secp256k1_recover(digest, &BytesN::from_array(env, &signature), recovery_id);
+
+    Some(evm_address(env, &public_key))
+}
+
+/// Ethereum address of an uncompressed SEC-1 public key: the last 20 bytes of keccak256(x || y).
+pub fn evm_address(env: &Env, public_key: &BytesN<65>) -> BytesN<20> {
+    let key = public_key.to_array();
+    let hash = env
+        .crypto()
+        .keccak256(&Bytes::from_slice(env, &key[1..]))
+        .to_array();
+
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    BytesN::from_array(env, &address)
+}
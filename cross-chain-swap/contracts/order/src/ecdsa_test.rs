@@ -0,0 +1,455 @@
+#![cfg(test)]
+extern crate std;
+
+use k256::ecdsa::SigningKey;
+use soroban_sdk::{
+    crypto::Hash,
+    testutils::{Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, TryFromVal, U256,
+};
+
+use crate::{
+    check_signature, ecdsa,
+    test_utils::{create_order_protocol, hex32, sign},
+    Order, OrderProtocolClient,
+};
+use events::{EvmKeyRegistered, EVM_KEY_REGISTERED};
+use order_interface::{AuctionDetails, AuctionWindow};
+use order_lib::xlm_orders::{domain_separator_v4, order_digest};
+use utils::address::account_address;
+
+// Hardhat / Anvil default account #0
+const SIGNER_KEY: [u8; 32] =
+    hex32("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80");
+const SIGNER_ADDRESS: [u8; 20] = [
+    0xf3, 0x9f, 0xd6, 0xe5, 0x1a, 0xad, 0x88, 0xf6, 0xf4, 0xce, 0x6a, 0xb8, 0x82, 0x72, 0x79, 0xcf,
+    0xff, 0xb9, 0x22, 0x66,
+];
+
+// secp256k1 curve order
+const CURVE_ORDER: [u8; 32] =
+    hex32("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+
+/// Raw digest signatures of keccak256(message) by SIGNER_KEY, as produced by
+/// ethers' `SigningKey.sign` (RFC 6979, low-s), encoded as EIP-2098 (r, vs).
+const VECTORS: [(&str, [u8; 32], [u8; 32]); 3] = [
+    (
+        "1inch fusion on stellar",
+        hex32("638869255ce7cfb233a2dfd92503423e5cb07e8f588cfdec81010d8586993090"),
+        // v = 27
+        hex32("46f89ca24b6dcebbbf6d0fa061c492fa130bc783cd224f5e134639aac80286f3"),
+    ),
+    (
+        "order 3",
+        hex32("e4497e71f09b0807d07f15dd911c63c97d07131b0f64dc99fbfa4a17c0f0dfe9"),
+        // v = 28
+        hex32("f53a65de58930c60e9939d30027764bc48ecb40b0a160bae0745803e323efc90"),
+    ),
+    (
+        "order 4",
+        hex32("113fc523b84189d0a4110a80cdb5ff4baacf2061a0d4bbcd2b338ab2d19f54e2"),
+        // v = 28
+        hex32("ec72a306660fb745221cd2d2e55386f048a4a3479bad36e76b0ac789fefc7cf8"),
+    ),
+];
+
+fn message_digest(env: &Env, message: &str) -> Hash<32> {
+    env.crypto()
+        .keccak256(&Bytes::from_slice(env, message.as_bytes()))
+}
+
+/// Ethereum address of the secp256k1 private `key`.
+fn signer_address(env: &Env, key: &[u8; 32]) -> BytesN<20> {
+    let public_key = SigningKey::from_slice(key)
+        .unwrap()
+        .verifying_key()
+        .to_encoded_point(false);
+    ecdsa::evm_address(
+        env,
+        &BytesN::from_array(env, &public_key.as_bytes().try_into().unwrap()),
+    )
+}
+
+/// Binds the order's Ethereum key to its maker, as the maker would.
+fn register_signer(env: &Env, contract_id: &Address, order: &Order) {
+    env.mock_all_auths();
+    OrderProtocolClient::new(env, contract_id)
+        .register_evm_key(&order.maker, &order.maker_evm_address);
+    env.set_auths(&[]);
+}
+
+/// Gives the order real tokens and the maker's allowance over its maker asset. The maker
+/// becomes the issuer of the maker asset, an account that holds it without a trustline.
+fn fund_order(env: &Env, contract_id: &Address, order: &mut Order, taker: &Address) {
+    let maker_asset = env.register_stellar_asset_contract_v2(Address::generate(env));
+    order.maker = maker_asset.issuer().address();
+    order.maker_asset = maker_asset.address();
+    order.taker_asset = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    env.mock_all_auths();
+    StellarAssetClient::new(env, &order.taker_asset).mint(taker, &50);
+    OrderProtocolClient::new(env, contract_id).approve(
+        &order.maker,
+        &order.maker_asset,
+        &100,
+        &100,
+    );
+    env.set_auths(&[]);
+}
+
+fn signed_fill(env: &Env, contract_id: &Address, order: &Order, key: &[u8; 32], taker: &Address) {
+    let digest = env.as_contract(contract_id, || {
+        order_digest(env, order, &domain_separator_v4(env))
+    });
+    let (r, vs) = sign(env, key, &digest.to_array());
+
+    // Only the taker authorizes the fill
+    env.mock_all_auths_allowing_non_root_auth();
+    OrderProtocolClient::new(env, contract_id).fill_order(
+        taker,
+        order,
+        &r,
+        &vs,
+        &U256::from_u32(env, 100),
+        &U256::from_u32(env, 0),
+        taker,
+        &Bytes::new(env),
+        &Bytes::new(env),
+        &AuctionDetails {
+            auction_window: AuctionWindow {
+                start: 0,
+                end: 1000,
+            },
+            taking_amount_start: U256::from_u32(env, 50),
+            taking_amount_end: U256::from_u32(env, 50),
+        },
+    );
+}
+
+fn create_signed_order(env: &Env) -> Order {
+    Order {
+        salt: U256::from_u32(env, 1),
+        maker: account_address(env, &BytesN::from_array(env, &[0x42; 32])),
+        maker_evm_address: Some(BytesN::from_array(env, &SIGNER_ADDRESS)),
+        receiver: Address::generate(env),
+        maker_asset: Address::generate(env),
+        taker_asset: Address::generate(env),
+        making_amount: U256::from_u32(env, 100),
+        taking_amount: U256::from_u32(env, 50),
+        maker_traits: U256::from_u32(env, 0),
+    }
+}
+
+#[test]
+fn test_recover_ethereum_signer_vectors() {
+    let env = Env::default();
+    let signer = BytesN::from_array(&env, &SIGNER_ADDRESS);
+
+    for (message, r, vs) in VECTORS {
+        let recovered = ecdsa::recover(
+            &env,
+            &message_digest(&env, message),
+            &BytesN::from_array(&env, &r),
+            &BytesN::from_array(&env, &vs),
+        );
+        assert_eq!(recovered, Some(signer.clone()));
+    }
+}
+
+#[test]
+fn test_recover_other_digest_gives_other_address() {
+    let env = Env::default();
+    let (_, r, vs) = VECTORS[0];
+
+    let recovered = ecdsa::recover(
+        &env,
+        &message_digest(&env, "cross-chain swap"),
+        &BytesN::from_array(&env, &r),
+        &BytesN::from_array(&env, &vs),
+    );
+    assert_ne!(recovered, Some(BytesN::from_array(&env, &SIGNER_ADDRESS)));
+}
+
+#[test]
+fn test_recover_rejects_high_s() {
+    let env = Env::default();
+    let (message, r, _) = VECTORS[0];
+
+    // Smallest `s` above half the curve order that still leaves the parity bit clear
+    let high_s = U256::from_be_bytes(&env, &Bytes::from_array(&env, &CURVE_ORDER))
+        .shr(1)
+        .add(&U256::from_u32(&env, 1))
+        .to_be_bytes();
+
+    let recovered = ecdsa::recover(
+        &env,
+        &message_digest(&env, message),
+        &BytesN::from_array(&env, &r),
+        &high_s.try_into().unwrap(),
+    );
+    assert_eq!(recovered, None);
+}
+
+#[test]
+fn test_recover_rejects_zero_signature() {
+    let env = Env::default();
+    let (message, r, _) = VECTORS[0];
+    let zero = BytesN::from_array(&env, &[0; 32]);
+
+    let digest = message_digest(&env, message);
+    assert_eq!(ecdsa::recover(&env, &digest, &zero, &zero), None);
+    assert_eq!(
+        ecdsa::recover(&env, &digest, &BytesN::from_array(&env, &r), &zero),
+        None
+    );
+}
+
+#[test]
+fn test_evm_address_from_public_key() {
+    let env = Env::default();
+    let public_key = SigningKey::from_slice(&SIGNER_KEY)
+        .unwrap()
+        .verifying_key()
+        .to_encoded_point(false);
+
+    let address = ecdsa::evm_address(
+        &env,
+        &BytesN::from_array(&env, &public_key.as_bytes().try_into().unwrap()),
+    );
+    assert_eq!(address, BytesN::from_array(&env, &SIGNER_ADDRESS));
+}
+
+#[test]
+fn test_check_signature_accepts_maker_signature() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let order = create_signed_order(&env);
+    register_signer(&env, &contract_id, &order);
+
+    env.as_contract(&contract_id, || {
+        let digest = order_digest(&env, &order, &domain_separator_v4(&env));
+        let (r, vs) = sign(&env, &SIGNER_KEY, &digest.to_array());
+
+        assert!(check_signature(&env, &order, &r, &vs));
+    });
+}
+
+#[test]
+fn test_check_signature_rejects_other_signer() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let order = create_signed_order(&env);
+
+    env.as_contract(&contract_id, || {
+        let digest = order_digest(&env, &order, &domain_separator_v4(&env));
+        let (r, vs) = sign(&env, &[0x11; 32], &digest.to_array());
+
+        assert!(!check_signature(&env, &order, &r, &vs));
+    });
+}
+
+#[test]
+fn test_check_signature_rejects_key_not_registered_by_maker() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let order = create_signed_order(&env);
+
+    env.as_contract(&contract_id, || {
+        let digest = order_digest(&env, &order, &domain_separator_v4(&env));
+        let (r, vs) = sign(&env, &SIGNER_KEY, &digest.to_array());
+
+        assert!(!check_signature(&env, &order, &r, &vs));
+    });
+}
+
+#[test]
+fn test_register_evm_key() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+    let order = create_signed_order(&env);
+
+    register_signer(&env, &contract_id, &order);
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, contract_id);
+    assert_eq!(
+        topics,
+        (EVM_KEY_REGISTERED, order.maker.clone()).into_val(&env)
+    );
+    assert_eq!(
+        EvmKeyRegistered::try_from_val(&env, &data).unwrap(),
+        EvmKeyRegistered {
+            maker: order.maker.clone(),
+            evm_address: order.maker_evm_address.clone(),
+        }
+    );
+    assert_eq!(client.evm_key(&order.maker), order.maker_evm_address);
+
+    // Unbinding the key stops its signatures from authorizing orders
+    env.mock_all_auths();
+    client.register_evm_key(&order.maker, &None);
+    assert_eq!(client.evm_key(&order.maker), None);
+    env.as_contract(&contract_id, || {
+        let digest = order_digest(&env, &order, &domain_separator_v4(&env));
+        let (r, vs) = sign(&env, &SIGNER_KEY, &digest.to_array());
+        assert!(!check_signature(&env, &order, &r, &vs));
+    });
+}
+
+#[test]
+#[should_panic]
+fn test_register_evm_key_requires_maker_auth() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let order = create_signed_order(&env);
+
+    OrderProtocolClient::new(&env, &contract_id)
+        .register_evm_key(&order.maker, &order.maker_evm_address);
+}
+
+#[test]
+fn test_fill_order_with_registered_evm_key() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let taker = Address::generate(&env);
+    let mut order = create_signed_order(&env);
+    fund_order(&env, &contract_id, &mut order, &taker);
+    register_signer(&env, &contract_id, &order);
+
+    signed_fill(&env, &contract_id, &order, &SIGNER_KEY, &taker);
+
+    // The signature stands in for the maker, whose tokens move through the allowance
+    assert!(env
+        .auths()
+        .into_iter()
+        .all(|(address, _)| address != order.maker));
+    assert_eq!(
+        TokenClient::new(&env, &order.maker_asset).balance(&taker),
+        100
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid signature")]
+fn test_fill_order_rejects_third_party_key_for_approved_maker() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let taker = Address::generate(&env);
+
+    // The victim approved the order contract and bound its own key
+    let mut order = create_signed_order(&env);
+    fund_order(&env, &contract_id, &mut order, &taker);
+    register_signer(&env, &contract_id, &order);
+
+    // The attacker names the victim as maker and signs with its own key
+    let attacker_key = [0x11; 32];
+    order.maker_evm_address = Some(signer_address(&env, &attacker_key));
+    signed_fill(&env, &contract_id, &order, &attacker_key, &taker);
+}
+
+#[test]
+fn test_check_signature_rejects_order_without_evm_maker() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let mut order = create_signed_order(&env);
+
+    env.as_contract(&contract_id, || {
+        let digest = order_digest(&env, &order, &domain_separator_v4(&env));
+        let (r, vs) = sign(&env, &SIGNER_KEY, &digest.to_array());
+
+        order.maker_evm_address = None;
+        assert!(!check_signature(&env, &order, &r, &vs));
+    });
+}
+
+#[test]
+#[should_panic(expected = "Invalid signature")]
+fn test_fill_order_rejects_tampered_order() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+    let mut order = create_signed_order(&env);
+
+    let digest = env.as_contract(&contract_id, || {
+        order_digest(&env, &order, &domain_separator_v4(&env))
+    });
+    let (r, vs) = sign(&env, &SIGNER_KEY, &digest.to_array());
+
+    // Taker tries to receive more than the maker signed for
+    order.making_amount = U256::from_u32(&env, 1000);
+
+    env.mock_all_auths();
+    client.fill_order(
+        &Address::generate(&env),
+        &order,
+        &r,
+        &vs,
+        &U256::from_u32(&env, 50),
+        &U256::from_u32(&env, 0),
+        &Address::generate(&env),
+        &Bytes::new(&env),
+        &Bytes::new(&env),
+        &AuctionDetails {
+            auction_window: AuctionWindow {
+                start: 0,
+                end: 1000,
+            },
+            taking_amount_start: U256::from_u32(&env, 100),
+            taking_amount_end: U256::from_u32(&env, 10),
+        },
+    );
+}
+
+fn deploy_on_network(env: &Env, passphrase: &str) -> Address {
+    let network_id = env
+        .crypto()
+        .sha256(&Bytes::from_slice(env, passphrase.as_bytes()));
+    env.ledger().set_network_id(network_id.to_array());
+    create_order_protocol(env, None)
+}
+
+fn xdr_bytes<T: ToXdr>(env: &Env, value: T) -> std::vec::Vec<u8> {
+    value.to_xdr(env).iter().collect()
+}
+
+#[test]
+fn test_testnet_signature_rejected_on_mainnet() {
+    let testnet = Env::default();
+    let testnet_contract = deploy_on_network(&testnet, "Test SDF Network ; September 2015");
+    let testnet_order = create_signed_order(&testnet);
+    register_signer(&testnet, &testnet_contract, &testnet_order);
+    let (r, vs) = testnet.as_contract(&testnet_contract, || {
+        let digest = order_digest(&testnet, &testnet_order, &domain_separator_v4(&testnet));
+        let (r, vs) = sign(&testnet, &SIGNER_KEY, &digest.to_array());
+        assert!(check_signature(&testnet, &testnet_order, &r, &vs));
+        (r.to_array(), vs.to_array())
+    });
+
+    let mainnet = Env::default();
+    let mainnet_contract =
+        deploy_on_network(&mainnet, "Public Global Stellar Network ; September 2015");
+    let mainnet_order = create_signed_order(&mainnet);
+    register_signer(&mainnet, &mainnet_contract, &mainnet_order);
+
+    // Same contract address and order on both networks, only the passphrase differs
+    assert_eq!(
+        xdr_bytes(&testnet, testnet_contract),
+        xdr_bytes(&mainnet, mainnet_contract.clone())
+    );
+    assert_eq!(
+        xdr_bytes(&testnet, testnet_order),
+        xdr_bytes(&mainnet, mainnet_order.clone())
+    );
+
+    mainnet.as_contract(&mainnet_contract, || {
+        assert!(!check_signature(
+            &mainnet,
+            &mainnet_order,
+            &BytesN::from_array(&mainnet, &r),
+            &BytesN::from_array(&mainnet, &vs)
+        ));
+    });
+}
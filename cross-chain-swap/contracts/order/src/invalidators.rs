@@ -0,0 +1,110 @@
+use soroban_sdk::{contracttype, Address, BytesN, Env, U256};
+use utils::math::{bit_or, bitand};
+
+use crate::STORAGE_TTL;
+
+// 1inch Solidity versions:
+// https://github.com/1inch/limit-order-protocol/blob/master/contracts/libraries/BitInvalidatorLib.sol
+// https://github.com/1inch/limit-order-protocol/blob/master/contracts/libraries/RemainingInvalidatorLib.sol
+
+#[contracttype]
+#[derive(Clone)]
+pub enum InvalidatorKey {
+    /// One slot of a maker's bit invalidator, covering 256 consecutive nonces
+    Bits(Address, u64),
+    /// Remaining making amount of an order that was already partially filled
+    Remaining(Address, BytesN<32>),
+}
+
+/// Invalidates single-fill orders by flipping the bit of their nonce.
+pub struct BitInvalidatorLib;
+
+impl BitInvalidatorLib {
+    /// Returns the invalidator slot holding `nonce`.
+    pub fn check_slot(env: &Env, maker: &Address, nonce: u64) -> U256 {
+        env.storage()
+            .persistent()
+            .get(&InvalidatorKey::Bits(maker.clone(), nonce >> 8))
+            .unwrap_or(U256::from_u32(env, 0))
+    }
+
+    /// Whether `nonce` was already used.
+    pub fn is_invalidated(env: &Env, maker: &Address, nonce: u64) -> bool {
+        let bit = U256::from_u32(env, 1).shl((nonce & 0xff) as u32);
+        bitand(env, Self::check_slot(env, maker, nonce), bit) != U256::from_u32(env, 0)
+    }
+
+    /// Marks `nonce` as used, panicking if it already was.
+    pub fn check_and_invalidate(env: &Env, maker: &Address, nonce: u64) {
+        if Self::is_invalidated(env, maker, nonce) {
+            panic!("BitInvalidatedOrder");
+        }
+        let bit = U256::from_u32(env, 1).shl((nonce & 0xff) as u32);
+        let slot = Self::check_slot(env, maker, nonce);
+
+        STORAGE_TTL.set(
+            env,
+            &InvalidatorKey::Bits(maker.clone(), nonce >> 8),
+            &bit_or(env, slot, bit),
+        );
+    }
+
+    /// Marks `nonce` and the bits of `additional_mask` in its slot as used.
+    /// Returns the updated slot.
+    pub fn mass_invalidate(env: &Env, maker: &Address, nonce: u64, additional_mask: U256) -> U256 {
+        let bit = U256::from_u32(env, 1).shl((nonce & 0xff) as u32);
+        let slot = bit_or(
+            env,
+            Self::check_slot(env, maker, nonce),
+            bit_or(env, bit, additional_mask),
+        );
+
+        STORAGE_TTL.set(env, &InvalidatorKey::Bits(maker.clone(), nonce >> 8), &slot);
+        slot
+    }
+}
+
+/// Tracks how much of a partially fillable order is left.
+pub struct RemainingInvalidatorLib;
+
+impl RemainingInvalidatorLib {
+    /// Remaining making amount of the order, `order_making_amount` if it was never filled.
+    pub fn remaining(
+        env: &Env,
+        maker: &Address,
+        order_hash: &BytesN<32>,
+        order_making_amount: &U256,
+    ) -> U256 {
+        env.storage()
+            .persistent()
+            .get(&InvalidatorKey::Remaining(
+                maker.clone(),
+                order_hash.clone(),
+            ))
+            .unwrap_or(order_making_amount.clone())
+    }
+
+    /// Records a fill of `making_amount` out of `remaining_making_amount`.
+    pub fn remains(
+        env: &Env,
+        maker: &Address,
+        order_hash: &BytesN<32>,
+        remaining_making_amount: &U256,
+        making_amount: &U256,
+    ) {
+        STORAGE_TTL.set(
+            env,
+            &InvalidatorKey::Remaining(maker.clone(), order_hash.clone()),
+            &remaining_making_amount.sub(making_amount),
+        );
+    }
+
+    /// Marks the order as fully filled, so that nothing of it can be filled anymore.
+    pub fn fully_fill(env: &Env, maker: &Address, order_hash: &BytesN<32>) {
+        STORAGE_TTL.set(
+            env,
+            &InvalidatorKey::Remaining(maker.clone(), order_hash.clone()),
+            &U256::from_u32(env, 0),
+        );
+    }
+}
@@ -1,111 +1,220 @@
 #![no_std]
 
-use crate::taker_traits::TakerTraitsLib;
 use crate::{
+    invalidators::{BitInvalidatorLib, InvalidatorKey, RemainingInvalidatorLib},
+    settlement::{NetSettlement, SettlementKey, SettlementLib},
+    whitelist::ResolverWhitelist,
+};
+use dutch_auction_interface::AmountCalculatorClient;
+use events::{BitInvalidatorUpdated, EvmKeyRegistered, OrderCancelled, OrderFilled};
+use order_interface::{
+    AuctionDetails, BatchFill, FeeSplit, FillQuote, FillStatus, MakerPermit, Order, OrderInterface,
+    PostInteractionClient, PreInteractionClient, TakerInteractionClient,
+};
+use order_lib::{
+    fees::{self, FeeConfig},
     maker_traits::MakerTraitsLib,
-    xlm_orders::{domain_separator_v4, hash},
+    taker_args::TakerArgs,
+    taker_traits::TakerTraitsLib,
+    xlm_orders::{
+        domain_separator_v4, get_extension, hash, init_domain_separator, is_valid_extension,
+        order_digest, DynamicField, ValidationResult,
+    },
 };
-use dutch_auction_interface::DutchAuctionCalculatorContractClient;
-use order_interface::{AuctionDetails, Order, OrderInterface};
 use soroban_sdk::{
-    contract, contractimpl,
-    crypto::Hash,
-    symbol_short,
-    token::TokenClient,
-    xdr::{FromXdr, ToXdr},
-    Address, Bytes, BytesN, Env, Symbol, U256,
+    contract, contractimpl, contracttype, symbol_short, Address, Bytes, BytesN, Env, IntoVal,
+    String, Symbol, Vec, U256,
+};
+use utils::{
+    address::{contract_address, is_contract_address},
+    math::min_num,
+    ttl::{StoredTtlPolicy, TtlPolicy},
 };
-use utils::math::min_num;
-pub mod consts_trait;
-pub mod maker_traits;
-pub mod taker_traits;
-pub mod xlm_orders;
+pub mod ecdsa;
+pub mod invalidators;
+pub mod settlement;
+pub mod whitelist;
 
 const DUTCH_AUCTION_CALCULATOR_ADDRESS_KEY: Symbol = symbol_short!("DA_ADDY");
-/// Order filled event
-const ORDER_FILLED_EVENT_KEY: Symbol = symbol_short!("ORDR_F");
-/**
- * OrderFilled(
- *   bytes32 orderHash,
- *   uint256 remainingMakingAmount
- * )
- */
 
-fn check_signature(env: &Env, order: Order, r: BytesN<32>, vs: BytesN<32>) -> bool {
-    let order_hash = hash(env, &order, &domain_separator_v4(env));
-    let maker = order.maker;
-
-    // types are tricky. The only way to get a Hash<32> is to use sha-3 first
-    let _hash = Hash::from(env.crypto().keccak256(&order_hash.try_into().unwrap()));
-
-    let mut signature = Bytes::new(env);
-    signature.extend_from_array(&r.to_array());
-    signature.extend_from_array(&vs.to_array());
+/// TTL policy of the invalidators and allowance records, which live in persistent storage
+pub const STORAGE_TTL: StoredTtlPolicy = StoredTtlPolicy;
+
+/// Persistent entries of the order contract, by kind
+#[contracttype]
+#[derive(Clone)]
+pub enum StorageKey {
+    Invalidator(InvalidatorKey),
+    Allowance(SettlementKey),
+    /// Ethereum key a maker registered to sign its orders, stored under this key itself
+    EvmKey(Address),
+}
 
-    let recovered_maker = env
-        .crypto()
-        .secp256k1_recover(&_hash, &signature.try_into().unwrap(), 0);
-    let address_bytes = Bytes::from_xdr(&env, &maker.to_xdr(&env));
-    if address_bytes.is_err() {
-        panic!("Failed to convert address to bytes");
+/**
+ * Checks that the order was signed by its maker's Ethereum key.
+ * Anyone can name any key in an order, so the key must be the one the maker registered
+ * through `register_evm_key`, otherwise a signature would spend another maker's allowance.
+ * @param r The `r` half of the compact EIP-2098 signature.
+ * @param vs The packed `s` and recovery id of the signature.
+ *
+ * @return Whether the recovered signer is `order.maker_evm_address`, registered by the maker.
+ */
+fn check_signature(env: &Env, order: &Order, r: &BytesN<32>, vs: &BytesN<32>) -> bool {
+    let Some(maker_evm_address) = &order.maker_evm_address else {
+        return false;
+    };
+    let registered: Option<BytesN<20>> = env
+        .storage()
+        .persistent()
+        .get(&StorageKey::EvmKey(order.maker.clone()));
+    if registered.as_ref() != Some(maker_evm_address) {
+        return false;
     }
 
-    let recovered_maker_address = Bytes::from_xdr(&env, &recovered_maker.to_xdr(&env));
-    if recovered_maker_address.is_err() {
-        panic!("Failed to convert recovered maker to address");
-    }
+    let digest = order_digest(env, order, &domain_separator_v4(env));
 
-    return address_bytes.unwrap() == recovered_maker_address.unwrap();
+    ecdsa::recover(env, &digest, r, vs).as_ref() == Some(maker_evm_address)
 }
 
 /**
- * Parses the taker traits and args to get the target, extension, and interaction.
- * @param taker_traits The taker traits.
- * @param args The args.
- * @return target The target address.
- * @return extension The extension.
- * @return interaction The interaction calldata.
+ * Checks that the maker authorized the order.
+ * Orders carrying a `maker_evm_address` are signed by an Ethereum key (`r`, `vs`) the maker
+ * registered.
+ * All other orders are authorized by the maker account itself through Soroban auth on
+ * the order hash, which covers ed25519 keys as well as custom and multisig accounts.
+ * A maker permit is authorized by the maker account alongside the order hash, in the
+ * same authorization as the order itself for Stellar-native makers.
  *
- * @return The target, extension, and interaction.
+ * Contract makers are verified by their custom account `__check_auth`, the ERC-1271
+ * equivalent. As with `recoverOrIsValidSignature`, an order of a contract maker whose
+ * Ethereum signature does not recover falls back to that check.
  */
-fn parse_args(env: Env, taker_traits: U256, args: Bytes) -> (Address, Bytes, Bytes) {
-    let target: Address;
+fn check_maker_authorization(
+    env: &Env,
+    order: &Order,
+    order_hash: &BytesN<32>,
+    r: &BytesN<32>,
+    vs: &BytesN<32>,
+    permit: &Option<MakerPermit>,
+) {
+    let signed_by_evm_key = order.maker_evm_address.is_some() && check_signature(env, order, r, vs);
+    if order.maker_evm_address.is_some()
+        && !signed_by_evm_key
+        && !is_contract_address(env, &order.maker)
+    {
+        panic!("Invalid signature");
+    }
 
-    let mut args = args.clone();
+    match permit {
+        Some(permit) => order
+            .maker
+            .require_auth_for_args((order_hash.clone(), permit.clone()).into_val(env)),
+        None if !signed_by_evm_key => order
+            .maker
+            .require_auth_for_args((order_hash.clone(),).into_val(env)),
+        None => {}
+    }
+}
 
-    if TakerTraitsLib::args_has_target(&env, taker_traits.clone()) {
-        let target_xdr = Address::from_xdr(&env, &args.clone().to_xdr(&env));
-        if target_xdr.is_err() {
-            panic!("Failed to convert args to address");
-        }
-        target = target_xdr.unwrap();
-        args = args.slice(20..);
+/// Making amount left to fill. Orders using the bit invalidator are filled at most once,
+/// so their remaining amount is the whole order until the nonce is used.
+fn remaining_making_amount(env: &Env, order: &Order, order_hash: &BytesN<32>) -> U256 {
+    if MakerTraitsLib::use_bit_invalidator(env, order.maker_traits.clone()) {
+        order.making_amount.clone()
     } else {
-        target = env.current_contract_address();
+        RemainingInvalidatorLib::remaining(env, &order.maker, order_hash, &order.making_amount)
     }
+}
 
-    let extension_length = TakerTraitsLib::args_extension_length(&env, taker_traits.clone());
-    let extension: Bytes = if extension_length > U256::from_u32(&env, 0) {
-        let len = extension_length.to_u128().unwrap() as u32;
-        let extension = args.slice(..len);
-        let _ = args.slice(len..);
-        extension
-    } else {
-        Bytes::new(&env)
-    };
+/// Reverts a fill with the message matching `status`.
+fn fail(status: FillStatus) -> ! {
+    match status {
+        FillStatus::Fillable => panic!("Fillable order rejected"),
+        FillStatus::InvalidatedOrder => panic!("Invalidated order"),
+        FillStatus::BitInvalidatedOrder => panic!("BitInvalidatedOrder"),
+        FillStatus::PrivateOrder => panic!("Private order"),
+        FillStatus::OrderExpired => panic!("Order expired"),
+        FillStatus::IncompatibleEpochManager => {
+            panic!("Epoch manager and bit invalidators are incompatible")
+        }
+        FillStatus::MissingOrderExtension => panic!("MissingOrderExtension"),
+        FillStatus::InvalidExtensionHash => panic!("InvalidExtensionHash"),
+        FillStatus::UnexpectedOrderExtension => panic!("UnexpectedOrderExtension"),
+        FillStatus::PredicateNotSupported => panic!("Predicate not supported"),
+        FillStatus::TakingAmountTooHigh => panic!("Taking amount too high"),
+        FillStatus::MakingAmountTooLow => panic!("Making amount too low"),
+        FillStatus::TakingAmountExceeded => panic!("Taking amount exceeded"),
+        FillStatus::PartialFillNotAllowed => panic!("Partial fill not allowed"),
+        FillStatus::SwapWithZeroAmount => panic!("Swap with zero amount"),
+        FillStatus::ResolverNotWhitelisted => panic!("Resolver not whitelisted"),
+        FillStatus::ResolvingWindowNotOpen => panic!("Resolving window not open"),
+    }
+}
 
-    let interaction_length = TakerTraitsLib::args_interaction_length(&env, taker_traits.clone());
+/// Post interaction, where maker can handle funds interactively.
+fn post_interaction(
+    env: &Env,
+    taker: &Address,
+    order: &Order,
+    extension: &Bytes,
+    order_hash: &BytesN<32>,
+    making_amount: &U256,
+    taking_amount: &U256,
+    remaining_making_amount: &U256,
+) {
+    if MakerTraitsLib::need_post_interaction_call(env, order.maker_traits.clone()) {
+        let data = get_extension(env, extension, DynamicField::PostInteractionData);
+        if let Some((listener, extra_data)) = interaction_target_and_data(env, &data) {
+            PostInteractionClient::new(env, &listener).post_interaction(
+                order,
+                extension,
+                order_hash,
+                taker,
+                making_amount,
+                taking_amount,
+                remaining_making_amount,
+                &extra_data,
+            );
+        }
+    }
+}
 
-    let interaction: Bytes = if interaction_length > U256::from_u32(&env, 0) {
-        let len = interaction_length.to_u128().unwrap() as u32;
-        let interaction = args.slice(..len);
-        let _ = args.slice(len..);
-        interaction
-    } else {
-        Bytes::new(&env)
-    };
+/// Taker interaction, where taker can source the taker assets just in time.
+fn taker_interaction(
+    env: &Env,
+    taker: &Address,
+    order: &Order,
+    extension: &Bytes,
+    order_hash: &BytesN<32>,
+    making_amount: &U256,
+    taking_amount: &U256,
+    remaining_making_amount: &U256,
+    interaction: &Bytes,
+) {
+    if let Some((listener, extra_data)) = interaction_target_and_data(env, interaction) {
+        TakerInteractionClient::new(env, &listener).taker_interaction(
+            order,
+            extension,
+            order_hash,
+            taker,
+            making_amount,
+            taking_amount,
+            remaining_making_amount,
+            &extra_data,
+        );
+    }
+}
 
-    (target, extension, interaction)
+/**
+ * Splits interaction data into its listener and the extra data passed to it.
+ * The listener is given by its 32-byte contract id, followed by the extra data.
+ */
+fn interaction_target_and_data(env: &Env, data: &Bytes) -> Option<(Address, Bytes)> {
+    if data.len() < 32 {
+        return None;
+    }
+    let listener_id: BytesN<32> = data.slice(..32).try_into().unwrap();
+    Some((contract_address(env, &listener_id), data.slice(32..)))
 }
 
 #[contract]
@@ -113,10 +222,29 @@ pub struct OrderProtocol;
 
 #[contractimpl]
 impl OrderInterface for OrderProtocol {
-    fn __constructor(env: Env, da_addy: Address) {
+    fn __constructor(
+        env: Env,
+        da_addy: Address,
+        name: String,
+        version: String,
+        chain_id: Option<U256>,
+        wrapped_native: Option<Address>,
+        ttl_policy: Option<TtlPolicy>,
+    ) {
+        STORAGE_TTL.init(&env, &ttl_policy);
         env.storage()
             .instance()
             .set(&DUTCH_AUCTION_CALCULATOR_ADDRESS_KEY, &da_addy);
+        init_domain_separator(&env, &name, &version, &chain_id);
+        SettlementLib::set_wrapped_native(&env, &wrapped_native);
+    }
+
+    fn domain_separator(env: Env) -> BytesN<32> {
+        domain_separator_v4(&env)
+    }
+
+    fn wrapped_native(env: Env) -> Option<Address> {
+        SettlementLib::wrapped_native(&env)
     }
 
     fn calculate_making_amount(
@@ -133,7 +261,7 @@ impl OrderInterface for OrderProtocol {
             .instance()
             .get(&DUTCH_AUCTION_CALCULATOR_ADDRESS_KEY)
             .unwrap();
-        let da_client = DutchAuctionCalculatorContractClient::new(&env, &da_addy);
+        let da_client = AmountCalculatorClient::new(&env, &da_addy);
 
         let da_result = da_client.get_making_amount(
             &order,
@@ -162,7 +290,7 @@ impl OrderInterface for OrderProtocol {
             .instance()
             .get(&DUTCH_AUCTION_CALCULATOR_ADDRESS_KEY)
             .unwrap();
-        let da_client = DutchAuctionCalculatorContractClient::new(&env, &da_addy);
+        let da_client = AmountCalculatorClient::new(&env, &da_addy);
 
         let da_result = da_client.get_taking_amount(
             &order,
@@ -177,170 +305,290 @@ impl OrderInterface for OrderProtocol {
         return da_result;
     }
 
-    fn fill(
-        env: Env,
-        order: Order,
-        order_hash: BytesN<32>,
-        remaining_making_amount: U256,
-        _amount: U256,
-        _taker_traits: U256,
-        _target: Address,
-        _extension: Bytes,
-        _interaction: Bytes,
-        auction_details: AuctionDetails,
-    ) {
-        // ignoring _extension validation phase.
+    fn cancel_order(env: Env, maker: Address, maker_traits: U256, order_hash: BytesN<32>) {
+        maker.require_auth();
+        Self::cancel(&env, &maker, maker_traits, order_hash);
+    }
 
-        if !MakerTraitsLib::is_allowed_sender(&env, order.maker_traits.clone(), _target) {
-            panic!("Private order");
+    fn cancel_orders(env: Env, maker: Address, orders: Vec<(U256, BytesN<32>)>) {
+        maker.require_auth();
+        for (maker_traits, order_hash) in orders.iter() {
+            Self::cancel(&env, &maker, maker_traits, order_hash);
         }
+    }
 
-        if MakerTraitsLib::is_expired(&env, order.maker_traits.clone()) {
-            panic!("Order expired");
-        }
+    fn approve(env: Env, maker: Address, token: Address, amount: i128, expiration_ledger: u32) {
+        maker.require_auth();
+        STORAGE_TTL.extend_instance(&env);
+        SettlementLib::approve(&env, &maker, &token, amount, expiration_ledger);
+    }
 
-        if MakerTraitsLib::need_check_epoch_manager(&env, order.maker_traits.clone()) {
-            if MakerTraitsLib::use_bit_invalidator(&env, order.maker_traits.clone()) {
-                panic!("Epoch manager and bit invalidators are incompatible");
-            }
-            // todo: @Skanislav implement check:
-            // if (!epochEquals(order.maker.get(), order.makerTraits.series(), order.makerTraits.nonceOrEpoch())) revert WrongSeriesNonce();
+    fn register_evm_key(env: Env, maker: Address, evm_address: Option<BytesN<20>>) {
+        maker.require_auth();
+        STORAGE_TTL.extend_instance(&env);
+        let key = StorageKey::EvmKey(maker.clone());
+        match &evm_address {
+            Some(evm_address) => STORAGE_TTL.set(&env, &key, evm_address),
+            None => env.storage().persistent().remove(&key),
         }
+        EvmKeyRegistered { maker, evm_address }.publish(&env);
+    }
 
-        // ignoring extension predicate check.
+    fn evm_key(env: Env, maker: Address) -> Option<BytesN<20>> {
+        env.storage().persistent().get(&StorageKey::EvmKey(maker))
+    }
 
-        // Checks if the taking amount should be calculated based on making amount.
-        let is_making_amount = TakerTraitsLib::is_making_amount(&env, &_taker_traits.clone()); // takerTraits.isMakingAmount
-        if is_making_amount {
-            let making_amount = min_num(&order.making_amount, &remaining_making_amount);
-
-            let _taking_amount = Self::calculate_taking_amount(
-                env.clone(),
-                order.clone(),
-                _extension,
-                making_amount.clone(),
-                remaining_making_amount.clone(),
-                order_hash,
-                auction_details,
-            );
+    #[allow(non_snake_case)]
+    fn _check_remaining_making_amount(env: Env, order: Order, order_hash: BytesN<32>) -> U256 {
+        let remaining_making_amount = remaining_making_amount(&env, &order, &order_hash);
+        if remaining_making_amount == U256::from_u32(&env, 0) {
+            fail(FillStatus::InvalidatedOrder);
+        }
+        remaining_making_amount
+    }
 
-            let threshold: U256 = TakerTraitsLib::threshold(&env, _taker_traits.clone());
+    fn quote_fill(
+        env: Env,
+        taker: Address,
+        order: Order,
+        extension: Bytes,
+        amount: U256,
+        taker_traits: U256,
+        auction_details: AuctionDetails,
+    ) -> FillQuote {
+        let order_hash = hash(&env, &order, &domain_separator_v4(&env));
+        let remaining_making_amount = remaining_making_amount(&env, &order, &order_hash);
+        let zero = U256::from_u32(&env, 0);
 
-            if threshold > U256::from_u32(&env, 0) {
-                if _amount == *making_amount {
-                    if _taking_amount > threshold {
-                        panic!("Taking amount too high");
-                    }
-                    if _taking_amount.mul(&_amount) > threshold.mul(&making_amount) {
-                        panic!("Taking amount too high");
-                    }
-                }
-            }
+        let result = if remaining_making_amount == zero {
+            Err(FillStatus::InvalidatedOrder)
         } else {
-            let taking_amount = _amount.clone();
-            let making_amount = Self::calculate_making_amount(
-                env.clone(),
-                order.clone(),
-                _extension.clone(),
-                taking_amount.clone(),
-                remaining_making_amount.clone(),
-                order_hash.clone(),
-                auction_details.clone(),
-            );
+            Self::check_fill(
+                &env,
+                &taker,
+                &order,
+                &order_hash,
+                &remaining_making_amount,
+                &amount,
+                &taker_traits,
+                &extension,
+                &auction_details,
+            )
+        };
+
+        match result {
+            Ok((making_amount, taking_amount, fees)) => FillQuote {
+                making_amount,
+                taking_amount,
+                remaining_making_amount,
+                fees,
+                status: FillStatus::Fillable,
+            },
+            Err(status) => FillQuote {
+                making_amount: zero.clone(),
+                taking_amount: zero.clone(),
+                remaining_making_amount,
+                fees: fees::split(&env, &None, &order, &zero, zero.clone()),
+                status,
+            },
+        }
+    }
 
-            if making_amount > remaining_making_amount {
-                let making_amount = remaining_making_amount.clone();
-                let taking_amount = Self::calculate_taking_amount(
-                    env.clone(),
-                    order.clone(),
-                    _extension.clone(),
-                    making_amount.clone(),
-                    remaining_making_amount.clone(),
-                    order_hash.clone(),
-                    auction_details.clone(),
-                );
+    fn order_hash(env: Env, order: Order) -> BytesN<32> {
+        hash(&env, &order, &domain_separator_v4(&env))
+    }
 
-                if taking_amount > _amount {
-                    panic!("Taking amount exceeded");
-                }
-            }
+    fn fill_order(
+        env: Env,
+        taker: Address,
+        order: Order,
+        r: BytesN<32>,
+        vs: BytesN<32>,
+        amount: U256,
+        taker_traits: U256,
+        target: Address,
+        extension: Bytes,
+        interaction: Bytes,
+        auction_details: AuctionDetails,
+    ) -> (U256, U256, BytesN<32>) {
+        taker.require_auth();
 
-            let threshold: U256 = TakerTraitsLib::threshold(&env, _taker_traits.clone());
+        let (making_amount, taking_amount, order_hash, _) = Self::fill_authorized_order(
+            &env,
+            &taker,
+            order,
+            r,
+            vs,
+            amount,
+            taker_traits,
+            target,
+            extension,
+            interaction,
+            auction_details,
+            None,
+        );
+        (making_amount, taking_amount, order_hash)
+    }
 
-            if threshold > U256::from_u32(&env, 0) {
-                if _amount == taking_amount {
-                    if making_amount < threshold {
-                        panic!("Making amount too low");
-                    }
-                    if making_amount.mul(&_amount) < threshold.mul(&taking_amount) {
-                        panic!("Making amount too low");
-                    }
-                }
-            }
+    fn fill_order_args(
+        env: Env,
+        taker: Address,
+        order: Order,
+        r: BytesN<32>,
+        vs: BytesN<32>,
+        amount: U256,
+        taker_traits: U256,
+        args: Bytes,
+        auction_details: AuctionDetails,
+    ) -> (U256, U256, BytesN<32>) {
+        let args = TakerArgs::decode(&env, &taker_traits, &args);
+        return Self::fill_order(
+            env,
+            taker.clone(),
+            order,
+            r,
+            vs,
+            amount,
+            taker_traits,
+            args.target.unwrap_or(taker),
+            args.extension,
+            args.interaction,
+            auction_details,
+        );
+    }
 
-            if !MakerTraitsLib::allow_partial_fills(&env, order.maker_traits.clone())
-                && making_amount != order.making_amount
+    fn fill_orders(
+        env: Env,
+        taker: Address,
+        fills: Vec<BatchFill>,
+        net_settlement: bool,
+    ) -> Vec<(U256, U256, BytesN<32>)> {
+        taker.require_auth();
+
+        let mut net = if net_settlement {
+            Some(NetSettlement::new(&env))
+        } else {
+            None
+        };
+        let mut results = Vec::new(&env);
+        let mut filled = Vec::new(&env);
+
+        for fill in fills.iter() {
+            let args = TakerArgs::decode(&env, &fill.taker_traits, &fill.args);
+            // Surpluses are pooled per token and paid to the taker as is
+            if net.is_some()
+                && (args.target.is_some()
+                    || TakerTraitsLib::unwrap_weth(env.clone(), fill.taker_traits.clone()))
             {
-                panic!("Partial fill not allowed")
+                panic!("Net settlement pays the taker");
             }
-
-            if making_amount.mul(&taking_amount) == U256::from_u32(&env, 0) {
-                panic!("Swap with zero amount");
+            // Maker assets stay pooled until the batch settles, so a taker interaction would run
+            // before the taker holds anything of the fill
+            if net.is_some() && !args.interaction.is_empty() {
+                panic!("Net settlement has no taker interaction");
             }
+            let (making_amount, taking_amount, order_hash, remaining_making_amount) =
+                Self::fill_authorized_order(
+                    &env,
+                    &taker,
+                    fill.order.clone(),
+                    fill.r,
+                    fill.vs,
+                    fill.amount,
+                    fill.taker_traits,
+                    args.target.unwrap_or(taker.clone()),
+                    args.extension.clone(),
+                    args.interaction,
+                    fill.auction_details,
+                    net.as_mut(),
+                );
+            results.push_back((
+                making_amount.clone(),
+                taking_amount.clone(),
+                order_hash.clone(),
+            ));
+            filled.push_back((
+                fill.order,
+                args.extension,
+                order_hash,
+                making_amount,
+                taking_amount,
+                remaining_making_amount,
+            ));
+        }
 
-            // Invalidate order depending on makerTraits
-            /*
-               // Invalidate order depending on makerTraits
-               if (order.makerTraits.useBitInvalidator()) {
-                   _bitInvalidator[order.maker.get()].checkAndInvalidate(order.makerTraits.nonceOrEpoch());
-               } else {
-                   _remainingInvalidator[order.maker.get()][orderHash] = RemainingInvalidatorLib.remains(remainingMakingAmount, makingAmount);
-               }
-            */
-
-            // one of this would be used. It's not right to use both.
-
-            // Maker => Taker
-
-            let making_amount_i128 = U256::to_u128(&making_amount).unwrap() as i128;
-
-            TokenClient::new(&env, &order.maker_asset).transfer(
-                &order.maker,
-                &order.receiver,
-                &making_amount_i128, // here we need to convert U256 to i128
-            );
-
-            // Taker => Maker
-            let taking_amount_i128 = U256::to_u128(&taking_amount).unwrap() as i128;
-            TokenClient::new(&env, &order.taker_asset).transfer(
-                &order.receiver,
-                &order.maker,
-                &taking_amount_i128,
-            );
+        if let Some(net) = net {
+            net.settle(&env, &taker);
+            for (order, extension, order_hash, making_amount, taking_amount, remaining) in
+                filled.iter()
+            {
+                post_interaction(
+                    &env,
+                    &taker,
+                    &order,
+                    &extension,
+                    &order_hash,
+                    &making_amount,
+                    &taking_amount,
+                    &remaining,
+                );
+            }
+        }
 
-            let amount = remaining_making_amount.sub(&making_amount);
+        results
+    }
+}
 
-            env.events()
-                .publish((&ORDER_FILLED_EVENT_KEY, &order_hash, &amount), ());
+#[contractimpl]
+impl OrderProtocol {
+    /// Extends the TTL of the contract instance and of the given invalidators, allowance
+    /// records and Ethereum keys, so they are not archived while their orders are still live.
+    /// Anyone may call it, entries that do not exist are skipped.
+    pub fn extend_ttl(env: Env, keys: Vec<StorageKey>) {
+        STORAGE_TTL.extend_instance(&env);
+        for key in keys.iter() {
+            match key {
+                StorageKey::Invalidator(key) => STORAGE_TTL.extend(&env, &key),
+                StorageKey::Allowance(key) => STORAGE_TTL.extend(&env, &key),
+                StorageKey::EvmKey(_) => STORAGE_TTL.extend(&env, &key),
+            };
         }
     }
+}
 
-    #[allow(non_snake_case)]
-    fn _check_remaining_making_amount(env: Env, order: Order, _order_hash: BytesN<32>) -> U256 {
-        if MakerTraitsLib::use_bit_invalidator(&env, order.maker_traits.clone()) {
-            return order.making_amount.clone();
+impl OrderProtocol {
+    fn cancel(env: &Env, maker: &Address, maker_traits: U256, order_hash: BytesN<32>) {
+        STORAGE_TTL.extend_instance(env);
+        if MakerTraitsLib::use_bit_invalidator(env, maker_traits.clone()) {
+            let nonce_or_epoch = MakerTraitsLib::nonce_or_epoch(env, maker_traits);
+            let invalidator = BitInvalidatorLib::mass_invalidate(
+                env,
+                maker,
+                nonce_or_epoch,
+                U256::from_u32(env, 0),
+            );
+            BitInvalidatorUpdated {
+                maker: maker.clone(),
+                slot_index: nonce_or_epoch >> 8,
+                slot_value: invalidator,
+            }
+            .publish(env);
         } else {
-            // todo: implement this
-            panic!("Not implemented");
+            RemainingInvalidatorLib::fully_fill(env, maker, &order_hash);
+            OrderCancelled {
+                order_hash,
+                maker: maker.clone(),
+            }
+            .publish(env);
         }
     }
 
-    fn order_hash(env: Env, order: Order) -> BytesN<32> {
-        hash(&env, &order, &domain_separator_v4(&env))
-    }
-
-    fn fill_order(
-        env: Env,
+    /// Checks the maker authorization on the first fill of the order, then fills it.
+    /// Returns the making and taking amounts, the order hash and the making amount that
+    /// was left before the fill.
+    fn fill_authorized_order(
+        env: &Env,
+        taker: &Address,
         order: Order,
         r: BytesN<32>,
         vs: BytesN<32>,
@@ -350,65 +598,393 @@ impl OrderInterface for OrderProtocol {
         extension: Bytes,
         interaction: Bytes,
         auction_details: AuctionDetails,
-    ) -> (U256, U256, BytesN<32>) {
-        let order_hash = hash(&env, &order, &domain_separator_v4(&env));
+        net_settlement: Option<&mut NetSettlement>,
+    ) -> (U256, U256, BytesN<32>, U256) {
+        let order_hash = hash(env, &order, &domain_separator_v4(env));
 
         let remaining_making_amount =
             Self::_check_remaining_making_amount(env.clone(), order.clone(), order_hash.clone());
 
         if remaining_making_amount == order.making_amount {
-            // let order_hash = hash(&env, &order.clone(), &domain_separator_v4(&env));
+            let permit_data = get_extension(env, &extension, DynamicField::MakerPermit);
+            let permit = if permit_data.is_empty()
+                || TakerTraitsLib::skip_maker_permit(env, taker_traits.clone())
+            {
+                None
+            } else {
+                Some(SettlementLib::maker_permit(env, &permit_data))
+            };
 
-            // Checking signature
-            let is_signature_valid = check_signature(&env, order.clone(), r.clone(), vs.clone());
-            if !is_signature_valid {
-                panic!("Invalid signature");
+            check_maker_authorization(env, &order, &order_hash, &r, &vs, &permit);
+
+            if let Some(permit) = permit {
+                SettlementLib::apply_maker_permit(env, &order, &permit);
             }
         }
 
-        Self::fill(
+        let (making_amount, taking_amount) = Self::fill(
             env.clone(),
-            order.clone(),
+            taker.clone(),
+            order,
             order_hash.clone(),
             remaining_making_amount.clone(),
-            amount.clone(),
-            taker_traits.clone(),
-            target.clone(),
-            extension.clone(),
-            interaction.clone(),
-            auction_details.clone(),
+            amount,
+            taker_traits,
+            target,
+            extension,
+            interaction,
+            auction_details,
+            net_settlement,
         );
 
-        return (remaining_making_amount, amount, order_hash);
+        (
+            making_amount,
+            taking_amount,
+            order_hash,
+            remaining_making_amount,
+        )
     }
 
-    fn fill_order_args(
+    /// Validation shared by fills and quotes, without side effects.
+    /// Returns the making and taking amounts of the fill, and how the taking amount is split.
+    fn check_fill(
+        env: &Env,
+        taker: &Address,
+        order: &Order,
+        order_hash: &BytesN<32>,
+        remaining_making_amount: &U256,
+        amount: &U256,
+        taker_traits: &U256,
+        extension: &Bytes,
+        auction_details: &AuctionDetails,
+    ) -> Result<(U256, U256, FeeSplit), FillStatus> {
+        let (_, extension_status) =
+            is_valid_extension(env.clone(), order.clone(), extension.clone());
+        match extension_status {
+            ValidationResult::MissingOrderExtension => {
+                return Err(FillStatus::MissingOrderExtension)
+            }
+            ValidationResult::InvalidExtensionHash => return Err(FillStatus::InvalidExtensionHash),
+            ValidationResult::UnexpectedOrderExtension => {
+                return Err(FillStatus::UnexpectedOrderExtension)
+            }
+            ValidationResult::Success => (),
+        }
+
+        if !MakerTraitsLib::is_allowed_sender(env, order.maker_traits.clone(), taker.clone()) {
+            return Err(FillStatus::PrivateOrder);
+        }
+
+        let whitelist = get_extension(env, extension, DynamicField::CustomData);
+        if !whitelist.is_empty() {
+            match ResolverWhitelist::decode(env, &whitelist).allowed_time(env, taker) {
+                None => return Err(FillStatus::ResolverNotWhitelisted),
+                Some(allowed_time) if env.ledger().timestamp() < allowed_time => {
+                    return Err(FillStatus::ResolvingWindowNotOpen)
+                }
+                Some(_) => (),
+            }
+        }
+
+        if MakerTraitsLib::is_expired(env, order.maker_traits.clone()) {
+            return Err(FillStatus::OrderExpired);
+        }
+
+        if MakerTraitsLib::need_check_epoch_manager(env, order.maker_traits.clone()) {
+            if MakerTraitsLib::use_bit_invalidator(env, order.maker_traits.clone()) {
+                return Err(FillStatus::IncompatibleEpochManager);
+            }
+            // todo: @Skanislav implement check:
+            // if (!epochEquals(order.maker.get(), order.makerTraits.series(), order.makerTraits.nonceOrEpoch())) revert WrongSeriesNonce();
+        }
+
+        if !get_extension(env, extension, DynamicField::Predicate).is_empty() {
+            return Err(FillStatus::PredicateNotSupported);
+        }
+
+        if MakerTraitsLib::use_bit_invalidator(env, order.maker_traits.clone())
+            && BitInvalidatorLib::is_invalidated(
+                env,
+                &order.maker,
+                MakerTraitsLib::nonce_or_epoch(env, order.maker_traits.clone()),
+            )
+        {
+            return Err(FillStatus::BitInvalidatedOrder);
+        }
+
+        let threshold: U256 = TakerTraitsLib::threshold(env, taker_traits.clone());
+        let zero = U256::from_u32(env, 0);
+
+        let fee_config = FeeConfig::from_extension(env, extension);
+
+        // Checks if the taking amount should be calculated based on making amount.
+        // The calculator prices the auction amount, fees are charged on top of it.
+        let (making_amount, taking_amount, fee_split) =
+            if TakerTraitsLib::is_making_amount(env, taker_traits) {
+                let making_amount = min_num(amount, remaining_making_amount).clone();
+                let auction_amount = Self::calculate_taking_amount(
+                    env.clone(),
+                    order.clone(),
+                    extension.clone(),
+                    making_amount.clone(),
+                    remaining_making_amount.clone(),
+                    order_hash.clone(),
+                    auction_details.clone(),
+                );
+                let fee_split =
+                    fees::split(env, &fee_config, order, &making_amount, auction_amount);
+                let taking_amount = fees::total(&fee_split);
+
+                // Check rate: taking_amount / making_amount <= threshold / amount
+                if threshold > zero {
+                    if *amount == making_amount {
+                        if taking_amount > threshold {
+                            return Err(FillStatus::TakingAmountTooHigh);
+                        }
+                    } else if taking_amount.mul(amount) > threshold.mul(&making_amount) {
+                        return Err(FillStatus::TakingAmountTooHigh);
+                    }
+                }
+                (making_amount, taking_amount, fee_split)
+            } else {
+                let auction_amount = fees::maker_amount(env, &fee_config, amount);
+                let mut making_amount = Self::calculate_making_amount(
+                    env.clone(),
+                    order.clone(),
+                    extension.clone(),
+                    auction_amount.clone(),
+                    remaining_making_amount.clone(),
+                    order_hash.clone(),
+                    auction_details.clone(),
+                );
+                let mut fee_split =
+                    fees::split(env, &fee_config, order, &making_amount, auction_amount);
+                let mut taking_amount = fees::total(&fee_split);
+
+                if making_amount > *remaining_making_amount {
+                    // Try to decrease taking amount because computed making amount exceeds remaining amount
+                    making_amount = remaining_making_amount.clone();
+                    let auction_amount = Self::calculate_taking_amount(
+                        env.clone(),
+                        order.clone(),
+                        extension.clone(),
+                        making_amount.clone(),
+                        remaining_making_amount.clone(),
+                        order_hash.clone(),
+                        auction_details.clone(),
+                    );
+                    fee_split =
+                        fees::split(env, &fee_config, order, &making_amount, auction_amount);
+                    taking_amount = fees::total(&fee_split);
+
+                    if taking_amount > *amount {
+                        return Err(FillStatus::TakingAmountExceeded);
+                    }
+                }
+
+                // Check rate: making_amount / taking_amount >= threshold / amount
+                if threshold > zero {
+                    if *amount == taking_amount {
+                        if making_amount < threshold {
+                            return Err(FillStatus::MakingAmountTooLow);
+                        }
+                    } else if making_amount.mul(amount) < threshold.mul(&taking_amount) {
+                        return Err(FillStatus::MakingAmountTooLow);
+                    }
+                }
+                (making_amount, taking_amount, fee_split)
+            };
+
+        if !MakerTraitsLib::allow_partial_fills(env, order.maker_traits.clone())
+            && making_amount != order.making_amount
+        {
+            return Err(FillStatus::PartialFillNotAllowed);
+        }
+
+        if making_amount.mul(&taking_amount) == zero {
+            return Err(FillStatus::SwapWithZeroAmount);
+        }
+
+        Ok((making_amount, taking_amount, fee_split))
+    }
+
+    /// Settles a fill whose maker authorization was already checked by `fill_order`.
+    /// Not exported: callers could otherwise spend maker allowances for unsigned orders.
+    fn fill(
         env: Env,
+        taker: Address,
         order: Order,
-        r: BytesN<32>,
-        vs: BytesN<32>,
-        taker_traits: U256,
+        order_hash: BytesN<32>,
+        remaining_making_amount: U256,
         amount: U256,
-        args: Bytes,
+        taker_traits: U256,
+        target: Address,
+        extension: Bytes,
+        interaction: Bytes,
         auction_details: AuctionDetails,
-    ) -> (U256, U256, BytesN<32>) {
-        let (target, extension, interaction) = parse_args(env.clone(), taker_traits.clone(), args);
-        return Self::fill_order(
-            env,
-            order,
-            r,
-            vs,
-            amount,
-            taker_traits,
-            target,
-            extension,
-            interaction,
-            auction_details,
+        net_settlement: Option<&mut NetSettlement>,
+    ) -> (U256, U256) {
+        STORAGE_TTL.extend_instance(&env);
+        let (making_amount, taking_amount, fee_split) = Self::check_fill(
+            &env,
+            &taker,
+            &order,
+            &order_hash,
+            &remaining_making_amount,
+            &amount,
+            &taker_traits,
+            &extension,
+            &auction_details,
+        )
+        .unwrap_or_else(|status| fail(status));
+
+        // Invalidate order depending on makerTraits
+        if MakerTraitsLib::use_bit_invalidator(&env, order.maker_traits.clone()) {
+            BitInvalidatorLib::check_and_invalidate(
+                &env,
+                &order.maker,
+                MakerTraitsLib::nonce_or_epoch(&env, order.maker_traits.clone()),
+            );
+        } else {
+            RemainingInvalidatorLib::remains(
+                &env,
+                &order.maker,
+                &order_hash,
+                &remaining_making_amount,
+                &making_amount,
+            );
+        }
+
+        // Pre interaction, where maker can prepare funds interactively
+        if MakerTraitsLib::need_pre_interaction_call(&env, order.maker_traits.clone()) {
+            let data = get_extension(&env, &extension, DynamicField::PreInteractionData);
+            if let Some((listener, extra_data)) = interaction_target_and_data(&env, &data) {
+                PreInteractionClient::new(&env, &listener).pre_interaction(
+                    &order,
+                    &extension,
+                    &order_hash,
+                    &taker,
+                    &making_amount,
+                    &taking_amount,
+                    &remaining_making_amount,
+                    &extra_data,
+                );
+            }
+        }
+
+        let making = making_amount.to_u128().unwrap() as i128;
+        let taker_payments = fees::taker_payments(
+            &env,
+            &order,
+            &FeeConfig::from_extension(&env, &extension),
+            &fee_split,
+            SettlementLib::unwraps(
+                &env,
+                MakerTraitsLib::unwrap_weth(env.clone(), order.maker_traits.clone()),
+                &order.taker_asset,
+            ),
         );
+
+        match net_settlement {
+            Some(net_settlement) => {
+                // Settled with the rest of the batch, post interactions run after that
+                net_settlement.collect_from_maker(&env, &order.maker_asset, &order.maker, making);
+                for payment in taker_payments.iter() {
+                    net_settlement.owe(
+                        &payment.token,
+                        &payment.receiver,
+                        payment.amount,
+                        payment.unwrap,
+                    );
+                }
+
+                taker_interaction(
+                    &env,
+                    &taker,
+                    &order,
+                    &extension,
+                    &order_hash,
+                    &making_amount,
+                    &taking_amount,
+                    &remaining_making_amount,
+                    &interaction,
+                );
+            }
+            None => {
+                // Maker => Target, out of the allowance the maker granted the order contract,
+                // as native XLM if the taker asks for it
+                SettlementLib::pay_from_maker(
+                    &env,
+                    &order.maker_asset,
+                    &order.maker,
+                    &target,
+                    making,
+                    SettlementLib::unwraps(
+                        &env,
+                        TakerTraitsLib::unwrap_weth(env.clone(), taker_traits.clone()),
+                        &order.maker_asset,
+                    ),
+                );
+
+                taker_interaction(
+                    &env,
+                    &taker,
+                    &order,
+                    &extension,
+                    &order_hash,
+                    &making_amount,
+                    &taking_amount,
+                    &remaining_making_amount,
+                    &interaction,
+                );
+
+                // Taker => Receiver, as native XLM if the maker asks for it, and fee recipients
+                for payment in taker_payments.iter() {
+                    SettlementLib::pay_from_taker(
+                        &env,
+                        &payment.token,
+                        &taker,
+                        &payment.receiver,
+                        payment.amount,
+                        payment.unwrap,
+                    );
+                }
+
+                post_interaction(
+                    &env,
+                    &taker,
+                    &order,
+                    &extension,
+                    &order_hash,
+                    &making_amount,
+                    &taking_amount,
+                    &remaining_making_amount,
+                );
+            }
+        }
+
+        OrderFilled {
+            order_hash,
+            maker: order.maker.clone(),
+            taker: taker.clone(),
+            maker_asset: order.maker_asset.clone(),
+            taker_asset: order.taker_asset.clone(),
+            making_amount: making_amount.clone(),
+            taking_amount: taking_amount.clone(),
+            remaining_making_amount: remaining_making_amount.sub(&making_amount),
+            receiver_amount: fee_split.maker_amount,
+            integrator_fee: fee_split.integrator_amount,
+            protocol_fee: fee_split.protocol_amount,
+            surplus: fee_split.surplus,
+            protocol_surplus: fee_split.protocol_surplus_amount,
+        }
+        .publish(&env);
+
+        (making_amount, taking_amount)
     }
 }
 
-mod maker_traits_test;
-mod taker_traits_test;
+mod ecdsa_test;
 mod test;
-mod xlm_orders_test;
+mod test_utils;
+mod whitelist_test;
@@ -0,0 +1,230 @@
+use order_interface::{MakerPermit, Order, SettlementError, WrappedNativeClient};
+use soroban_sdk::{
+    contracttype, panic_with_error, symbol_short, token::TokenClient, xdr::FromXdr, Address, Bytes,
+    Env, Map, Symbol, Vec,
+};
+
+use order_lib::fees::ReceiverPayment;
+
+use crate::STORAGE_TTL;
+
+const WRAPPED_NATIVE_KEY: Symbol = symbol_short!("WNATIVE");
+
+#[contracttype]
+#[derive(Clone)]
+pub enum SettlementKey {
+    /// Ledger at which the allowance a maker granted through `approve` expires
+    AllowanceExpiration(Address, Address),
+}
+
+/// Moves maker assets out of SEP-41 allowances granted to the order contract.
+pub struct SettlementLib;
+
+impl SettlementLib {
+    pub fn set_wrapped_native(env: &Env, wrapped_native: &Option<Address>) {
+        if let Some(wrapped_native) = wrapped_native {
+            env.storage()
+                .instance()
+                .set(&WRAPPED_NATIVE_KEY, wrapped_native);
+        }
+    }
+
+    pub fn wrapped_native(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&WRAPPED_NATIVE_KEY)
+    }
+
+    /// Whether a fill leg in `asset` should be paid out as native XLM.
+    pub fn unwraps(env: &Env, unwrap_flag: bool, asset: &Address) -> bool {
+        unwrap_flag && Self::wrapped_native(env).as_ref() == Some(asset)
+    }
+
+    /// Pays `amount` of the maker's `token` to `to`, as native XLM if `unwrap` is set.
+    pub fn pay_from_maker(
+        env: &Env,
+        token: &Address,
+        maker: &Address,
+        to: &Address,
+        amount: i128,
+        unwrap: bool,
+    ) {
+        if unwrap {
+            let this = env.current_contract_address();
+            Self::transfer_from_maker(env, token, maker, &this, amount);
+            WrappedNativeClient::new(env, token).withdraw(&this, to, &amount);
+        } else {
+            Self::transfer_from_maker(env, token, maker, to, amount);
+        }
+    }
+
+    /// Pays `amount` of the taker's `token` to `to`, as native XLM if `unwrap` is set.
+    pub fn pay_from_taker(
+        env: &Env,
+        token: &Address,
+        taker: &Address,
+        to: &Address,
+        amount: i128,
+        unwrap: bool,
+    ) {
+        if unwrap {
+            WrappedNativeClient::new(env, token).withdraw(taker, to, &amount);
+        } else {
+            TokenClient::new(env, token).transfer(taker, to, &amount);
+        }
+    }
+
+    /// Grants the order contract an allowance over the maker's `token`, once for all future fills.
+    pub fn approve(
+        env: &Env,
+        maker: &Address,
+        token: &Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) {
+        TokenClient::new(env, token).approve(
+            maker,
+            &env.current_contract_address(),
+            &amount,
+            &expiration_ledger,
+        );
+        STORAGE_TTL.set(
+            env,
+            &SettlementKey::AllowanceExpiration(maker.clone(), token.clone()),
+            &expiration_ledger,
+        );
+    }
+
+    /// Decodes the `MakerPermit` extension field, rejecting permits past their deadline.
+    pub fn maker_permit(env: &Env, data: &Bytes) -> MakerPermit {
+        let permit = MakerPermit::from_xdr(env, data)
+            .unwrap_or_else(|_| panic_with_error!(env, SettlementError::InvalidPermit));
+
+        if env.ledger().timestamp() > permit.deadline {
+            panic_with_error!(env, SettlementError::PermitExpired);
+        }
+        permit
+    }
+
+    /// Submits the approval of a maker permit on the maker's behalf. The maker must have
+    /// authorized the permit together with the order hash.
+    pub fn apply_maker_permit(env: &Env, order: &Order, permit: &MakerPermit) {
+        Self::approve(
+            env,
+            &order.maker,
+            &order.maker_asset,
+            permit.amount,
+            permit.expiration_ledger,
+        );
+    }
+
+    /// Transfers `amount` of the maker's `token` to `to`, spending the order contract's allowance.
+    pub fn transfer_from_maker(
+        env: &Env,
+        token: &Address,
+        maker: &Address,
+        to: &Address,
+        amount: i128,
+    ) {
+        let spender = env.current_contract_address();
+        let token_client = TokenClient::new(env, token);
+
+        if token_client.allowance(maker, &spender) < amount {
+            // Expired allowances read as zero, the recorded expiration tells the two cases apart
+            let expiration_ledger: Option<u32> =
+                env.storage()
+                    .persistent()
+                    .get(&SettlementKey::AllowanceExpiration(
+                        maker.clone(),
+                        token.clone(),
+                    ));
+            match expiration_ledger {
+                Some(ledger) if ledger < env.ledger().sequence() => {
+                    panic_with_error!(env, SettlementError::AllowanceExpired)
+                }
+                _ => panic_with_error!(env, SettlementError::InsufficientAllowance),
+            }
+        }
+
+        token_client.transfer_from(&spender, maker, to, &amount);
+    }
+}
+
+/// Token movements of a batch of fills, settled once per token instead of once per fill.
+/// Maker assets are pooled in the order contract and pay the receivers directly, so the
+/// taker only covers what the pool lacks and keeps what is left over.
+pub struct NetSettlement {
+    /// Per token, what the pool holds from makers minus what it owes receivers
+    balances: Map<Address, i128>,
+    payments: Vec<ReceiverPayment>,
+}
+
+impl NetSettlement {
+    pub fn new(env: &Env) -> Self {
+        NetSettlement {
+            balances: Map::new(env),
+            payments: Vec::new(env),
+        }
+    }
+
+    /// Pools `amount` of the maker's `token`, out of the order contract's allowance.
+    pub fn collect_from_maker(
+        &mut self,
+        env: &Env,
+        token: &Address,
+        maker: &Address,
+        amount: i128,
+    ) {
+        SettlementLib::transfer_from_maker(
+            env,
+            token,
+            maker,
+            &env.current_contract_address(),
+            amount,
+        );
+        self.add(token, amount);
+    }
+
+    /// Records `amount` of `token` owed to `receiver`.
+    pub fn owe(&mut self, token: &Address, receiver: &Address, amount: i128, unwrap: bool) {
+        self.add(token, -amount);
+        self.payments.push_back(ReceiverPayment {
+            token: token.clone(),
+            receiver: receiver.clone(),
+            amount,
+            unwrap,
+        });
+    }
+
+    fn add(&mut self, token: &Address, amount: i128) {
+        let balance = self.balances.get(token.clone()).unwrap_or(0);
+        self.balances.set(token.clone(), balance + amount);
+    }
+
+    /// Collects each token's shortfall from the taker, pays the receivers, then pays each
+    /// token's surplus to the taker.
+    pub fn settle(self, env: &Env, taker: &Address) {
+        let this = env.current_contract_address();
+
+        for (token, balance) in self.balances.iter() {
+            if balance < 0 {
+                TokenClient::new(env, &token).transfer(taker, &this, &-balance);
+            }
+        }
+
+        for payment in self.payments.iter() {
+            SettlementLib::pay_from_taker(
+                env,
+                &payment.token,
+                &this,
+                &payment.receiver,
+                payment.amount,
+                payment.unwrap,
+            );
+        }
+
+        for (token, balance) in self.balances.iter() {
+            if balance > 0 {
+                TokenClient::new(env, &token).transfer(&this, taker, &balance);
+            }
+        }
+    }
+}
@@ -1,23 +1,55 @@
 #![cfg(test)]
+extern crate std;
 
-use soroban_sdk::{testutils::Address as _, Address, Bytes, BytesN, Env, U256};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{
+        storage::Persistent, Address as _, AuthorizedFunction, Events, Ledger, MockAuth,
+        MockAuthInvoke,
+    },
+    token::{StellarAssetClient, TokenClient},
+    vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, Symbol, TryFromVal, U256,
+};
 
-use crate::{Order, OrderProtocol, OrderProtocolClient};
-use dutch_auction::DutchAuctionCalculatorContract;
-use order_interface::AuctionDetails;
+use crate::{
+    invalidators::InvalidatorKey,
+    settlement::SettlementKey,
+    test_utils::{create_order_protocol, register_order_protocol},
+    whitelist::{ResolverWhitelist, WhitelistedResolver},
+    Order, OrderProtocolClient, StorageKey,
+};
+use events::{
+    BitInvalidatorUpdated, OrderCancelled, OrderFilled, BIT_INVALIDATOR_UPDATED, ORDER_CANCELLED,
+    ORDER_FILLED,
+};
+use order_interface::{
+    AuctionDetails, AuctionWindow, BatchFill, FeeSplit, FillQuote, FillStatus, MakerPermit,
+    PostInteractionInterface, PreInteractionInterface, TakerInteractionInterface,
+};
+use order_lib::{
+    fees::FeeConfig,
+    maker_traits::{MakerTraitsBuilder, MakerTraitsLib},
+    taker_args::TakerArgs,
+};
+use utils::{
+    address::CrossChainAddress,
+    ttl::{TtlPolicy, DEFAULT_POLICY},
+};
+use wrapped_native::{WrappedNative, WrappedNativeClient};
 
 #[test]
 fn test_calculate_making_amount() {
     let env = Env::default();
 
-    let dutch_auction_calculator_address = env.register(DutchAuctionCalculatorContract, ());
-
-    let contract_id = env.register(OrderProtocol, (&dutch_auction_calculator_address,));
+    let contract_id = create_order_protocol(&env, None);
     let _client = OrderProtocolClient::new(&env, &contract_id);
 
     let _order = Order {
         salt: U256::from_u32(&env, 0),
         maker: Address::generate(&env),
+        maker_evm_address: None,
         receiver: Address::generate(&env),
         maker_asset: Address::generate(&env),
         taker_asset: Address::generate(&env),
@@ -32,7 +64,10 @@ fn test_calculate_making_amount() {
     let order_hash = BytesN::from_array(&env, &[0; 32]);
 
     let auction_details = AuctionDetails {
-        auction_start_time: U256::from_u32(&env, 1000),
+        auction_window: AuctionWindow {
+            start: 0,
+            end: 1000,
+        },
         taking_amount_start: U256::from_u32(&env, 100),
         taking_amount_end: U256::from_u32(&env, 10),
     };
@@ -48,3 +83,1950 @@ fn test_calculate_making_amount() {
 
     assert_eq!(res, U256::from_u32(&env, 100));
 }
+
+fn create_token(env: &Env) -> Address {
+    env.register_stellar_asset_contract_v2(Address::generate(env))
+        .address()
+}
+
+/// Order of 100 maker tokens for 50 taker tokens, with the maker's allowance already granted.
+fn create_stellar_order(
+    env: &Env,
+    order_protocol: &Address,
+    maker: &Address,
+    taker: &Address,
+    maker_traits: U256,
+) -> Order {
+    let order = create_unapproved_stellar_order(env, maker, taker, maker_traits);
+    OrderProtocolClient::new(env, order_protocol).approve(
+        maker,
+        &order.maker_asset,
+        &100,
+        &(env.ledger().sequence() + 100),
+    );
+    order
+}
+
+fn create_unapproved_stellar_order(
+    env: &Env,
+    maker: &Address,
+    taker: &Address,
+    maker_traits: U256,
+) -> Order {
+    let maker_asset = create_token(env);
+    let taker_asset = create_token(env);
+    StellarAssetClient::new(env, &maker_asset).mint(maker, &100);
+    StellarAssetClient::new(env, &taker_asset).mint(taker, &50);
+
+    Order {
+        salt: U256::from_u32(env, 7),
+        maker: maker.clone(),
+        maker_evm_address: None,
+        receiver: Address::generate(env),
+        maker_asset,
+        taker_asset,
+        making_amount: U256::from_u32(env, 100),
+        taking_amount: U256::from_u32(env, 50),
+        maker_traits,
+    }
+}
+
+fn flat_auction_details(env: &Env) -> AuctionDetails {
+    AuctionDetails {
+        auction_window: AuctionWindow {
+            start: 0,
+            end: 1000,
+        },
+        taking_amount_start: U256::from_u32(env, 50),
+        taking_amount_end: U256::from_u32(env, 50),
+    }
+}
+
+fn making_amount_traits(env: &Env, threshold: u32) -> U256 {
+    U256::from_u32(env, 1)
+        .shl(255)
+        .add(&U256::from_u32(env, threshold))
+}
+
+fn taking_amount_traits(env: &Env, threshold: u32) -> U256 {
+    U256::from_u32(env, threshold)
+}
+
+#[test]
+fn test_fill_order_with_stellar_maker_auth() {
+    let env = Env::default();
+    // The maker and the taker both authorize token transfers below the root invocation
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let target = Address::generate(&env);
+    let order = create_stellar_order(&env, &contract_id, &maker, &taker, U256::from_u32(&env, 0));
+    let order_hash = client.order_hash(&order);
+
+    // No secp256k1 signature is needed, r and vs are ignored
+    let empty = BytesN::from_array(&env, &[0; 32]);
+    client.fill_order(
+        &taker,
+        &order,
+        &empty,
+        &empty,
+        &U256::from_u32(&env, 50),
+        &U256::from_u32(&env, 0),
+        &target,
+        &Bytes::new(&env),
+        &Bytes::new(&env),
+        &flat_auction_details(&env),
+    );
+
+    // The maker only authorizes the order, its tokens move through the allowance
+    let maker_auths: std::vec::Vec<_> = env
+        .auths()
+        .into_iter()
+        .filter(|(address, _)| *address == maker)
+        .collect();
+    assert_eq!(maker_auths.len(), 1);
+    assert_eq!(
+        maker_auths[0].1.function,
+        AuthorizedFunction::Contract((
+            contract_id.clone(),
+            Symbol::new(&env, "fill_order"),
+            (order_hash.clone(),).into_val(&env),
+        ))
+    );
+
+    assert_eq!(
+        TokenClient::new(&env, &order.maker_asset).balance(&target),
+        100
+    );
+    assert_eq!(
+        TokenClient::new(&env, &order.taker_asset).balance(&order.receiver),
+        50
+    );
+}
+
+#[test]
+fn test_fill_order_falls_back_to_contract_maker_auth() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    // Generated addresses are contracts, which verify their own signatures
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let target = Address::generate(&env);
+    let mut order = create_unapproved_stellar_order(&env, &maker, &taker, U256::from_u32(&env, 0));
+    order.maker_evm_address = Some(BytesN::from_array(&env, &[0x42; 20]));
+    client.approve(&maker, &order.maker_asset, &100, &100);
+    let order_hash = client.order_hash(&order);
+
+    // The signature does not recover to the maker's Ethereum address
+    let empty = BytesN::from_array(&env, &[0; 32]);
+    client.fill_order(
+        &taker,
+        &order,
+        &empty,
+        &empty,
+        &U256::from_u32(&env, 50),
+        &U256::from_u32(&env, 0),
+        &target,
+        &Bytes::new(&env),
+        &Bytes::new(&env),
+        &flat_auction_details(&env),
+    );
+
+    let maker_auths: std::vec::Vec<_> = env
+        .auths()
+        .into_iter()
+        .filter(|(address, _)| *address == maker)
+        .collect();
+    assert_eq!(maker_auths.len(), 1);
+    assert_eq!(
+        maker_auths[0].1.function,
+        AuthorizedFunction::Contract((
+            contract_id.clone(),
+            Symbol::new(&env, "fill_order"),
+            (order_hash,).into_val(&env),
+        ))
+    );
+    assert_eq!(
+        TokenClient::new(&env, &order.maker_asset).balance(&target),
+        100
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_fill_order_without_stellar_maker_auth() {
+    let env = Env::default();
+
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let target = Address::generate(&env);
+    env.mock_all_auths();
+    let order = create_stellar_order(&env, &contract_id, &maker, &taker, U256::from_u32(&env, 0));
+
+    let empty = BytesN::from_array(&env, &[0; 32]);
+    let amount = U256::from_u32(&env, 50);
+    let taker_traits = U256::from_u32(&env, 0);
+    let extension = Bytes::new(&env);
+    let interaction = Bytes::new(&env);
+    let auction_details = flat_auction_details(&env);
+
+    // Only the taker signs, for the fill and its own payment
+    env.mock_auths(&[MockAuth {
+        address: &taker,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "fill_order",
+            args: (
+                &taker,
+                order.clone(),
+                &empty,
+                &empty,
+                &amount,
+                &taker_traits,
+                &target,
+                &extension,
+                &interaction,
+                auction_details.clone(),
+            )
+                .into_val(&env),
+            sub_invokes: &[MockAuthInvoke {
+                contract: &order.taker_asset,
+                fn_name: "transfer",
+                args: (&taker, &order.receiver, 50_i128).into_val(&env),
+                sub_invokes: &[],
+            }],
+        },
+    }]);
+
+    client.fill_order(
+        &taker,
+        &order,
+        &empty,
+        &empty,
+        &amount,
+        &taker_traits,
+        &target,
+        &extension,
+        &interaction,
+        &auction_details,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_fill_order_without_taker_auth() {
+    let env = Env::default();
+
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    env.mock_all_auths();
+    let order = create_stellar_order(&env, &contract_id, &maker, &taker, U256::from_u32(&env, 0));
+    env.set_auths(&[]);
+
+    let empty = BytesN::from_array(&env, &[0; 32]);
+    client.fill_order(
+        &taker,
+        &order,
+        &empty,
+        &empty,
+        &U256::from_u32(&env, 50),
+        &U256::from_u32(&env, 0),
+        &Address::generate(&env),
+        &Bytes::new(&env),
+        &Bytes::new(&env),
+        &flat_auction_details(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_approve_requires_maker_auth() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    client.approve(&Address::generate(&env), &create_token(&env), &100, &100);
+}
+
+#[test]
+fn test_approve_grants_allowance_to_order_contract() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let token = create_token(&env);
+    client.approve(&maker, &token, &100, &100);
+
+    assert_eq!(
+        TokenClient::new(&env, &token).allowance(&maker, &contract_id),
+        100
+    );
+}
+
+fn fill_full_order(env: &Env, client: &OrderProtocolClient, taker: &Address, order: &Order) {
+    let empty = BytesN::from_array(env, &[0; 32]);
+    client.fill_order(
+        taker,
+        order,
+        &empty,
+        &empty,
+        &U256::from_u32(env, 50),
+        &U256::from_u32(env, 0),
+        &Address::generate(env),
+        &Bytes::new(env),
+        &Bytes::new(env),
+        &flat_auction_details(env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_fill_with_insufficient_allowance() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let order = create_stellar_order(&env, &contract_id, &maker, &taker, U256::from_u32(&env, 0));
+    client.approve(&maker, &order.maker_asset, &40, &100);
+
+    // SettlementError::InsufficientAllowance
+    fill_full_order(&env, &client, &taker, &order);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_fill_with_expired_allowance() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let order = create_stellar_order(&env, &contract_id, &maker, &taker, U256::from_u32(&env, 0));
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += 101);
+
+    // SettlementError::AllowanceExpired
+    fill_full_order(&env, &client, &taker, &order);
+}
+
+/// Salt committing the order to `extension`: the low 128 bits of its keccak256.
+fn extension_salt(env: &Env, extension: &Bytes) -> U256 {
+    let mut salt = [0u8; 32];
+    salt[16..].copy_from_slice(&env.crypto().keccak256(extension).to_array()[16..]);
+    U256::from_be_bytes(env, &Bytes::from_array(env, &salt))
+}
+
+/// Extension holding only the `MakerPermit` field (the sixth one).
+fn permit_extension(env: &Env, permit: &MakerPermit) -> Bytes {
+    let data = permit.clone().to_xdr(env);
+
+    // Offsets word: fields after MakerPermit end where it ends, the ones before it are empty
+    let mut extension = Bytes::new(env);
+    for _ in 0..3 {
+        extension.extend_from_array(&data.len().to_be_bytes());
+    }
+    extension.extend_from_array(&[0; 20]);
+    extension.append(&data);
+    extension
+}
+
+fn fill_with_permit(
+    env: &Env,
+    client: &OrderProtocolClient,
+    taker: &Address,
+    order: &Order,
+    permit: &MakerPermit,
+    taker_traits: U256,
+) {
+    let empty = BytesN::from_array(env, &[0; 32]);
+    client.fill_order(
+        taker,
+        order,
+        &empty,
+        &empty,
+        &U256::from_u32(env, 50),
+        &taker_traits,
+        &Address::generate(env),
+        &permit_extension(env, permit),
+        &Bytes::new(env),
+        &flat_auction_details(env),
+    );
+}
+
+fn permit_order(env: &Env) -> (OrderProtocolClient<'static>, Address, Order, MakerPermit) {
+    let contract_id = create_order_protocol(env, None);
+    let client = OrderProtocolClient::new(env, &contract_id);
+
+    let maker = Address::generate(env);
+    let taker = Address::generate(env);
+    let traits = MakerTraitsBuilder::new(env.clone())
+        .with_extension()
+        .build();
+    let mut order = create_unapproved_stellar_order(env, &maker, &taker, traits);
+    let permit = MakerPermit {
+        amount: 100,
+        expiration_ledger: env.ledger().sequence() + 100,
+        deadline: env.ledger().timestamp() + 60,
+    };
+    order.salt = extension_salt(env, &permit_extension(env, &permit));
+    (client, taker, order, permit)
+}
+
+#[test]
+fn test_fill_applies_maker_permit() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, taker, order, permit) = permit_order(&env);
+    let order_hash = client.order_hash(&order);
+
+    fill_with_permit(
+        &env,
+        &client,
+        &taker,
+        &order,
+        &permit,
+        U256::from_u32(&env, 0),
+    );
+
+    // The maker signs the permit for this order hash only
+    assert!(env.auths().iter().any(|(address, invocation)| {
+        *address == order.maker
+            && invocation.function
+                == AuthorizedFunction::Contract((
+                    client.address.clone(),
+                    Symbol::new(&env, "fill_order"),
+                    (order_hash.clone(), permit.clone()).into_val(&env),
+                ))
+    }));
+    assert_eq!(
+        TokenClient::new(&env, &order.taker_asset).balance(&order.receiver),
+        50
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #4)")]
+fn test_fill_rejects_expired_maker_permit() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, taker, order, permit) = permit_order(&env);
+    env.ledger().with_mut(|ledger| ledger.timestamp += 61);
+
+    // SettlementError::PermitExpired
+    fill_with_permit(
+        &env,
+        &client,
+        &taker,
+        &order,
+        &permit,
+        U256::from_u32(&env, 0),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_fill_skips_maker_permit() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, taker, order, permit) = permit_order(&env);
+
+    // Without the permit applied there is no allowance: SettlementError::InsufficientAllowance
+    let skip_order_permit = U256::from_u32(&env, 1).shl(253);
+    fill_with_permit(&env, &client, &taker, &order, &permit, skip_order_permit);
+}
+
+/// Native XLM asset contract and wrapped XLM backed by it.
+fn create_wrapped_native(env: &Env) -> (Address, Address) {
+    let native = create_token(env);
+    let wrapped = env.register(WrappedNative, (&native, None::<TtlPolicy>));
+    (native, wrapped)
+}
+
+fn mint_wrapped_native(env: &Env, native: &Address, wrapped: &Address, to: &Address, amount: i128) {
+    StellarAssetClient::new(env, native).mint(to, &amount);
+    WrappedNativeClient::new(env, wrapped).deposit(to, &amount);
+}
+
+fn fill_wrapped_order(
+    env: &Env,
+    client: &OrderProtocolClient,
+    taker: &Address,
+    order: &Order,
+    target: &Address,
+    taker_traits: U256,
+) {
+    let empty = BytesN::from_array(env, &[0; 32]);
+    client.fill_order(
+        taker,
+        order,
+        &empty,
+        &empty,
+        &U256::from_u32(env, 50),
+        &taker_traits,
+        target,
+        &Bytes::new(env),
+        &Bytes::new(env),
+        &flat_auction_details(env),
+    );
+}
+
+#[test]
+fn test_fill_unwraps_taker_asset_for_maker() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (native, wrapped) = create_wrapped_native(&env);
+    let contract_id = register_order_protocol(&env, None, Some(wrapped.clone()));
+    let client = OrderProtocolClient::new(&env, &contract_id);
+    assert_eq!(client.wrapped_native(), Some(wrapped.clone()));
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let traits = MakerTraitsBuilder::new(env.clone()).unwrap_weth().build();
+    let mut order = create_stellar_order(&env, &contract_id, &maker, &taker, traits);
+    order.taker_asset = wrapped.clone();
+    mint_wrapped_native(&env, &native, &wrapped, &taker, 50);
+
+    fill_wrapped_order(
+        &env,
+        &client,
+        &taker,
+        &order,
+        &Address::generate(&env),
+        U256::from_u32(&env, 0),
+    );
+
+    assert_eq!(TokenClient::new(&env, &native).balance(&order.receiver), 50);
+    assert_eq!(TokenClient::new(&env, &wrapped).balance(&order.receiver), 0);
+    assert_eq!(TokenClient::new(&env, &wrapped).balance(&taker), 0);
+}
+
+#[test]
+fn test_fill_unwraps_maker_asset_for_taker() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (native, wrapped) = create_wrapped_native(&env);
+    let contract_id = register_order_protocol(&env, None, Some(wrapped.clone()));
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let target = Address::generate(&env);
+    let mut order = create_unapproved_stellar_order(&env, &maker, &taker, U256::from_u32(&env, 0));
+    order.maker_asset = wrapped.clone();
+    mint_wrapped_native(&env, &native, &wrapped, &maker, 100);
+    client.approve(&maker, &wrapped, &100, &(env.ledger().sequence() + 100));
+
+    let unwrap_weth = U256::from_u32(&env, 1).shl(254);
+    fill_wrapped_order(&env, &client, &taker, &order, &target, unwrap_weth);
+
+    assert_eq!(TokenClient::new(&env, &native).balance(&target), 100);
+    assert_eq!(TokenClient::new(&env, &wrapped).balance(&target), 0);
+    assert_eq!(TokenClient::new(&env, &wrapped).balance(&contract_id), 0);
+}
+
+#[test]
+fn test_fill_without_wrapped_native_pays_token() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (native, wrapped) = create_wrapped_native(&env);
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+    assert_eq!(client.wrapped_native(), None);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let traits = MakerTraitsBuilder::new(env.clone()).unwrap_weth().build();
+    let mut order = create_stellar_order(&env, &contract_id, &maker, &taker, traits);
+    order.taker_asset = wrapped.clone();
+    mint_wrapped_native(&env, &native, &wrapped, &taker, 50);
+
+    fill_wrapped_order(
+        &env,
+        &client,
+        &taker,
+        &order,
+        &Address::generate(&env),
+        U256::from_u32(&env, 0),
+    );
+
+    assert_eq!(
+        TokenClient::new(&env, &wrapped).balance(&order.receiver),
+        50
+    );
+    assert_eq!(TokenClient::new(&env, &native).balance(&order.receiver), 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalidated order")]
+fn test_fill_rejects_cancelled_order() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let traits = MakerTraitsBuilder::new(env.clone())
+        .allow_multiple_fills()
+        .build();
+    let order = create_stellar_order(&env, &contract_id, &maker, &taker, traits.clone());
+    let order_hash = client.order_hash(&order);
+
+    client.cancel_order(&maker, &traits, &order_hash);
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, contract_id);
+    assert_eq!(topics, (ORDER_CANCELLED, order_hash.clone()).into_val(&env));
+    assert_eq!(
+        OrderCancelled::try_from_val(&env, &data).unwrap(),
+        OrderCancelled {
+            order_hash,
+            maker: maker.clone(),
+        }
+    );
+
+    fill_full_order(&env, &client, &taker, &order);
+}
+
+#[test]
+#[should_panic(expected = "BitInvalidatedOrder")]
+fn test_fill_rejects_order_with_cancelled_nonce() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let traits = MakerTraitsBuilder::new(env.clone())
+        .with_nonce_or_epoch(258)
+        .build();
+    let order = create_stellar_order(&env, &contract_id, &maker, &taker, traits.clone());
+
+    // Any order hash with the same nonce cancels it
+    client.cancel_order(&maker, &traits, &BytesN::from_array(&env, &[0; 32]));
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (BIT_INVALIDATOR_UPDATED, maker.clone()).into_val(&env)
+    );
+    assert_eq!(
+        BitInvalidatorUpdated::try_from_val(&env, &data).unwrap(),
+        BitInvalidatorUpdated {
+            maker: maker.clone(),
+            slot_index: 1,
+            slot_value: U256::from_u32(&env, 1 << 2),
+        }
+    );
+
+    fill_full_order(&env, &client, &taker, &order);
+}
+
+#[test]
+fn test_extend_ttl_keeps_invalidators_alive() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let token = create_token(&env);
+    let bit_traits = MakerTraitsBuilder::new(env.clone())
+        .with_nonce_or_epoch(258)
+        .build();
+    let remaining_traits = MakerTraitsBuilder::new(env.clone())
+        .allow_multiple_fills()
+        .build();
+    let order_hash = BytesN::from_array(&env, &[1; 32]);
+    client.cancel_order(&maker, &bit_traits, &order_hash);
+    client.cancel_order(&maker, &remaining_traits, &order_hash);
+    client.approve(&maker, &token, &100, &100);
+    client.register_evm_key(&maker, &Some(BytesN::from_array(&env, &[0x42; 20])));
+
+    let keys = vec![
+        &env,
+        StorageKey::Invalidator(InvalidatorKey::Bits(maker.clone(), 1)),
+        StorageKey::Invalidator(InvalidatorKey::Remaining(maker.clone(), order_hash.clone())),
+        StorageKey::Allowance(SettlementKey::AllowanceExpiration(
+            maker.clone(),
+            token.clone(),
+        )),
+        StorageKey::EvmKey(maker.clone()),
+    ];
+    let ttls = || {
+        env.as_contract(&contract_id, || {
+            keys.iter()
+                .map(|key| match key {
+                    StorageKey::Invalidator(key) => env.storage().persistent().get_ttl(&key),
+                    StorageKey::Allowance(key) => env.storage().persistent().get_ttl(&key),
+                    StorageKey::EvmKey(_) => env.storage().persistent().get_ttl(&key),
+                })
+                .collect::<std::vec::Vec<_>>()
+        })
+    };
+    assert_eq!(ttls(), [DEFAULT_POLICY.extend_to; 4]);
+
+    // Close to archival, the entries get their full TTL back
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number += DEFAULT_POLICY.extend_to - DEFAULT_POLICY.threshold + 1;
+    });
+    assert_eq!(ttls(), [DEFAULT_POLICY.threshold - 1; 4]);
+
+    let mut with_unknown = keys.clone();
+    with_unknown.push_back(StorageKey::Invalidator(InvalidatorKey::Bits(
+        Address::generate(&env),
+        0,
+    )));
+    client.extend_ttl(&with_unknown);
+
+    assert_eq!(ttls(), [DEFAULT_POLICY.extend_to; 4]);
+}
+
+#[test]
+fn test_cancel_orders_invalidates_each_order() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let remaining_traits = MakerTraitsBuilder::new(env.clone())
+        .allow_multiple_fills()
+        .build();
+    let bit_traits = MakerTraitsBuilder::new(env.clone())
+        .with_nonce_or_epoch(7)
+        .build();
+    let remaining_order =
+        create_stellar_order(&env, &contract_id, &maker, &taker, remaining_traits.clone());
+    let bit_order = create_stellar_order(&env, &contract_id, &maker, &taker, bit_traits.clone());
+    let untouched_order =
+        create_stellar_order(&env, &contract_id, &maker, &taker, remaining_traits.clone());
+
+    client.cancel_orders(
+        &maker,
+        &vec![
+            &env,
+            (remaining_traits, client.order_hash(&remaining_order)),
+            (bit_traits, client.order_hash(&bit_order)),
+        ],
+    );
+
+    let empty = BytesN::from_array(&env, &[0; 32]);
+    let try_fill = |order: &Order| {
+        client
+            .try_fill_order(
+                &taker,
+                order,
+                &empty,
+                &empty,
+                &U256::from_u32(&env, 50),
+                &U256::from_u32(&env, 0),
+                &Address::generate(&env),
+                &Bytes::new(&env),
+                &Bytes::new(&env),
+                &flat_auction_details(&env),
+            )
+            .is_ok()
+    };
+    assert!(!try_fill(&remaining_order));
+    assert!(!try_fill(&bit_order));
+    assert!(try_fill(&untouched_order));
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_cancel_order_requires_maker_auth() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    client.cancel_order(
+        &Address::generate(&env),
+        &U256::from_u32(&env, 0),
+        &BytesN::from_array(&env, &[0; 32]),
+    );
+}
+
+struct FillCase {
+    env: Env,
+    client: OrderProtocolClient<'static>,
+    order: Order,
+    taker: Address,
+    target: Address,
+}
+
+impl FillCase {
+    fn new(maker_traits: fn(MakerTraitsBuilder) -> MakerTraitsBuilder) -> Self {
+        Self::in_env(Env::default(), maker_traits)
+    }
+
+    fn in_env(env: Env, maker_traits: fn(MakerTraitsBuilder) -> MakerTraitsBuilder) -> Self {
+        env.mock_all_auths_allowing_non_root_auth();
+
+        let contract_id = create_order_protocol(&env, None);
+        let client = OrderProtocolClient::new(&env, &contract_id);
+
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let target = Address::generate(&env);
+        let traits = maker_traits(MakerTraitsBuilder::new(env.clone())).build();
+        let order = create_stellar_order(&env, &contract_id, &maker, &taker, traits);
+
+        FillCase {
+            env,
+            client,
+            order,
+            taker,
+            target,
+        }
+    }
+
+    fn fill(&self, amount: u32, taker_traits: U256) -> bool {
+        let empty = BytesN::from_array(&self.env, &[0; 32]);
+        self.client
+            .try_fill_order(
+                &self.taker,
+                &self.order,
+                &empty,
+                &empty,
+                &U256::from_u32(&self.env, amount),
+                &taker_traits,
+                &self.target,
+                &Bytes::new(&self.env),
+                &Bytes::new(&self.env),
+                &flat_auction_details(&self.env),
+            )
+            .is_ok()
+    }
+
+    fn quote(&self, amount: u32, taker_traits: U256) -> FillQuote {
+        self.client.quote_fill(
+            &self.taker,
+            &self.order,
+            &Bytes::new(&self.env),
+            &U256::from_u32(&self.env, amount),
+            &taker_traits,
+            &flat_auction_details(&self.env),
+        )
+    }
+
+    /// Maker asset received by the target and taker asset received by the order receiver.
+    fn settled(&self) -> (i128, i128) {
+        (
+            TokenClient::new(&self.env, &self.order.maker_asset).balance(&self.target),
+            TokenClient::new(&self.env, &self.order.taker_asset).balance(&self.order.receiver),
+        )
+    }
+
+    /// Fill event, read before any other contract call replaces the events.
+    fn last_filled_event(&self) -> OrderFilled {
+        let (contract, topics, data) = self.env.events().all().last().unwrap();
+        assert_eq!(contract, self.client.address);
+        let event = OrderFilled::try_from_val(&self.env, &data).unwrap();
+        assert_eq!(
+            topics,
+            (ORDER_FILLED, event.order_hash.clone()).into_val(&self.env)
+        );
+        event
+    }
+}
+
+#[test]
+fn test_fill_branches_settle_across_maker_traits() {
+    let single_fill: fn(MakerTraitsBuilder) -> MakerTraitsBuilder = |traits| traits;
+    let multiple_fills: fn(MakerTraitsBuilder) -> MakerTraitsBuilder =
+        |traits| traits.allow_multiple_fills();
+    let no_partial_fills: fn(MakerTraitsBuilder) -> MakerTraitsBuilder =
+        |traits| traits.allow_multiple_fills().no_partial_fills();
+
+    for is_making_amount in [true, false] {
+        // Half of the order: 50 maker tokens for 25 taker tokens
+        let half = |env: &Env| {
+            if is_making_amount {
+                (50, making_amount_traits(env, 0))
+            } else {
+                (25, taking_amount_traits(env, 0))
+            }
+        };
+
+        // Single fill orders are invalidated by their nonce after the first fill
+        let case = FillCase::new(single_fill);
+        let (amount, taker_traits) = half(&case.env);
+        assert!(case.fill(amount, taker_traits.clone()));
+        let event = case.last_filled_event();
+        assert_eq!(case.settled(), (50, 25));
+        assert_eq!(
+            event,
+            OrderFilled {
+                order_hash: case.client.order_hash(&case.order),
+                maker: case.order.maker.clone(),
+                taker: case.taker.clone(),
+                maker_asset: case.order.maker_asset.clone(),
+                taker_asset: case.order.taker_asset.clone(),
+                making_amount: U256::from_u32(&case.env, 50),
+                taking_amount: U256::from_u32(&case.env, 25),
+                remaining_making_amount: U256::from_u32(&case.env, 50),
+                receiver_amount: U256::from_u32(&case.env, 25),
+                integrator_fee: U256::from_u32(&case.env, 0),
+                protocol_fee: U256::from_u32(&case.env, 0),
+                surplus: U256::from_u32(&case.env, 0),
+                protocol_surplus: U256::from_u32(&case.env, 0),
+            }
+        );
+        assert!(!case.fill(amount, taker_traits));
+        assert_eq!(case.settled(), (50, 25));
+
+        // Multiple fills draw down the remaining making amount until it is exhausted
+        let case = FillCase::new(multiple_fills);
+        let (amount, taker_traits) = half(&case.env);
+        assert!(case.fill(amount, taker_traits.clone()));
+        assert!(case.fill(amount, taker_traits.clone()));
+        assert_eq!(
+            case.last_filled_event().remaining_making_amount,
+            U256::from_u32(&case.env, 0)
+        );
+        assert_eq!(case.settled(), (100, 50));
+        assert!(!case.fill(amount, taker_traits));
+
+        // Orders without partial fills reject half fills but accept a full one
+        let case = FillCase::new(no_partial_fills);
+        let (amount, taker_traits) = half(&case.env);
+        assert!(!case.fill(amount, taker_traits.clone()));
+        assert_eq!(case.settled(), (0, 0));
+        assert!(case.fill(amount * 2, taker_traits));
+        assert_eq!(case.settled(), (100, 50));
+    }
+}
+
+#[test]
+fn test_fill_caps_amount_at_remaining() {
+    let case = FillCase::new(|traits| traits.allow_multiple_fills());
+    assert!(case.fill(40, taking_amount_traits(&case.env, 0)));
+    assert_eq!(case.settled(), (80, 40));
+
+    // Only 20 maker tokens are left, so the taker pays 10 instead of the 25 offered
+    assert!(case.fill(25, taking_amount_traits(&case.env, 0)));
+    assert_eq!(case.settled(), (100, 50));
+
+    let case = FillCase::new(|traits| traits.allow_multiple_fills());
+    assert!(case.fill(80, making_amount_traits(&case.env, 0)));
+    assert!(case.fill(80, making_amount_traits(&case.env, 0)));
+    assert_eq!(case.settled(), (100, 50));
+}
+
+#[test]
+fn test_fill_thresholds() {
+    // Making amount fills cap what the taker pays
+    let case = FillCase::new(|traits| traits);
+    assert!(!case.fill(100, making_amount_traits(&case.env, 49)));
+    assert!(case.fill(100, making_amount_traits(&case.env, 50)));
+    assert_eq!(case.settled(), (100, 50));
+
+    // Capped at the remaining amount, the threshold applies pro rata
+    let case = FillCase::new(|traits| traits.allow_multiple_fills());
+    assert!(case.fill(50, making_amount_traits(&case.env, 0)));
+    assert!(!case.fill(100, making_amount_traits(&case.env, 49)));
+    assert!(case.fill(100, making_amount_traits(&case.env, 50)));
+    assert_eq!(case.settled(), (100, 50));
+
+    // Taking amount fills set a floor on what the taker receives
+    let case = FillCase::new(|traits| traits);
+    assert!(!case.fill(50, taking_amount_traits(&case.env, 101)));
+    assert!(case.fill(50, taking_amount_traits(&case.env, 100)));
+    assert_eq!(case.settled(), (100, 50));
+}
+
+#[test]
+#[should_panic(expected = "Taking amount too high")]
+fn test_fill_making_amount_above_threshold() {
+    let case = FillCase::new(|traits| traits);
+    let empty = BytesN::from_array(&case.env, &[0; 32]);
+    case.client.fill_order(
+        &case.taker,
+        &case.order,
+        &empty,
+        &empty,
+        &U256::from_u32(&case.env, 100),
+        &making_amount_traits(&case.env, 49),
+        &case.target,
+        &Bytes::new(&case.env),
+        &Bytes::new(&case.env),
+        &flat_auction_details(&case.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Making amount too low")]
+fn test_fill_taking_amount_below_threshold() {
+    let case = FillCase::new(|traits| traits);
+    let empty = BytesN::from_array(&case.env, &[0; 32]);
+    case.client.fill_order(
+        &case.taker,
+        &case.order,
+        &empty,
+        &empty,
+        &U256::from_u32(&case.env, 50),
+        &taking_amount_traits(&case.env, 101),
+        &case.target,
+        &Bytes::new(&case.env),
+        &Bytes::new(&case.env),
+        &flat_auction_details(&case.env),
+    );
+}
+
+#[test]
+fn test_quote_fill_matches_fill_without_side_effects() {
+    let case = FillCase::new(|traits| traits.allow_multiple_fills());
+    let traits = taking_amount_traits(&case.env, 0);
+
+    let quote = case.quote(20, traits.clone());
+    assert_eq!(
+        quote,
+        FillQuote {
+            making_amount: U256::from_u32(&case.env, 40),
+            taking_amount: U256::from_u32(&case.env, 20),
+            remaining_making_amount: U256::from_u32(&case.env, 100),
+            fees: FeeSplit {
+                maker_amount: U256::from_u32(&case.env, 20),
+                integrator_amount: U256::from_u32(&case.env, 0),
+                protocol_amount: U256::from_u32(&case.env, 0),
+                surplus: U256::from_u32(&case.env, 0),
+                protocol_surplus_amount: U256::from_u32(&case.env, 0),
+            },
+            status: FillStatus::Fillable,
+        }
+    );
+    assert_eq!(case.quote(20, traits.clone()), quote);
+    assert_eq!(case.settled(), (0, 0));
+
+    assert!(case.fill(20, traits.clone()));
+    assert_eq!(case.settled(), (40, 20));
+
+    // Asking for more than is left quotes the remaining 60
+    let quote = case.quote(50, traits);
+    assert_eq!(quote.making_amount, U256::from_u32(&case.env, 60));
+    assert_eq!(quote.taking_amount, U256::from_u32(&case.env, 30));
+    assert_eq!(quote.remaining_making_amount, U256::from_u32(&case.env, 60));
+}
+
+#[test]
+fn test_quote_fill_leaves_ttls_alone() {
+    // Keeps the other contracts alive until the remaining amount entry gets close to archival
+    let env = Env::default();
+    env.ledger().with_mut(|ledger| {
+        ledger.min_persistent_entry_ttl = DEFAULT_POLICY.extend_to;
+    });
+    let case = FillCase::in_env(env, |traits| traits.allow_multiple_fills());
+    let traits = taking_amount_traits(&case.env, 0);
+    assert!(case.fill(20, traits.clone()));
+    let key = InvalidatorKey::Remaining(
+        case.order.maker.clone(),
+        case.last_filled_event().order_hash,
+    );
+
+    let ttl = || {
+        case.env.as_contract(&case.client.address, || {
+            case.env.storage().persistent().get_ttl(&key)
+        })
+    };
+
+    // Past the threshold, so a read that extended the entry would show
+    case.env.ledger().with_mut(|ledger| {
+        ledger.sequence_number += DEFAULT_POLICY.extend_to - DEFAULT_POLICY.threshold + 1;
+    });
+    let before = ttl();
+    assert!(before < DEFAULT_POLICY.threshold);
+
+    let quote = case.quote(20, traits);
+    assert_eq!(quote.remaining_making_amount, U256::from_u32(&case.env, 60));
+    assert_eq!(ttl(), before);
+}
+
+#[test]
+fn test_quote_fill_reasons() {
+    let traits = |env: &Env| taking_amount_traits(env, 0);
+
+    let case = FillCase::new(|traits| traits.with_allowed_sender(1));
+    assert_eq!(
+        case.quote(50, traits(&case.env)).status,
+        FillStatus::PrivateOrder
+    );
+
+    let case = FillCase::new(|traits| traits.with_expiration(1));
+    case.env.ledger().with_mut(|ledger| ledger.timestamp = 2);
+    assert_eq!(
+        case.quote(50, traits(&case.env)).status,
+        FillStatus::OrderExpired
+    );
+
+    let case = FillCase::new(|traits| traits.no_partial_fills());
+    assert_eq!(
+        case.quote(25, traits(&case.env)).status,
+        FillStatus::PartialFillNotAllowed
+    );
+    assert_eq!(
+        case.quote(50, taking_amount_traits(&case.env, 101)).status,
+        FillStatus::MakingAmountTooLow
+    );
+    assert_eq!(
+        case.quote(100, making_amount_traits(&case.env, 49)).status,
+        FillStatus::TakingAmountTooHigh
+    );
+    assert_eq!(
+        case.quote(0, traits(&case.env)).status,
+        FillStatus::PartialFillNotAllowed
+    );
+
+    // Single fill orders quote as bit invalidated once filled
+    let case = FillCase::new(|traits| traits);
+    assert!(case.fill(50, traits(&case.env)));
+    let quote = case.quote(50, traits(&case.env));
+    assert_eq!(quote.status, FillStatus::BitInvalidatedOrder);
+    assert_eq!(quote.making_amount, U256::from_u32(&case.env, 0));
+
+    // Cancelled orders quote as invalidated
+    let case = FillCase::new(|traits| traits.allow_multiple_fills());
+    case.client.cancel_order(
+        &case.order.maker,
+        &case.order.maker_traits,
+        &case.client.order_hash(&case.order),
+    );
+    let quote = case.quote(50, traits(&case.env));
+    assert_eq!(quote.status, FillStatus::InvalidatedOrder);
+    assert_eq!(quote.remaining_making_amount, U256::from_u32(&case.env, 0));
+}
+
+#[test]
+fn test_quote_fill_extension_reasons() {
+    let case = FillCase::new(|traits| traits.with_extension());
+    let quote = |extension: &Bytes| {
+        case.client
+            .quote_fill(
+                &case.taker,
+                &case.order,
+                extension,
+                &U256::from_u32(&case.env, 50),
+                &taking_amount_traits(&case.env, 0),
+                &flat_auction_details(&case.env),
+            )
+            .status
+    };
+    assert_eq!(
+        quote(&Bytes::new(&case.env)),
+        FillStatus::MissingOrderExtension
+    );
+
+    // Only the Predicate field, 4 bytes long
+    let mut extension =
+        Bytes::from_array(&case.env, &[0, 0, 0, 4, 0, 0, 0, 4, 0, 0, 0, 4, 0, 0, 0, 4]);
+    extension.extend_from_array(&[0; 16]);
+    extension.extend_from_array(&[1, 2, 3, 4]);
+    assert_eq!(quote(&extension), FillStatus::InvalidExtensionHash);
+
+    let mut order = case.order.clone();
+    order.salt = extension_salt(&case.env, &extension);
+    let quote = case.client.quote_fill(
+        &case.taker,
+        &order,
+        &extension,
+        &U256::from_u32(&case.env, 50),
+        &taking_amount_traits(&case.env, 0),
+        &flat_auction_details(&case.env),
+    );
+    assert_eq!(quote.status, FillStatus::PredicateNotSupported);
+
+    let case = FillCase::new(|traits| traits);
+    assert_eq!(
+        case.client
+            .quote_fill(
+                &case.taker,
+                &case.order,
+                &Bytes::from_array(&case.env, &[0; 32]),
+                &U256::from_u32(&case.env, 50),
+                &taking_amount_traits(&case.env, 0),
+                &flat_auction_details(&case.env),
+            )
+            .status,
+        FillStatus::UnexpectedOrderExtension
+    );
+}
+
+/// Extension holding only custom data: every field offset is zero.
+fn custom_data_extension(env: &Env, data: &Bytes) -> Bytes {
+    let mut extension = Bytes::from_array(env, &[0; 32]);
+    extension.append(data);
+    extension
+}
+
+/// Order of `case` whose extension whitelists its taker from `start + delay`.
+fn whitelisted_order(case: &FillCase, start: u32, delay: u32) -> (Order, Bytes) {
+    let whitelist = ResolverWhitelist {
+        resolving_start_time: start,
+        resolvers: vec![
+            &case.env,
+            WhitelistedResolver {
+                sender_bits: MakerTraitsLib::sender_bits(
+                    &case.env,
+                    &CrossChainAddress::Stellar(case.taker.clone()),
+                ),
+                delay,
+            },
+        ],
+    };
+    let extension = custom_data_extension(&case.env, &whitelist.encode(&case.env));
+    let mut order = case.order.clone();
+    order.salt = extension_salt(&case.env, &extension);
+    (order, extension)
+}
+
+#[test]
+fn test_fill_opens_to_whitelisted_resolver_after_its_delay() {
+    let case = FillCase::new(|traits| traits.with_extension());
+    case.env.ledger().set_timestamp(100);
+    let (order, extension) = whitelisted_order(&case, 100, 60);
+    let quote = |taker: &Address| {
+        case.client
+            .quote_fill(
+                taker,
+                &order,
+                &extension,
+                &U256::from_u32(&case.env, 50),
+                &taking_amount_traits(&case.env, 0),
+                &flat_auction_details(&case.env),
+            )
+            .status
+    };
+
+    assert_eq!(
+        quote(&Address::generate(&case.env)),
+        FillStatus::ResolverNotWhitelisted
+    );
+    assert_eq!(quote(&case.taker), FillStatus::ResolvingWindowNotOpen);
+
+    case.env.ledger().set_timestamp(160);
+    assert_eq!(quote(&case.taker), FillStatus::Fillable);
+
+    let empty = BytesN::from_array(&case.env, &[0; 32]);
+    case.client.fill_order(
+        &case.taker,
+        &order,
+        &empty,
+        &empty,
+        &U256::from_u32(&case.env, 50),
+        &taking_amount_traits(&case.env, 0),
+        &case.target,
+        &extension,
+        &Bytes::new(&case.env),
+        &flat_auction_details(&case.env),
+    );
+    assert_eq!(case.settled(), (100, 50));
+}
+
+#[test]
+#[should_panic(expected = "Resolving window not open")]
+fn test_fill_rejects_whitelisted_resolver_before_its_window() {
+    let case = FillCase::new(|traits| traits.with_extension());
+    case.env.ledger().set_timestamp(100);
+    let (order, extension) = whitelisted_order(&case, 100, 60);
+
+    let empty = BytesN::from_array(&case.env, &[0; 32]);
+    case.client.fill_order(
+        &case.taker,
+        &order,
+        &empty,
+        &empty,
+        &U256::from_u32(&case.env, 50),
+        &taking_amount_traits(&case.env, 0),
+        &case.target,
+        &extension,
+        &Bytes::new(&case.env),
+        &flat_auction_details(&case.env),
+    );
+}
+
+/// Extension holding only the `TakingAmountData` field (the fourth one).
+fn fee_extension(env: &Env, fees: &FeeConfig) -> Bytes {
+    let data = fees.encode(env);
+
+    let mut extension = Bytes::new(env);
+    for _ in 0..5 {
+        extension.extend_from_array(&data.len().to_be_bytes());
+    }
+    extension.extend_from_array(&[0; 12]);
+    extension.append(&data);
+    extension
+}
+
+#[test]
+fn test_fill_splits_taker_payment_with_fee_recipients() {
+    let case = FillCase::new(|traits| traits.allow_multiple_fills().with_extension());
+    let fees = FeeConfig {
+        integrator: Address::generate(&case.env),
+        integrator_fee: 1000,
+        protocol: Address::generate(&case.env),
+        protocol_fee: 400,
+        protocol_surplus_share: 5000,
+    };
+    let extension = fee_extension(&case.env, &fees);
+    let mut order = case.order.clone();
+    order.salt = extension_salt(&case.env, &extension);
+    StellarAssetClient::new(&case.env, &order.taker_asset).mint(&case.taker, &7);
+
+    // Half of the order: 25 for the maker, 10% and 4% of it on top
+    let quote = |amount: u32, taker_traits: U256| {
+        case.client.quote_fill(
+            &case.taker,
+            &order,
+            &extension,
+            &U256::from_u32(&case.env, amount),
+            &taker_traits,
+            &flat_auction_details(&case.env),
+        )
+    };
+    let expected_fees = FeeSplit {
+        maker_amount: U256::from_u32(&case.env, 25),
+        integrator_amount: U256::from_u32(&case.env, 2),
+        protocol_amount: U256::from_u32(&case.env, 1),
+        surplus: U256::from_u32(&case.env, 0),
+        protocol_surplus_amount: U256::from_u32(&case.env, 0),
+    };
+    let by_making = quote(50, making_amount_traits(&case.env, 0));
+    assert_eq!(by_making.taking_amount, U256::from_u32(&case.env, 28));
+    assert_eq!(by_making.fees, expected_fees);
+
+    // Paying 28 in total buys the same half
+    let by_taking = quote(28, taking_amount_traits(&case.env, 0));
+    assert_eq!(by_taking.making_amount, U256::from_u32(&case.env, 50));
+    assert_eq!(by_taking.fees, expected_fees);
+
+    let empty = BytesN::from_array(&case.env, &[0; 32]);
+    let fill = |amount: u32| {
+        case.client.fill_order(
+            &case.taker,
+            &order,
+            &empty,
+            &empty,
+            &U256::from_u32(&case.env, amount),
+            &making_amount_traits(&case.env, 0),
+            &case.target,
+            &extension,
+            &Bytes::new(&case.env),
+            &flat_auction_details(&case.env),
+        )
+    };
+    fill(50);
+    fill(50);
+
+    let taker_asset = TokenClient::new(&case.env, &order.taker_asset);
+    assert_eq!(case.settled(), (100, 50));
+    assert_eq!(taker_asset.balance(&fees.integrator), 4);
+    assert_eq!(taker_asset.balance(&fees.protocol), 2);
+    assert_eq!(taker_asset.balance(&case.taker), 1);
+}
+
+#[test]
+fn test_fill_shares_surplus_above_order_rate_with_protocol() {
+    let case = FillCase::new(|traits| traits.with_extension());
+    let fees = FeeConfig {
+        integrator: Address::generate(&case.env),
+        integrator_fee: 0,
+        protocol: Address::generate(&case.env),
+        protocol_fee: 0,
+        protocol_surplus_share: 5000,
+    };
+    let extension = fee_extension(&case.env, &fees);
+    let mut order = case.order.clone();
+    order.salt = extension_salt(&case.env, &extension);
+    StellarAssetClient::new(&case.env, &order.taker_asset).mint(&case.taker, &10);
+
+    // The auction prices the whole order at 60, 10 above its own rate
+    let mut auction_details = flat_auction_details(&case.env);
+    auction_details.taking_amount_start = U256::from_u32(&case.env, 60);
+    auction_details.taking_amount_end = U256::from_u32(&case.env, 60);
+
+    let empty = BytesN::from_array(&case.env, &[0; 32]);
+    case.client.fill_order(
+        &case.taker,
+        &order,
+        &empty,
+        &empty,
+        &U256::from_u32(&case.env, 100),
+        &making_amount_traits(&case.env, 0),
+        &case.target,
+        &extension,
+        &Bytes::new(&case.env),
+        &auction_details,
+    );
+
+    let event = case.last_filled_event();
+    assert_eq!(event.taking_amount, U256::from_u32(&case.env, 60));
+    assert_eq!(event.surplus, U256::from_u32(&case.env, 10));
+    assert_eq!(event.protocol_surplus, U256::from_u32(&case.env, 5));
+    assert_eq!(event.receiver_amount, U256::from_u32(&case.env, 55));
+
+    let taker_asset = TokenClient::new(&case.env, &order.taker_asset);
+    assert_eq!(case.settled(), (100, 55));
+    assert_eq!(taker_asset.balance(&fees.protocol), 5);
+    assert_eq!(taker_asset.balance(&fees.integrator), 0);
+}
+
+#[test]
+#[should_panic(expected = "InvalidExtensionHash")]
+fn test_fill_rejects_extension_not_bound_to_order() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, taker, mut order, permit) = permit_order(&env);
+    order.salt = U256::from_u32(&env, 7);
+
+    fill_with_permit(
+        &env,
+        &client,
+        &taker,
+        &order,
+        &permit,
+        U256::from_u32(&env, 0),
+    );
+}
+
+/// Records the extra data it was called with and what the order receiver held at that point.
+#[contract]
+pub struct InteractionListener;
+
+#[contractimpl]
+impl PreInteractionInterface for InteractionListener {
+    fn pre_interaction(
+        env: Env,
+        order: Order,
+        _extension: Bytes,
+        _order_hash: BytesN<32>,
+        _taker: Address,
+        _making_amount: U256,
+        _taking_amount: U256,
+        _remaining_making_amount: U256,
+        extra_data: Bytes,
+    ) {
+        let received = TokenClient::new(&env, &order.taker_asset).balance(&order.receiver);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("PRE"), &(extra_data, received));
+    }
+}
+
+#[contractimpl]
+impl PostInteractionInterface for InteractionListener {
+    fn post_interaction(
+        env: Env,
+        order: Order,
+        _extension: Bytes,
+        _order_hash: BytesN<32>,
+        _taker: Address,
+        _making_amount: U256,
+        _taking_amount: U256,
+        _remaining_making_amount: U256,
+        extra_data: Bytes,
+    ) {
+        let received = TokenClient::new(&env, &order.taker_asset).balance(&order.receiver);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("POST"), &(extra_data, received));
+    }
+}
+
+#[test]
+fn test_fill_calls_interactions_around_transfers() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+    let listener = env.register(InteractionListener, ());
+    let listener_xdr = listener.clone().to_xdr(&env);
+    let listener_id = listener_xdr.slice(listener_xdr.len() - 32..);
+
+    let mut pre_data = listener_id.clone();
+    pre_data.extend_from_slice(b"pre");
+    let mut post_data = listener_id;
+    post_data.extend_from_slice(b"post");
+
+    // Offsets word: end of PreInteractionData in bits 192..224, of PostInteractionData in 224..256
+    let mut extension = Bytes::new(&env);
+    extension.extend_from_array(&(pre_data.len() + post_data.len()).to_be_bytes());
+    extension.extend_from_array(&pre_data.len().to_be_bytes());
+    extension.extend_from_array(&[0; 24]);
+    extension.append(&pre_data);
+    extension.append(&post_data);
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let traits = MakerTraitsBuilder::new(env.clone())
+        .with_extension()
+        .with_pre_interaction_call()
+        .with_post_interaction_call()
+        .build();
+    let mut order = create_stellar_order(&env, &contract_id, &maker, &taker, traits);
+    order.salt = extension_salt(&env, &extension);
+
+    let empty = BytesN::from_array(&env, &[0; 32]);
+    client.fill_order(
+        &taker,
+        &order,
+        &empty,
+        &empty,
+        &U256::from_u32(&env, 50),
+        &U256::from_u32(&env, 0),
+        &Address::generate(&env),
+        &extension,
+        &Bytes::new(&env),
+        &flat_auction_details(&env),
+    );
+
+    env.as_contract(&listener, || {
+        let pre: (Bytes, i128) = env.storage().instance().get(&symbol_short!("PRE")).unwrap();
+        let post: (Bytes, i128) = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("POST"))
+            .unwrap();
+        assert_eq!(pre, (Bytes::from_slice(&env, b"pre"), 0));
+        assert_eq!(post, (Bytes::from_slice(&env, b"post"), 50));
+    });
+}
+
+/// Sends the taker the taker assets of the fill out of its own liquidity, recording the extra
+/// data and what the order receiver held at that point.
+#[contract]
+pub struct JustInTimeLiquidity;
+
+#[contractimpl]
+impl TakerInteractionInterface for JustInTimeLiquidity {
+    fn taker_interaction(
+        env: Env,
+        order: Order,
+        _extension: Bytes,
+        _order_hash: BytesN<32>,
+        taker: Address,
+        _making_amount: U256,
+        taking_amount: U256,
+        _remaining_making_amount: U256,
+        extra_data: Bytes,
+    ) {
+        let taker_asset = TokenClient::new(&env, &order.taker_asset);
+        let received = taker_asset.balance(&order.receiver);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("TAKER"), &(extra_data, received));
+
+        taker_asset.transfer(
+            &env.current_contract_address(),
+            &taker,
+            &(taking_amount.to_u128().unwrap() as i128),
+        );
+    }
+}
+
+#[test]
+fn test_fill_calls_taker_interaction_between_transfers() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+    let liquidity = env.register(JustInTimeLiquidity, ());
+    let liquidity_xdr = liquidity.clone().to_xdr(&env);
+    let mut interaction = liquidity_xdr.slice(liquidity_xdr.len() - 32..);
+    interaction.extend_from_slice(b"taker");
+
+    // The taker holds none of the taker asset, the interaction sources it
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let order = create_stellar_order(
+        &env,
+        &contract_id,
+        &maker,
+        &liquidity,
+        U256::from_u32(&env, 0),
+    );
+    let target = Address::generate(&env);
+
+    let empty = BytesN::from_array(&env, &[0; 32]);
+    client.fill_order(
+        &taker,
+        &order,
+        &empty,
+        &empty,
+        &U256::from_u32(&env, 50),
+        &U256::from_u32(&env, 0),
+        &target,
+        &Bytes::new(&env),
+        &interaction,
+        &flat_auction_details(&env),
+    );
+
+    env.as_contract(&liquidity, || {
+        let taker_call: (Bytes, i128) = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("TAKER"))
+            .unwrap();
+        assert_eq!(taker_call, (Bytes::from_slice(&env, b"taker"), 0));
+    });
+    assert_eq!(
+        TokenClient::new(&env, &order.maker_asset).balance(&target),
+        100
+    );
+    assert_eq!(
+        TokenClient::new(&env, &order.taker_asset).balance(&order.receiver),
+        50
+    );
+    assert_eq!(
+        TokenClient::new(&env, &order.taker_asset).balance(&taker),
+        0
+    );
+}
+
+#[test]
+fn test_fill_order_args_decodes_target_and_interaction() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+    let liquidity = env.register(JustInTimeLiquidity, ());
+    let liquidity_xdr = liquidity.clone().to_xdr(&env);
+    let mut interaction = liquidity_xdr.slice(liquidity_xdr.len() - 32..);
+    interaction.extend_from_slice(b"args");
+
+    let maker = Address::generate(&env);
+    let taker = Address::generate(&env);
+    let target = Address::generate(&env);
+    let order = create_stellar_order(
+        &env,
+        &contract_id,
+        &maker,
+        &liquidity,
+        U256::from_u32(&env, 0),
+    );
+
+    let (taker_traits, args) = TakerArgs {
+        target: Some(target.clone()),
+        extension: Bytes::new(&env),
+        interaction,
+    }
+    .encode(&env, making_amount_traits(&env, 100));
+
+    // Half of the order, at most 100 making for the 25 taking
+    client.fill_order_args(
+        &taker,
+        &order,
+        &BytesN::from_array(&env, &[0; 32]),
+        &BytesN::from_array(&env, &[0; 32]),
+        &U256::from_u32(&env, 50),
+        &taker_traits,
+        &args,
+        &flat_auction_details(&env),
+    );
+
+    env.as_contract(&liquidity, || {
+        let taker_call: (Bytes, i128) = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("TAKER"))
+            .unwrap();
+        assert_eq!(taker_call, (Bytes::from_slice(&env, b"args"), 0));
+    });
+    assert_eq!(
+        TokenClient::new(&env, &order.maker_asset).balance(&target),
+        50
+    );
+    assert_eq!(
+        TokenClient::new(&env, &order.taker_asset).balance(&order.receiver),
+        25
+    );
+}
+
+#[test]
+fn test_domain_separator_with_explicit_chain_id() {
+    let env = Env::default();
+    let contract_id = create_order_protocol(&env, Some(U256::from_u32(&env, 1)));
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let keccak = |data: &[u8]| env.crypto().keccak256(&Bytes::from_slice(&env, data));
+    let contract_xdr = contract_id.clone().to_xdr(&env);
+
+    let mut expected = Bytes::new(&env);
+    expected.extend_from_array(
+        &keccak(
+            b"EIP712Domain(string name,string version,uint256 chainId,bytes32 verifyingContract)",
+        )
+        .to_array(),
+    );
+    expected.extend_from_array(&keccak(b"1inch Limit Order Protocol").to_array());
+    expected.extend_from_array(&keccak(b"4").to_array());
+    expected.append(&U256::from_u32(&env, 1).to_be_bytes());
+    expected.append(&contract_xdr.slice(contract_xdr.len() - 32..));
+
+    assert_eq!(
+        client.domain_separator(),
+        BytesN::from(env.crypto().keccak256(&expected))
+    );
+}
+
+#[test]
+fn test_domain_separator_depends_on_network() {
+    let separator_on = |passphrase: &str| {
+        let env = Env::default();
+        let network_id = env
+            .crypto()
+            .sha256(&Bytes::from_slice(&env, passphrase.as_bytes()));
+        env.ledger().set_network_id(network_id.to_array());
+
+        let contract_id = create_order_protocol(&env, None);
+        OrderProtocolClient::new(&env, &contract_id)
+            .domain_separator()
+            .to_array()
+    };
+
+    assert_ne!(
+        separator_on("Test SDF Network ; September 2015"),
+        separator_on("Public Global Stellar Network ; September 2015")
+    );
+}
+
+/// Order of a fresh maker selling `making_amount` of `maker_asset`, approved to the order
+/// contract, with auction details pricing it at its own rate.
+fn batch_fill(
+    env: &Env,
+    order_protocol: &Address,
+    maker_asset: &Address,
+    taker_asset: &Address,
+    making_amount: u32,
+    taking_amount: u32,
+) -> BatchFill {
+    let maker = Address::generate(env);
+    StellarAssetClient::new(env, maker_asset).mint(&maker, &(making_amount as i128));
+    OrderProtocolClient::new(env, order_protocol).approve(
+        &maker,
+        maker_asset,
+        &(making_amount as i128),
+        &(env.ledger().sequence() + 100),
+    );
+
+    let empty = BytesN::from_array(env, &[0; 32]);
+    BatchFill {
+        order: Order {
+            salt: U256::from_u32(env, 7),
+            maker,
+            maker_evm_address: None,
+            receiver: Address::generate(env),
+            maker_asset: maker_asset.clone(),
+            taker_asset: taker_asset.clone(),
+            making_amount: U256::from_u32(env, making_amount),
+            taking_amount: U256::from_u32(env, taking_amount),
+            maker_traits: U256::from_u32(env, 0),
+        },
+        r: empty.clone(),
+        vs: empty,
+        amount: U256::from_u32(env, taking_amount),
+        taker_traits: U256::from_u32(env, 0),
+        args: Bytes::new(env),
+        auction_details: AuctionDetails {
+            auction_window: AuctionWindow {
+                start: 0,
+                end: 1000,
+            },
+            taking_amount_start: U256::from_u32(env, taking_amount),
+            taking_amount_end: U256::from_u32(env, taking_amount),
+        },
+    }
+}
+
+#[test]
+fn test_fill_orders_settles_each_order() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let taker = Address::generate(&env);
+    let x = create_token(&env);
+    let y = create_token(&env);
+    StellarAssetClient::new(&env, &y).mint(&taker, &80);
+
+    let first = batch_fill(&env, &contract_id, &x, &y, 100, 50);
+    let second = batch_fill(&env, &contract_id, &x, &y, 60, 30);
+    let results = client.fill_orders(&taker, &vec![&env, first.clone(), second.clone()], &false);
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.get(0).unwrap().0, U256::from_u32(&env, 100));
+    assert_eq!(results.get(1).unwrap().1, U256::from_u32(&env, 30));
+    assert_eq!(results.get(1).unwrap().2, client.order_hash(&second.order));
+
+    // Without a target in the args, maker assets go to the taker
+    assert_eq!(TokenClient::new(&env, &x).balance(&taker), 160);
+    assert_eq!(TokenClient::new(&env, &y).balance(&taker), 0);
+    assert_eq!(
+        TokenClient::new(&env, &y).balance(&first.order.receiver),
+        50
+    );
+    assert_eq!(
+        TokenClient::new(&env, &y).balance(&second.order.receiver),
+        30
+    );
+}
+
+#[test]
+fn test_fill_orders_net_settlement_of_crossing_orders() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let taker = Address::generate(&env);
+    let x = create_token(&env);
+    let y = create_token(&env);
+    // Makers bring 100 X and 40 Y, receivers are owed 80 X and 50 Y
+    let sell_x = batch_fill(&env, &contract_id, &x, &y, 100, 50);
+    let sell_y = batch_fill(&env, &contract_id, &y, &x, 40, 80);
+    StellarAssetClient::new(&env, &y).mint(&taker, &10);
+
+    client.fill_orders(&taker, &vec![&env, sell_x.clone(), sell_y.clone()], &true);
+
+    // The taker only covered the 10 Y shortfall and kept the 20 X surplus
+    assert_eq!(TokenClient::new(&env, &x).balance(&taker), 20);
+    assert_eq!(TokenClient::new(&env, &y).balance(&taker), 0);
+    assert_eq!(
+        TokenClient::new(&env, &y).balance(&sell_x.order.receiver),
+        50
+    );
+    assert_eq!(
+        TokenClient::new(&env, &x).balance(&sell_y.order.receiver),
+        80
+    );
+    assert_eq!(TokenClient::new(&env, &x).balance(&contract_id), 0);
+    assert_eq!(TokenClient::new(&env, &y).balance(&contract_id), 0);
+}
+
+#[test]
+#[should_panic(expected = "Net settlement pays the taker")]
+fn test_fill_orders_net_settlement_rejects_target() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let taker = Address::generate(&env);
+    let x = create_token(&env);
+    let y = create_token(&env);
+    StellarAssetClient::new(&env, &y).mint(&taker, &50);
+
+    let mut fill = batch_fill(&env, &contract_id, &x, &y, 100, 50);
+    (fill.taker_traits, fill.args) = TakerArgs {
+        target: Some(Address::generate(&env)),
+        extension: Bytes::new(&env),
+        interaction: Bytes::new(&env),
+    }
+    .encode(&env, fill.taker_traits);
+
+    client.fill_orders(&taker, &vec![&env, fill], &true);
+}
+
+#[test]
+#[should_panic(expected = "Net settlement pays the taker")]
+fn test_fill_orders_net_settlement_rejects_unwrap() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let taker = Address::generate(&env);
+    let x = create_token(&env);
+    let y = create_token(&env);
+    StellarAssetClient::new(&env, &y).mint(&taker, &50);
+
+    let mut fill = batch_fill(&env, &contract_id, &x, &y, 100, 50);
+    fill.taker_traits = U256::from_u32(&env, 1).shl(254);
+
+    client.fill_orders(&taker, &vec![&env, fill], &true);
+}
+
+#[test]
+#[should_panic(expected = "Net settlement has no taker interaction")]
+fn test_fill_orders_net_settlement_rejects_taker_interaction() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let taker = Address::generate(&env);
+    let x = create_token(&env);
+    let y = create_token(&env);
+    StellarAssetClient::new(&env, &y).mint(&taker, &50);
+
+    let mut fill = batch_fill(&env, &contract_id, &x, &y, 100, 50);
+    (fill.taker_traits, fill.args) = TakerArgs {
+        target: None,
+        extension: Bytes::new(&env),
+        interaction: Bytes::from_array(&env, &[0x11; 32]),
+    }
+    .encode(&env, fill.taker_traits);
+
+    client.fill_orders(&taker, &vec![&env, fill], &true);
+}
+
+#[test]
+fn test_fill_orders_reverts_together() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let contract_id = create_order_protocol(&env, None);
+    let client = OrderProtocolClient::new(&env, &contract_id);
+
+    let taker = Address::generate(&env);
+    let x = create_token(&env);
+    let y = create_token(&env);
+    StellarAssetClient::new(&env, &y).mint(&taker, &80);
+
+    let valid = batch_fill(&env, &contract_id, &x, &y, 100, 50);
+    let mut expired = batch_fill(&env, &contract_id, &x, &y, 60, 30);
+    expired.order.maker_traits = MakerTraitsBuilder::new(env.clone())
+        .with_expiration(1)
+        .build();
+    env.ledger().with_mut(|ledger| ledger.timestamp = 10);
+
+    for net_settlement in [false, true] {
+        let result = client.try_fill_orders(
+            &taker,
+            &vec![&env, valid.clone(), expired.clone()],
+            &net_settlement,
+        );
+        assert!(result.is_err());
+
+        // The valid order was neither paid nor invalidated
+        assert_eq!(TokenClient::new(&env, &x).balance(&taker), 0);
+        assert_eq!(TokenClient::new(&env, &y).balance(&taker), 80);
+        assert_eq!(
+            client._check_remaining_making_amount(&valid.order, &client.order_hash(&valid.order)),
+            U256::from_u32(&env, 100)
+        );
+    }
+}
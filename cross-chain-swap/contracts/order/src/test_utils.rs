@@ -0,0 +1,70 @@
+#![cfg(test)]
+
+// Helpers shared by the test modules of the crate.
+
+use k256::ecdsa::SigningKey;
+use soroban_sdk::{Address, BytesN, Env, String, U256};
+use utils::ttl::TtlPolicy;
+
+use crate::OrderProtocol;
+use dutch_auction::DutchAuctionCalculatorContract;
+
+pub fn create_order_protocol(env: &Env, chain_id: Option<U256>) -> Address {
+    register_order_protocol(env, chain_id, None)
+}
+
+pub fn register_order_protocol(
+    env: &Env,
+    chain_id: Option<U256>,
+    wrapped_native: Option<Address>,
+) -> Address {
+    let dutch_auction_calculator_address = env.register(DutchAuctionCalculatorContract, ());
+    env.register(
+        OrderProtocol,
+        (
+            &dutch_auction_calculator_address,
+            String::from_str(env, "1inch Limit Order Protocol"),
+            String::from_str(env, "4"),
+            chain_id,
+            wrapped_native,
+            None::<TtlPolicy>,
+        ),
+    )
+}
+
+/// Signs `digest` the way an Ethereum wallet does and packs it as EIP-2098 (r, vs).
+pub fn sign(env: &Env, key: &[u8; 32], digest: &[u8; 32]) -> (BytesN<32>, BytesN<32>) {
+    let (signature, recovery_id) = SigningKey::from_slice(key)
+        .unwrap()
+        .sign_prehash_recoverable(digest)
+        .unwrap();
+    let bytes = signature.to_bytes();
+
+    let mut r = [0u8; 32];
+    r.copy_from_slice(&bytes[..32]);
+    let mut vs = [0u8; 32];
+    vs.copy_from_slice(&bytes[32..]);
+    if recovery_id.is_y_odd() {
+        vs[0] |= 0x80;
+    }
+    (BytesN::from_array(env, &r), BytesN::from_array(env, &vs))
+}
+
+/// Decodes 64 lowercase hex digits.
+pub const fn hex32(s: &str) -> [u8; 32] {
+    let s = s.as_bytes();
+    let mut out = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        out[i] = (nibble(s[2 * i]) << 4) | nibble(s[2 * i + 1]);
+        i += 1;
+    }
+    out
+}
+
+const fn nibble(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        _ => c - b'a' + 10,
+    }
+}
@@ -0,0 +1,94 @@
+use soroban_sdk::{contracttype, Address, Bytes, Env, Vec};
+use utils::address::CrossChainAddress;
+
+use order_lib::maker_traits::MakerTraitsLib;
+
+// Binary layout of the `CustomData` extension field, restricting fills to whitelisted
+// resolvers:
+//
+//   resolving_start_time  4 bytes, big-endian Unix time the resolving windows are counted from
+//   resolvers             RESOLVER_LENGTH bytes each:
+//     sender_bits         10 bytes, `MakerTraitsLib::sender_bits` of the resolver
+//     delay               2 bytes, big-endian seconds after `resolving_start_time` its window opens
+//
+// Orders without the field can be filled by anyone.
+
+/// Length of an encoded resolver entry
+pub const RESOLVER_LENGTH: u32 = 12;
+
+const START_TIME_LENGTH: u32 = 4;
+const SENDER_BITS_LENGTH: u32 = 10;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WhitelistedResolver {
+    /// Low 80 bits of the resolver identifier, as in the allowed sender maker trait
+    pub sender_bits: u128,
+    /// Seconds after the resolving start time the resolver may fill from
+    pub delay: u32,
+}
+
+/// Resolvers allowed to fill an order, each from its own start time
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResolverWhitelist {
+    pub resolving_start_time: u32,
+    pub resolvers: Vec<WhitelistedResolver>,
+}
+
+impl ResolverWhitelist {
+    pub fn encode(&self, env: &Env) -> Bytes {
+        let mut data = Bytes::from_array(env, &self.resolving_start_time.to_be_bytes());
+        for resolver in self.resolvers.iter() {
+            if resolver.sender_bits >> (SENDER_BITS_LENGTH * 8) != 0 || resolver.delay > 0xffff {
+                panic!("Invalid whitelist");
+            }
+            data.extend_from_slice(&resolver.sender_bits.to_be_bytes()[6..]);
+            data.extend_from_slice(&(resolver.delay as u16).to_be_bytes());
+        }
+        data
+    }
+
+    pub fn decode(env: &Env, data: &Bytes) -> Self {
+        if data.len() < START_TIME_LENGTH
+            || !(data.len() - START_TIME_LENGTH).is_multiple_of(RESOLVER_LENGTH)
+        {
+            panic!("Invalid whitelist");
+        }
+
+        let mut start_time = [0u8; 4];
+        data.slice(..START_TIME_LENGTH)
+            .copy_into_slice(&mut start_time);
+
+        let mut resolvers = Vec::new(env);
+        let mut offset = START_TIME_LENGTH;
+        while offset < data.len() {
+            let mut entry = [0u8; RESOLVER_LENGTH as usize];
+            data.slice(offset..offset + RESOLVER_LENGTH)
+                .copy_into_slice(&mut entry);
+
+            let mut sender_bits = [0u8; 16];
+            sender_bits[6..].copy_from_slice(&entry[..SENDER_BITS_LENGTH as usize]);
+            resolvers.push_back(WhitelistedResolver {
+                sender_bits: u128::from_be_bytes(sender_bits),
+                delay: u16::from_be_bytes([entry[10], entry[11]]) as u32,
+            });
+            offset += RESOLVER_LENGTH;
+        }
+
+        ResolverWhitelist {
+            resolving_start_time: u32::from_be_bytes(start_time),
+            resolvers,
+        }
+    }
+
+    /// Time `taker` may fill from, or `None` if it is not whitelisted.
+    pub fn allowed_time(&self, env: &Env, taker: &Address) -> Option<u64> {
+        let sender_bits =
+            MakerTraitsLib::sender_bits(env, &CrossChainAddress::Stellar(taker.clone()));
+
+        self.resolvers
+            .iter()
+            .find(|resolver| resolver.sender_bits == sender_bits)
+            .map(|resolver| self.resolving_start_time as u64 + resolver.delay as u64)
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(test)]
+
+use soroban_sdk::{testutils::Address as _, vec, Address, Bytes, Env};
+use utils::address::CrossChainAddress;
+
+use crate::whitelist::{ResolverWhitelist, WhitelistedResolver, RESOLVER_LENGTH};
+use order_lib::maker_traits::MakerTraitsLib;
+
+fn resolver(env: &Env, address: &Address, delay: u32) -> WhitelistedResolver {
+    WhitelistedResolver {
+        sender_bits: MakerTraitsLib::sender_bits(env, &CrossChainAddress::Stellar(address.clone())),
+        delay,
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let env = Env::default();
+    let whitelist = ResolverWhitelist {
+        resolving_start_time: 1_700_000_000,
+        resolvers: vec![
+            &env,
+            resolver(&env, &Address::generate(&env), 0),
+            resolver(&env, &Address::generate(&env), 0xffff),
+        ],
+    };
+
+    let data = whitelist.encode(&env);
+    assert_eq!(data.len(), 4 + 2 * RESOLVER_LENGTH);
+    assert_eq!(ResolverWhitelist::decode(&env, &data), whitelist);
+}
+
+#[test]
+fn test_allowed_time_is_offset_by_resolver_delay() {
+    let env = Env::default();
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let whitelist = ResolverWhitelist {
+        resolving_start_time: 1000,
+        resolvers: vec![&env, resolver(&env, &first, 0), resolver(&env, &second, 30)],
+    };
+
+    assert_eq!(whitelist.allowed_time(&env, &first), Some(1000));
+    assert_eq!(whitelist.allowed_time(&env, &second), Some(1030));
+    assert_eq!(whitelist.allowed_time(&env, &Address::generate(&env)), None);
+}
+
+#[test]
+#[should_panic(expected = "Invalid whitelist")]
+fn test_decode_rejects_truncated_resolver() {
+    let env = Env::default();
+    let whitelist = ResolverWhitelist {
+        resolving_start_time: 1000,
+        resolvers: vec![&env, resolver(&env, &Address::generate(&env), 0)],
+    };
+    let data = whitelist.encode(&env);
+
+    ResolverWhitelist::decode(&env, &data.slice(..data.len() - 1));
+}
+
+#[test]
+#[should_panic(expected = "Invalid whitelist")]
+fn test_encode_rejects_delay_over_two_bytes() {
+    let env = Env::default();
+    let whitelist = ResolverWhitelist {
+        resolving_start_time: 1000,
+        resolvers: vec![&env, resolver(&env, &Address::generate(&env), 0x10000)],
+    };
+
+    whitelist.encode(&env);
+}
+
+#[test]
+fn test_decode_empty_list() {
+    let env = Env::default();
+    let whitelist = ResolverWhitelist::decode(&env, &Bytes::from_array(&env, &[0, 0, 0, 1]));
+
+    assert_eq!(whitelist.resolving_start_time, 1);
+    assert!(whitelist.resolvers.is_empty());
+}
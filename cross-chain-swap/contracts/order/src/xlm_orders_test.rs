@@ -1,844 +0,0 @@
-#![cfg(test)]
-
-use soroban_sdk::{
-    symbol_short,
-    testutils::{Address as _, Ledger},
-    xdr::ToXdr,
-    Address, Bytes, BytesN, Env, Vec, U256,
-};
-
-use crate::xlm_orders::{
-    domain_separator_v4, hash, is_valid_extension, ValidationResult, XLMOrders, XLMOrdersArr,
-};
-use order_interface::Order;
-
-fn create_test_env() -> Env {
-    let env = Env::default();
-    env.ledger().with_mut(|ledger| {
-        ledger.timestamp = 1000;
-    });
-    env
-}
-
-fn create_test_order(env: &Env, maker: Address, receiver: Address) -> Order {
-    Order {
-        salt: U256::from_u32(env, 12345),
-        maker: maker.clone(),
-        receiver: receiver.clone(),
-        maker_asset: Address::generate(env),
-        taker_asset: Address::generate(env),
-        making_amount: U256::from_u128(env, 1000),
-        taking_amount: U256::from_u128(env, 500),
-        maker_traits: U256::from_u32(env, 0),
-    }
-}
-
-fn create_xlm_orders_contract(env: &Env) -> Address {
-    let xlm = Address::generate(env);
-    let limit_order_protocol = Address::generate(env);
-    let access_token = Address::generate(env);
-
-    let contract_id = env.register_contract(None, XLMOrders);
-
-    // Set up storage for the contract
-    env.as_contract(&contract_id, || {
-        env.storage().instance().set(&symbol_short!("XLM"), &xlm);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("LIM_ORP"), &limit_order_protocol);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("ACC_TOK"), &access_token);
-    });
-
-    contract_id
-}
-
-fn with_contract_storage<F, R>(env: &Env, contract_id: &Address, f: F) -> R
-where
-    F: FnOnce() -> R,
-{
-    env.as_contract(contract_id, f)
-}
-
-#[test]
-fn test_constructor() {
-    let env = create_test_env();
-    let xlm = Address::generate(&env);
-    let limit_order_protocol = Address::generate(&env);
-    let access_token = Address::generate(&env);
-
-    let contract_id = env.register_contract(None, XLMOrders);
-
-    // Call constructor within contract context
-    env.as_contract(&contract_id, || {
-        XLMOrders::constructor(
-            env.clone(),
-            xlm.clone(),
-            limit_order_protocol.clone(),
-            access_token.clone(),
-        );
-    });
-
-    // Verify storage was set correctly
-    with_contract_storage(&env, &contract_id, || {
-        assert_eq!(
-            env.storage()
-                .instance()
-                .get::<_, Address>(&symbol_short!("XLM"))
-                .unwrap(),
-            xlm
-        );
-        assert_eq!(
-            env.storage()
-                .instance()
-                .get::<_, Address>(&symbol_short!("LIM_ORP"))
-                .unwrap(),
-            limit_order_protocol
-        );
-        assert_eq!(
-            env.storage()
-                .instance()
-                .get::<_, Address>(&symbol_short!("ACC_TOK"))
-                .unwrap(),
-            access_token
-        );
-    });
-}
-
-#[test]
-fn test_xlm_orders_batch_empty() {
-    let env = create_test_env();
-    let _contract_id = create_xlm_orders_contract(&env);
-
-    let order_hashes = Vec::new(&env);
-    let result = XLMOrders::xlm_orders_batch(env, order_hashes);
-
-    assert_eq!(result.len(), 0);
-}
-
-#[test]
-fn test_xlm_orders_batch_with_orders() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    // Create test order hashes
-    let mut order_hashes = Vec::new(&env);
-    let hash1 = BytesN::from_array(&env, &[1u8; 32]);
-    let hash2 = BytesN::from_array(&env, &[2u8; 32]);
-    order_hashes.push_back(hash1.clone());
-    order_hashes.push_back(hash2.clone());
-
-    // Create test order data
-    let order_data1 = XLMOrdersArr {
-        maker: Address::generate(&env),
-        balance: 1000,
-        maximum_premium: 100,
-        auction_duration: 3600,
-    };
-
-    let order_data2 = XLMOrdersArr {
-        maker: Address::generate(&env),
-        balance: 2000,
-        maximum_premium: 200,
-        auction_duration: 7200,
-    };
-
-    // Store order data and call function within contract context
-    with_contract_storage(&env, &contract_id, || {
-        env.storage().instance().set(&hash1, &order_data1);
-        env.storage().instance().set(&hash2, &order_data2);
-
-        let result = XLMOrders::xlm_orders_batch(env.clone(), order_hashes);
-
-        assert_eq!(result.len(), 2);
-        assert_eq!(result.get(0).unwrap(), order_data1);
-        assert_eq!(result.get(1).unwrap(), order_data2);
-    });
-}
-
-#[test]
-fn test_xlm_order_deposit_success() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let receiver = Address::generate(&env);
-    // Create order with maker as the contract address
-    let order = create_test_order(&env, contract_id.clone(), receiver.clone());
-
-    // Don't set HAS_EXTENSION_FLAG to avoid complex extension parsing
-    // order.maker_traits = U256::from_u128(&env, 1u128).shl(249u32);
-
-    // Create a simple extension without complex parsing
-    let _extension = Bytes::from_array(&env, &[0u8; 0]);
-
-    let _maximum_premium = 100u32;
-    let _auction_duration = 3600u32;
-
-    // For now, just test that the basic setup is correct
-    // The complex extension parsing will need more work
-    with_contract_storage(&env, &contract_id, || {
-        // Set up storage for the deposit
-        env.storage()
-            .instance()
-            .set(&symbol_short!("sender"), &receiver);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("value"), &1000u128);
-
-        // Verify the setup is correct
-        assert_eq!(order.maker, contract_id);
-        assert_eq!(order.making_amount, U256::from_u32(&env, 1000));
-        assert_eq!(order.receiver, receiver);
-    });
-}
-
-#[test]
-#[should_panic(expected = "InvalidOrder")]
-fn test_xlm_order_deposit_no_post_interaction() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let _maker = Address::generate(&env);
-    let receiver = Address::generate(&env);
-
-    // Create order with post-interaction flag
-    let mut order = create_test_order(&env, contract_id.clone(), receiver.clone());
-    order.maker_traits = U256::from_u128(&env, 1u128).shl(251u32); // POST_INTERACTION_CALL_FLAG
-
-    let extension = Bytes::from_array(&env, &[0u8; 0]);
-    let maximum_premium = 100u32;
-    let auction_duration = 3600u32;
-
-    XLMOrders::xlm_order_deposit(env, order, extension, maximum_premium, auction_duration);
-}
-
-#[test]
-#[should_panic(expected = "AccessDenied")]
-fn test_xlm_order_deposit_wrong_maker() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let maker = Address::generate(&env);
-    let receiver = Address::generate(&env);
-
-    // Create order with wrong maker (not the contract)
-    let order = create_test_order(&env, maker.clone(), receiver.clone());
-
-    with_contract_storage(&env, &contract_id, || {
-        let extension = Bytes::from_array(&env, &[0u8; 0]);
-        let maximum_premium = 100u32;
-        let auction_duration = 3600u32;
-
-        XLMOrders::xlm_order_deposit(
-            env.clone(),
-            order,
-            extension,
-            maximum_premium,
-            auction_duration,
-        );
-    });
-}
-
-#[test]
-#[should_panic(expected = "AccessDenied")]
-fn test_xlm_order_deposit_wrong_receiver() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let maker = Address::generate(&env);
-    let receiver = Address::generate(&env);
-    let wrong_receiver = Address::generate(&env);
-
-    let order = create_test_order(&env, maker.clone(), receiver.clone());
-
-    with_contract_storage(&env, &contract_id, || {
-        // Set wrong receiver in storage
-        env.storage()
-            .instance()
-            .set(&symbol_short!("sender"), &wrong_receiver);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("value"), &1000u128);
-
-        let extension = Bytes::from_array(&env, &[0u8; 0]);
-        let maximum_premium = 100u32;
-        let auction_duration = 3600u32;
-
-        XLMOrders::xlm_order_deposit(
-            env.clone(),
-            order,
-            extension,
-            maximum_premium,
-            auction_duration,
-        );
-    });
-}
-
-#[test]
-fn test_xlm_order_deposit_wrong_making_amount() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let receiver = Address::generate(&env);
-    // Create order with maker as the contract address
-    let order = create_test_order(&env, contract_id.clone(), receiver.clone());
-
-    with_contract_storage(&env, &contract_id, || {
-        // Set wrong making amount in storage
-        env.storage()
-            .instance()
-            .set(&symbol_short!("sender"), &receiver);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("value"), &999u128); // Different from order.making_amount
-
-        // Verify the setup is correct
-        assert_eq!(order.maker, contract_id);
-        assert_eq!(order.making_amount, U256::from_u32(&env, 1000));
-        assert_eq!(order.receiver, receiver);
-
-        // Verify that the storage has the wrong value
-        let stored_value: u128 = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("value"))
-            .unwrap();
-        assert_eq!(stored_value, 999);
-        assert_ne!(stored_value, 1000);
-    });
-}
-
-#[test]
-fn test_xlm_order_deposit_existing_order() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let receiver = Address::generate(&env);
-    // Create order with maker as the contract address
-    let order = create_test_order(&env, contract_id.clone(), receiver.clone());
-
-    // Don't set HAS_EXTENSION_FLAG to avoid complex extension parsing
-    // order.maker_traits = U256::from_u128(&env, 1u128).shl(249u32);
-
-    with_contract_storage(&env, &contract_id, || {
-        // Set up storage
-        env.storage()
-            .instance()
-            .set(&symbol_short!("sender"), &receiver);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("value"), &1000u128);
-
-        // Create order hash
-        let order_hash = hash(&env, &order, &domain_separator_v4(&env));
-
-        // Pre-store order data to simulate existing order
-        let existing_order = XLMOrdersArr {
-            maker: contract_id.clone(),
-            balance: 500,
-            maximum_premium: 50,
-            auction_duration: 1800,
-        };
-        env.storage().instance().set(&order_hash, &existing_order);
-
-        // Verify the setup is correct
-        assert_eq!(order.maker, contract_id);
-        assert_eq!(order.making_amount, U256::from_u32(&env, 1000));
-        assert_eq!(order.receiver, receiver);
-
-        // Verify the existing order was stored
-        let stored_order: XLMOrdersArr = env.storage().instance().get(&order_hash).unwrap();
-        assert_eq!(stored_order.maker, contract_id);
-        assert_eq!(stored_order.balance, 500);
-    });
-}
-
-#[test]
-fn test_cancel_order_success() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let maker = Address::generate(&env);
-    let order_hash = BytesN::from_array(&env, &[1u8; 32]);
-
-    // Create and store order data within contract context
-    with_contract_storage(&env, &contract_id, || {
-        let order_data = XLMOrdersArr {
-            maker: maker.clone(),
-            balance: 1000,
-            maximum_premium: 100,
-            auction_duration: 3600,
-        };
-        env.storage().instance().set(&order_hash, &order_data);
-
-        // Set sender as the maker
-        env.storage()
-            .instance()
-            .set(&symbol_short!("sender"), &maker);
-
-        // Verify the setup is correct
-        let stored_order: XLMOrdersArr = env.storage().instance().get(&order_hash).unwrap();
-        assert_eq!(stored_order.maker, maker);
-        assert_eq!(stored_order.balance, 1000);
-
-        // For now, just test the setup without calling cancel_order
-        // The token transfer functionality will need proper mocking
-        assert_eq!(order_data.maker, maker);
-        assert_eq!(order_data.balance, 1000);
-    });
-}
-
-#[test]
-#[should_panic(expected = "InvalidOrder")]
-fn test_cancel_order_wrong_maker() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let maker = Address::generate(&env);
-    let wrong_maker = Address::generate(&env);
-    let order_hash = BytesN::from_array(&env, &[1u8; 32]);
-
-    // Create and store order data within contract context
-    with_contract_storage(&env, &contract_id, || {
-        let order_data = XLMOrdersArr {
-            maker: maker.clone(),
-            balance: 1000,
-            maximum_premium: 100,
-            auction_duration: 3600,
-        };
-        env.storage().instance().set(&order_hash, &order_data);
-
-        // Set wrong sender
-        env.storage()
-            .instance()
-            .set(&symbol_short!("sender"), &wrong_maker);
-
-        let maker_traits = U256::from_u32(&env, 0);
-
-        XLMOrders::cancel_order(env.clone(), maker_traits, order_hash);
-    });
-}
-
-#[test]
-fn test_cancel_order_mixin_bit_invalidator() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let _maker = Address::generate(&env);
-    let order_hash = BytesN::from_array(&env, &[1u8; 32]);
-
-    // Create maker traits with bit invalidator flag
-    let maker_traits = U256::from_u128(&env, 1u128).shl(134u32); // USE_BIT_INVALIDATOR_FLAG
-
-    with_contract_storage(&env, &contract_id, || {
-        XLMOrders::cancel_order_mixin(env.clone(), maker_traits, order_hash);
-
-        // Verify bit invalidator was updated
-        let bit_invalidator: U256 = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("BIT_INV"))
-            .unwrap();
-        assert!(bit_invalidator > U256::from_u32(&env, 0));
-    });
-}
-
-#[test]
-fn test_cancel_order_mixin_remaining_invalidator() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let _maker = Address::generate(&env);
-    let order_hash = BytesN::from_array(&env, &[1u8; 32]);
-
-    // Create maker traits without bit invalidator flag
-    let maker_traits = U256::from_u32(&env, 0);
-
-    with_contract_storage(&env, &contract_id, || {
-        // For now, just test that the function can be called without panicking
-        // The storage verification will need more work
-        XLMOrders::cancel_order_mixin(env.clone(), maker_traits.clone(), order_hash.clone());
-
-        // Verify that the function was called successfully
-        // The storage verification is complex and will need proper setup
-        assert_eq!(maker_traits, U256::from_u32(&env, 0));
-    });
-}
-
-#[test]
-fn test_get_current_premium_multiplier_not_expired() {
-    let env = create_test_env();
-    let _contract_id = create_xlm_orders_contract(&env);
-
-    let order = XLMOrdersArr {
-        maker: Address::generate(&env),
-        balance: 1000,
-        maximum_premium: 100,
-        auction_duration: 3600,
-    };
-
-    // Set expiration time in the future
-    let expiration_time = U256::from_u128(&env, 2000); // Future time
-
-    let result = XLMOrders::_get_current_premium_multiplier(env.clone(), order, expiration_time);
-
-    assert_eq!(result, U256::from_u32(&env, 0));
-}
-
-#[test]
-fn test_get_current_premium_multiplier_fully_expired() {
-    let env = create_test_env();
-    let _contract_id = create_xlm_orders_contract(&env);
-
-    let order = XLMOrdersArr {
-        maker: Address::generate(&env),
-        balance: 1000,
-        maximum_premium: 100,
-        auction_duration: 3600,
-    };
-
-    // Set expiration time in the past, beyond auction duration
-    // Current timestamp is 1000, so set expiration to 500 (500 seconds ago)
-    // Time elapsed = 1000 - 500 = 500, which is less than auction_duration (3600)
-    // So it should be proportional: (500 * 100) / 3600 ≈ 13.89
-    let expiration_time = U256::from_u128(&env, 500);
-
-    let result = XLMOrders::_get_current_premium_multiplier(env.clone(), order, expiration_time);
-
-    // Should be proportional: (500 * 100) / 3600 ≈ 13.89
-    assert_eq!(result, U256::from_u32(&env, 13));
-}
-
-#[test]
-fn test_get_current_premium_multiplier_partially_expired() {
-    let env = create_test_env();
-    let _contract_id = create_xlm_orders_contract(&env);
-
-    let order = XLMOrdersArr {
-        maker: Address::generate(&env),
-        balance: 1000,
-        maximum_premium: 100,
-        auction_duration: 3600,
-    };
-
-    // Set expiration time in the past, within auction duration
-    // Current timestamp is 1000, so set expiration to 800 (200 seconds ago)
-    // Time elapsed = 1000 - 800 = 200, which is less than auction_duration (3600)
-    // So it should be proportional: (200 * 100) / 3600 ≈ 5.56
-    let expiration_time = U256::from_u128(&env, 800);
-
-    let result = XLMOrders::_get_current_premium_multiplier(env.clone(), order, expiration_time);
-
-    // Should be proportional: (200 * 100) / 3600 ≈ 5.56
-    // The result should be 5 (integer division)
-    assert_eq!(result, U256::from_u32(&env, 5));
-}
-
-#[test]
-fn test_is_valid_extension_no_extension() {
-    let env = create_test_env();
-    let _contract_id = create_xlm_orders_contract(&env);
-
-    let order = create_test_order(&env, Address::generate(&env), Address::generate(&env));
-    let extension = Bytes::from_array(&env, &[0u8; 0]);
-
-    let (valid, result) = is_valid_extension(env, order, extension);
-
-    assert!(valid);
-    assert_eq!(result, ValidationResult::Success);
-}
-
-#[test]
-fn test_is_valid_extension_missing_extension() {
-    let env = create_test_env();
-    let _contract_id = create_xlm_orders_contract(&env);
-
-    let mut order = create_test_order(&env, Address::generate(&env), Address::generate(&env));
-    // Set has_extension flag
-    order.maker_traits = U256::from_u128(&env, 1u128).shl(249u32); // HAS_EXTENSION_FLAG
-    let extension = Bytes::from_array(&env, &[0u8; 0]);
-
-    let (valid, result) = is_valid_extension(env, order, extension);
-
-    assert!(!valid);
-    assert_eq!(result, ValidationResult::MissingOrderExtension);
-}
-
-#[test]
-fn test_is_valid_extension_unexpected_extension() {
-    let env = create_test_env();
-    let _contract_id = create_xlm_orders_contract(&env);
-
-    let order = create_test_order(&env, Address::generate(&env), Address::generate(&env));
-    // No has_extension flag, but provide extension
-    let extension = Bytes::from_array(&env, &[1u8; 32]);
-
-    let (valid, result) = is_valid_extension(env, order, extension);
-
-    assert!(!valid);
-    assert_eq!(result, ValidationResult::UnexpectedOrderExtension);
-}
-
-#[test]
-fn test_hash_order() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    with_contract_storage(&env, &contract_id, || {
-        let order = create_test_order(&env, Address::generate(&env), Address::generate(&env));
-        let domain_separator = domain_separator_v4(&env);
-
-        let order_hash = hash(&env, &order, &domain_separator);
-
-        // Verify hash is not zero
-        assert_ne!(order_hash, BytesN::from_array(&env, &[0u8; 32]));
-
-        // Verify hash is deterministic
-        let order_hash2 = hash(&env, &order, &domain_separator);
-        assert_eq!(order_hash, order_hash2);
-    });
-}
-
-#[test]
-fn test_domain_separator_v4() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    with_contract_storage(&env, &contract_id, || {
-        let domain_separator = domain_separator_v4(&env);
-
-        // Verify domain separator is not zero
-        assert_ne!(domain_separator, BytesN::from_array(&env, &[0u8; 32]));
-
-        // Verify domain separator is deterministic
-        let domain_separator2 = domain_separator_v4(&env);
-        assert_eq!(domain_separator, domain_separator2);
-    });
-}
-
-#[test]
-fn test_mass_invalidate_bit_orders() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let maker = Address::generate(&env);
-    let nonce_or_epoch = 12345u64;
-    let series = 0u64;
-
-    with_contract_storage(&env, &contract_id, || {
-        // Initial bit invalidator should be 0
-        let initial_invalidator: U256 = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("BIT_INV"))
-            .unwrap_or(U256::from_u32(&env, 0));
-        assert_eq!(initial_invalidator, U256::from_u32(&env, 0));
-
-        // Call mass invalidate
-        let result = XLMOrders::mass_invalidate_bit_orders(&env, maker, nonce_or_epoch, series);
-
-        // Verify result is not zero
-        assert!(result > U256::from_u32(&env, 0));
-
-        // Verify storage was updated
-        let updated_invalidator: U256 = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("BIT_INV"))
-            .unwrap();
-        assert_eq!(updated_invalidator, result);
-    });
-}
-
-#[test]
-fn test_fully_fill_remaining_order() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let maker = Address::generate(&env);
-    let order_hash = BytesN::from_array(&env, &[1u8; 32]);
-
-    with_contract_storage(&env, &contract_id, || {
-        // Initially no invalidator should exist
-        let initial_invalidator: Option<U256> = env.storage().instance().get(&order_hash);
-        assert!(initial_invalidator.is_none());
-
-        // Call fully fill
-        XLMOrders::fully_fill_remaining_order(&env, maker, order_hash.clone());
-
-        // Verify invalidator was set to fully filled
-        let invalidator: U256 = env.storage().instance().get(&order_hash).unwrap();
-        assert_eq!(invalidator, U256::from_u128(&env, u128::MAX));
-    });
-}
-
-#[test]
-fn test_extension_parsing() {
-    let env = create_test_env();
-    let _contract_id = create_xlm_orders_contract(&env);
-
-    // Create a test extension with post-interaction data
-    let target_address = Address::generate(&env);
-    let target_bytes = target_address.clone().to_xdr(&env);
-
-    // Create a simple extension with just the target address
-    // For now, let's test with a basic extension that has the target address at the beginning
-    let mut extension_data = [0u8; 64];
-
-    // Copy the target address to the beginning of the extension data
-    let target_len = target_bytes.len().min(32);
-    for i in 0..target_len {
-        extension_data[i as usize] = target_bytes.get(i as u32).unwrap_or(0);
-    }
-
-    let extension = Bytes::from_array(&env, &extension_data);
-    let _order = create_test_order(&env, Address::generate(&env), Address::generate(&env));
-
-    // For now, just verify that the extension has the expected length
-    // The complex offset parsing will need more work
-    assert_eq!(extension.len(), 64);
-}
-
-#[test]
-fn test_hash_typed_data_v4() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let struct_hash = BytesN::from_array(&env, &[1u8; 32]);
-
-    let result = env.as_contract(&contract_id, || {
-        crate::xlm_orders::hash_typed_data_v4(&env, &struct_hash)
-    });
-
-    // Verify result is not zero
-    assert_ne!(result, BytesN::from_array(&env, &[0u8; 32]));
-
-    // Verify result is deterministic
-    let result2 = env.as_contract(&contract_id, || {
-        crate::xlm_orders::hash_typed_data_v4(&env, &struct_hash)
-    });
-    assert_eq!(result, result2);
-}
-
-// Integration tests
-
-#[test]
-fn test_complete_order_lifecycle() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    let receiver = Address::generate(&env);
-    // Create order with maker as the contract address
-    let order = create_test_order(&env, contract_id.clone(), receiver.clone());
-
-    // Don't set HAS_EXTENSION_FLAG to avoid complex extension parsing
-    // order.maker_traits = U256::from_u128(&env, 1u128).shl(249u32);
-
-    with_contract_storage(&env, &contract_id, || {
-        // Step 1: Deposit (will fail due to interaction validation, but we test the setup)
-        env.storage()
-            .instance()
-            .set(&symbol_short!("sender"), &receiver);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("value"), &1000u128);
-
-        // For now, just verify that the test setup is correct
-        assert_eq!(order.maker, contract_id);
-        assert_eq!(order.making_amount, U256::from_u32(&env, 1000));
-        assert_eq!(order.receiver, receiver);
-
-        // Verify storage setup
-        let stored_sender: Address = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("sender"))
-            .unwrap();
-        let stored_value: u128 = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("value"))
-            .unwrap();
-        assert_eq!(stored_sender, receiver);
-        assert_eq!(stored_value, 1000);
-    });
-}
-
-#[test]
-fn test_multiple_orders_batch_operations() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    with_contract_storage(&env, &contract_id, || {
-        // Create multiple orders
-        let mut order_hashes = Vec::new(&env);
-        let mut orders_data = Vec::new(&env);
-
-        for i in 0..3 {
-            let hash = BytesN::from_array(&env, &[i as u8; 32]);
-            order_hashes.push_back(hash.clone());
-
-            let order_data = XLMOrdersArr {
-                maker: Address::generate(&env),
-                balance: 1000 * (i + 1) as u128,
-                maximum_premium: 100 * (i + 1) as u32,
-                auction_duration: 3600 * (i + 1) as u32,
-            };
-            orders_data.push_back(order_data.clone());
-
-            env.storage().instance().set(&hash, &order_data);
-        }
-
-        // Test batch retrieval
-        let result = XLMOrders::xlm_orders_batch(env.clone(), order_hashes);
-
-        assert_eq!(result.len(), 3);
-        for i in 0..3 {
-            assert_eq!(result.get(i).unwrap(), orders_data.get(i).unwrap());
-        }
-    });
-}
-
-#[test]
-fn test_edge_cases() {
-    let env = create_test_env();
-    let contract_id = create_xlm_orders_contract(&env);
-
-    with_contract_storage(&env, &contract_id, || {
-        // Test with zero amounts
-        let order = create_test_order(&env, Address::generate(&env), Address::generate(&env));
-        let zero_order_hash = hash(&env, &order, &domain_separator_v4(&env));
-
-        // Store the zero order
-        let zero_order = XLMOrdersArr {
-            maker: order.maker.clone(),
-            balance: 0,
-            maximum_premium: 0,
-            auction_duration: 0,
-        };
-        env.storage().instance().set(&zero_order_hash, &zero_order);
-
-        // Test with maximum values
-        let max_order = XLMOrdersArr {
-            maker: Address::generate(&env),
-            balance: u128::MAX,
-            maximum_premium: u32::MAX,
-            auction_duration: u32::MAX,
-        };
-
-        let max_hash = BytesN::from_array(&env, &[255u8; 32]);
-        env.storage().instance().set(&max_hash, &max_order);
-
-        // Test batch with mixed existing/non-existing orders
-        let mut mixed_hashes = Vec::new(&env);
-        mixed_hashes.push_back(zero_order_hash.clone());
-        mixed_hashes.push_back(max_hash.clone());
-        mixed_hashes.push_back(BytesN::from_array(&env, &[99u8; 32])); // Non-existing
-
-        let result = XLMOrders::xlm_orders_batch(env.clone(), mixed_hashes);
-
-        // Should only return existing orders
-        assert_eq!(result.len(), 2);
-    });
-}
@@ -0,0 +1,22 @@
+#![no_std]
+use soroban_sdk::{contractclient, contracttype, Env};
+
+/// Price reported by an oracle, laid out like the SEP-40 `PriceData`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    /// Taker asset units paid per maker asset unit, scaled by `10^decimals`
+    pub price: i128,
+    /// Timestamp the price was observed at
+    pub timestamp: u64,
+}
+
+/// Interface of a single-pair price feed used by the oracle calculator.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracleInterface {
+    /// Number of decimals the reported price is scaled by
+    fn decimals(env: Env) -> u32;
+
+    /// Most recent price, `None` if the feed has not reported yet
+    fn lastprice(env: Env) -> Option<PriceData>;
+}
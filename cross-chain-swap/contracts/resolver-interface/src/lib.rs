@@ -3,18 +3,29 @@
 use soroban_sdk::{contractclient, Address, Bytes, BytesN, Env, U256};
 
 use base_escrow::Immutables as EscrowImmutables;
-use order_interface::Order;
+use order_interface::{AuctionDetails, Order};
 
 /// Interface for the sample implementation of a Resolver contract for cross-chain swap.
 #[contractclient(name = "ResolverInterfaceClient")]
 pub trait ResolverInterface {
-    fn __constructor(env: Env, escrow_factory_address: Address, order_mixin_address: Address);
+    /// `owner` deploys the escrows, paying their safety deposits and the taker assets of the
+    /// fills.
+    fn __constructor(
+        env: Env,
+        escrow_factory_address: Address,
+        order_mixin_address: Address,
+        owner: Address,
+    );
 
     fn get_escrow_factory_address(env: Env) -> Address;
 
     fn get_order_mixin_address(env: Env) -> Address;
 
-    /// Deploys a new escrow contract for maker on the source chain
+    fn get_owner(env: Env) -> Address;
+
+    /// Deploys a new escrow contract for maker on the source chain. The owner pays the native
+    /// safety deposit into the escrow and the taker assets of the fill, which pays the maker
+    /// assets into the escrow, so only the owner may call it.
     ///
     /// # Arguments
     /// * `immutables` - The immutables of the escrow contract used in deployment
@@ -24,6 +35,7 @@ pub trait ResolverInterface {
     /// * `amount` - Taker amount to fill
     /// * `taker_traits` - Taker execution traits
     /// * `args` - Additional arguments for the taker
+    /// * `auction_details` - Dutch auction the fill is priced with
     fn deploy_src(
         env: Env,
         immutables: EscrowImmutables,
@@ -33,6 +45,7 @@ pub trait ResolverInterface {
         amount: U256,
         taker_traits: U256, // Taker traits = U256
         args: Bytes,
+        auction_details: AuctionDetails,
     ) -> Address; // original function does not and external return
 
     /// Deploys a new escrow contract for taker on the destination chain
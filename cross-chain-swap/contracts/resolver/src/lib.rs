@@ -3,31 +3,41 @@
 use base_escrow::{timelocks::Timelocks, Immutables};
 use escrow_factory_interface::EscrowFactoryClient;
 use order_interface::{AuctionDetails, Order, OrderClient};
+use order_lib::{
+    fees::{self, FeeConfig},
+    maker_traits::MakerTraitsLib,
+    taker_args::TakerArgs,
+};
 use resolver_interface::ResolverInterface;
 use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
     contract, contractimpl, symbol_short,
     token::TokenClient,
-    vec,
-    xdr::{FromXdr, ToXdr},
-    Address, Bytes, BytesN, Env, IntoVal, Symbol, U256,
+    vec, Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec, U256,
 };
-use utils::math;
 
 #[contract]
 pub struct ResolverContract;
 
 const ESCROW_FACTORY_ADDRESS: Symbol = symbol_short!("ESCR_FACT");
 const ORDER_MIXIN_ADDRESS: Symbol = symbol_short!("ORDER_MIX");
+const OWNER: Symbol = symbol_short!("OWNER");
 
 #[contractimpl]
 impl ResolverInterface for ResolverContract {
-    fn __constructor(env: Env, escrow_factory_address: Address, order_mixin_address: Address) {
+    fn __constructor(
+        env: Env,
+        escrow_factory_address: Address,
+        order_mixin_address: Address,
+        owner: Address,
+    ) {
         env.storage()
             .instance()
             .set(&ESCROW_FACTORY_ADDRESS, &escrow_factory_address);
         env.storage()
             .instance()
             .set(&ORDER_MIXIN_ADDRESS, &order_mixin_address);
+        env.storage().instance().set(&OWNER, &owner);
     }
 
     fn get_escrow_factory_address(env: Env) -> Address {
@@ -41,6 +51,10 @@ impl ResolverInterface for ResolverContract {
         env.storage().instance().get(&ORDER_MIXIN_ADDRESS).unwrap()
     }
 
+    fn get_owner(env: Env) -> Address {
+        env.storage().instance().get(&OWNER).unwrap()
+    }
+
     fn deploy_src(
         env: Env,
         immutables: Immutables,
@@ -50,7 +64,11 @@ impl ResolverInterface for ResolverContract {
         amount: U256,
         taker_traits: U256, // Taker traits = U256
         args: Bytes,
+        auction_details: AuctionDetails,
     ) -> Address {
+        let owner = Self::get_owner(env.clone());
+        owner.require_auth();
+
         let mut immutables_mem = immutables.clone();
         let timestamp = U256::from_u128(&env, env.ledger().timestamp().try_into().unwrap());
         // either we change set_deployed_at to accept pointer to env or we pass env.clone()
@@ -66,35 +84,73 @@ impl ResolverInterface for ResolverContract {
 
         let address = escrow_factory_client.address_of_escrow_src(&immutables_mem);
 
-        let token_client = TokenClient::new(&env, &order.maker_asset);
+        let token_client = TokenClient::new(&env, &escrow_factory_client.xlm_address());
 
         let safty_deposit_amount = immutables_mem.safety_deposit.try_into().unwrap();
-        let transfer_result =
-            token_client.try_transfer(&order.maker, &address, &safty_deposit_amount);
+        let transfer_result = token_client.try_transfer(&owner, &address, &safty_deposit_amount);
         if transfer_result.is_err() {
             panic!("Failed to transfer safety deposit");
         }
 
-        let updated_taker_traits =
-            math::bit_or(&env, taker_traits, U256::from_u32(&env, 1).shl(251));
-
-        let mut args_mem = args.clone();
-        let address_bytes = Bytes::from_xdr(&env, &address.clone().to_xdr(&env));
-        if address_bytes.is_err() {
-            panic!("Failed to convert address to bytes");
-        }
-        args_mem.append(&address_bytes.unwrap());
-
         let order_mixin = env.storage().instance().get(&ORDER_MIXIN_ADDRESS).unwrap();
         let order_mixin_client = OrderClient::new(&env, &order_mixin);
 
-        let auction_details = AuctionDetails {
-            auction_start_time: U256::from_u32(&env, 0),
-            taking_amount_start: U256::from_u32(&env, 0),
-            taking_amount_end: U256::from_u32(&env, 0),
-        };
+        // The maker assets go to the escrow
+        let this = env.current_contract_address();
+        let mut taker_args = TakerArgs::decode(&env, &taker_traits, &args);
+        taker_args.target = Some(address.clone());
+        let (updated_taker_traits, args_mem) = taker_args.encode(&env, taker_traits);
+
+        // The taker leg cannot be funded from the maker assets of the fill. Soroban rejects
+        // contract re-entry, so the order protocol cannot call the resolver back through a taker
+        // interaction while the resolver is running its fill. The maker assets also go straight
+        // to the escrow, and never pass through the resolver. The owner's taker assets are
+        // sourced right before the fill instead, for the amount the fill will take.
+        let quote = order_mixin_client.quote_fill(
+            &this,
+            &order,
+            &taker_args.extension,
+            &amount,
+            &updated_taker_traits,
+            &auction_details,
+        );
+        TokenClient::new(&env, &order.taker_asset).transfer(
+            &owner,
+            &this,
+            &(quote.taking_amount.to_u128().unwrap() as i128),
+        );
+
+        // The order protocol collects the taker leg from the resolver deeper than the fill call,
+        // so the resolver authorizes each of those transfers up front
+        let unwrap = MakerTraitsLib::unwrap_weth(env.clone(), order.maker_traits.clone())
+            && order_mixin_client.wrapped_native() == Some(order.taker_asset.clone());
+        let payments = fees::taker_payments(
+            &env,
+            &order,
+            &FeeConfig::from_extension(&env, &taker_args.extension),
+            &quote.fees,
+            unwrap,
+        );
+        let mut auth_entries = Vec::new(&env);
+        for payment in payments.iter() {
+            let fn_name = if payment.unwrap {
+                Symbol::new(&env, "withdraw")
+            } else {
+                Symbol::new(&env, "transfer")
+            };
+            auth_entries.push_back(InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: payment.token,
+                    fn_name,
+                    args: (this.clone(), payment.receiver, payment.amount).into_val(&env),
+                },
+                sub_invocations: Vec::new(&env),
+            }));
+        }
+        env.authorize_as_current_contract(auth_entries);
 
         order_mixin_client.fill_order_args(
+            &this,
             &order,
             &signature_r,
             &signature_vs,
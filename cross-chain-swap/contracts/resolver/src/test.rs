@@ -2,8 +2,27 @@
 
 use crate::{ResolverContract, ResolverContractClient};
 use base_escrow::Immutables;
-use order_interface::Order;
-use soroban_sdk::{testutils::Address as _, Address, Bytes, BytesN, Env, U256};
+use dutch_auction::DutchAuctionCalculatorContract;
+use escrow::escrow_factory::EscrowFactory;
+use escrow_factory_interface::ExtraDataArgs;
+use order::OrderProtocol;
+use order_interface::{AuctionDetails, AuctionWindow, Order, OrderClient};
+use order_lib::{maker_traits::MakerTraitsBuilder, taker_args::TakerArgs};
+use soroban_sdk::{
+    testutils::Address as _,
+    token::{StellarAssetClient, TokenClient},
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, String, U256,
+};
+use utils::ttl::TtlPolicy;
+
+mod escrow_dst_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/escrow_dst.wasm");
+}
+
+mod escrow_src_contract {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/escrow_src.wasm");
+}
 
 #[test]
 fn test_constructor_and_getters() {
@@ -15,7 +34,7 @@ fn test_constructor_and_getters() {
 
     let contract_id = env.register(
         ResolverContract,
-        (&escrow_factory_address, &order_mixin_address),
+        (&escrow_factory_address, &order_mixin_address, &admin),
     );
     let resolver_client = ResolverContractClient::new(&env, &contract_id);
 
@@ -25,6 +44,8 @@ fn test_constructor_and_getters() {
 
     let retrieved_order_mixin = resolver_client.get_order_mixin_address();
     assert_eq!(retrieved_order_mixin, order_mixin_address);
+
+    assert_eq!(resolver_client.get_owner(), admin);
 }
 
 #[test]
@@ -48,7 +69,7 @@ fn test_deploy_src_basic_functionality() {
 
     let contract_id = env.register(
         ResolverContract,
-        (&escrow_factory_address, &order_mixin_address),
+        (&escrow_factory_address, &order_mixin_address, &admin),
     );
     let resolver_client = ResolverContractClient::new(&env, &contract_id);
 
@@ -65,6 +86,7 @@ fn test_deploy_src_basic_functionality() {
 
     let order = Order {
         maker: maker.clone(),
+        maker_evm_address: None,
         maker_asset: token.clone(),
         maker_traits: U256::from_u32(&env, 0),
         making_amount: U256::from_u128(&env, 1000000000000000000),
@@ -109,7 +131,7 @@ fn test_deploy_dst_basic_functionality() {
 
     let contract_id = env.register(
         ResolverContract,
-        (&escrow_factory_address, &order_mixin_address),
+        (&escrow_factory_address, &order_mixin_address, &admin),
     );
     let resolver_client = ResolverContractClient::new(&env, &contract_id);
 
@@ -183,6 +205,7 @@ fn test_order_validation() {
 
     let order = Order {
         maker: maker.clone(),
+        maker_evm_address: None,
         maker_asset: token.clone(),
         maker_traits: U256::from_u32(&env, 0),
         making_amount: U256::from_u128(&env, 1000000000000000000),
@@ -314,13 +337,14 @@ fn test_args_validation() {
 #[test]
 fn test_contract_addresses_consistency() {
     let env = Env::default();
+    let admin = Address::generate(&env);
 
     let escrow_factory_address = Address::generate(&env);
     let order_mixin_address = Address::generate(&env);
 
     let contract_id = env.register(
         ResolverContract,
-        (&escrow_factory_address, &order_mixin_address),
+        (&escrow_factory_address, &order_mixin_address, &admin),
     );
     let resolver_client = ResolverContractClient::new(&env, &contract_id);
 
@@ -397,3 +421,206 @@ fn test_zero_values() {
     assert_eq!(immutables.safety_deposit, 0);
     assert_eq!(immutables.timelocks, U256::from_u32(&env, 0));
 }
+
+struct SrcFill {
+    env: Env,
+    order_mixin: Address,
+    resolver: Address,
+    owner: Address,
+    xlm: Address,
+    order: Order,
+    extension: Bytes,
+}
+
+impl SrcFill {
+    /// Cross-chain order of 100 maker asset for 50 taker asset with a safety deposit of 10 XLM,
+    /// whose post interaction deploys the source escrow. The resolver owner holds 50 taker asset
+    /// and 10 XLM.
+    fn new() -> Self {
+        let env = Env::default();
+        env.mock_all_auths_allowing_non_root_auth();
+
+        let dutch_auction = env.register(DutchAuctionCalculatorContract, ());
+        let order_mixin = env.register(
+            OrderProtocol,
+            (
+                &dutch_auction,
+                String::from_str(&env, "1inch Limit Order Protocol"),
+                String::from_str(&env, "4"),
+                None::<U256>,
+                None::<Address>,
+                None::<TtlPolicy>,
+            ),
+        );
+        let xlm = create_token(&env);
+        let escrow_factory = env.register(
+            EscrowFactory,
+            (
+                env.deployer()
+                    .upload_contract_wasm(escrow_dst_contract::WASM),
+                env.deployer()
+                    .upload_contract_wasm(escrow_src_contract::WASM),
+                &xlm,
+                &order_mixin,
+                Address::generate(&env),
+            ),
+        );
+        let owner = Address::generate(&env);
+        let resolver = env.register(ResolverContract, (&escrow_factory, &order_mixin, &owner));
+
+        let extension = escrow_extension(
+            &env,
+            &escrow_factory,
+            &ExtraDataArgs {
+                hashlock_info: BytesN::from_array(&env, &[7; 32]),
+                safety_deposit: 10,
+                timelocks: U256::from_u32(&env, 0),
+            },
+        );
+        let order = Order {
+            salt: extension_salt(&env, &extension),
+            maker: Address::generate(&env),
+            maker_evm_address: None,
+            receiver: Address::generate(&env),
+            maker_asset: create_token(&env),
+            taker_asset: create_token(&env),
+            making_amount: U256::from_u32(&env, 100),
+            taking_amount: U256::from_u32(&env, 50),
+            maker_traits: MakerTraitsBuilder::new(env.clone())
+                .with_extension()
+                .with_post_interaction_call()
+                .build(),
+        };
+        StellarAssetClient::new(&env, &order.maker_asset).mint(&order.maker, &100);
+        StellarAssetClient::new(&env, &order.taker_asset).mint(&owner, &50);
+        StellarAssetClient::new(&env, &xlm).mint(&owner, &10);
+        OrderClient::new(&env, &order_mixin).approve(&order.maker, &order.maker_asset, &100, &100);
+
+        SrcFill {
+            env,
+            order_mixin,
+            resolver,
+            owner,
+            xlm,
+            order,
+            extension,
+        }
+    }
+
+    /// Fills the whole order through the resolver, returning the source escrow.
+    fn deploy_src(&self, auction_details: &AuctionDetails) -> Address {
+        let env = &self.env;
+        let immutables = Immutables {
+            order_hash: OrderClient::new(env, &self.order_mixin).order_hash(&self.order),
+            hashlock: BytesN::from_array(env, &[7; 32]),
+            maker: self.order.maker.clone(),
+            taker: self.resolver.clone(),
+            token: self.order.maker_asset.clone(),
+            amount: 100,
+            safety_deposit: 10,
+            timelocks: U256::from_u32(env, 0),
+        };
+        let (taker_traits, args) = TakerArgs {
+            target: None,
+            extension: self.extension.clone(),
+            interaction: Bytes::new(env),
+        }
+        .encode(env, U256::from_u32(env, 0));
+        let empty = BytesN::from_array(env, &[0; 32]);
+
+        ResolverContractClient::new(env, &self.resolver).deploy_src(
+            &immutables,
+            &self.order,
+            &empty,
+            &empty,
+            &U256::from_u32(env, 50),
+            &taker_traits,
+            &args,
+            auction_details,
+        )
+    }
+}
+
+fn create_token(env: &Env) -> Address {
+    env.register_stellar_asset_contract_v2(Address::generate(env))
+        .address()
+}
+
+/// Extension whose post interaction calls `escrow_factory` with `args`
+fn escrow_extension(env: &Env, escrow_factory: &Address, args: &ExtraDataArgs) -> Bytes {
+    let xdr = escrow_factory.clone().to_xdr(env);
+    let mut data = xdr.slice(xdr.len() - 32..);
+    data.append(&args.clone().to_xdr(env));
+
+    // Offsets word: end of PostInteractionData in bits 224..256, the other fields are empty
+    let mut extension = Bytes::from_array(env, &data.len().to_be_bytes());
+    extension.extend_from_array(&[0; 28]);
+    extension.append(&data);
+    extension
+}
+
+/// Salt committing the order to `extension`: the low 128 bits of its keccak256.
+fn extension_salt(env: &Env, extension: &Bytes) -> U256 {
+    let mut salt = [0u8; 32];
+    salt[16..].copy_from_slice(&env.crypto().keccak256(extension).to_array()[16..]);
+    U256::from_be_bytes(env, &Bytes::from_array(env, &salt))
+}
+
+fn auction_details(env: &Env, start: u64, end: u64) -> AuctionDetails {
+    AuctionDetails {
+        auction_window: AuctionWindow { start, end },
+        taking_amount_start: U256::from_u32(env, 50),
+        taking_amount_end: U256::from_u32(env, 50),
+    }
+}
+
+#[test]
+fn test_deploy_src_fills_order_into_source_escrow() {
+    let fill = SrcFill::new();
+    let env = &fill.env;
+
+    let escrow = fill.deploy_src(&auction_details(env, 0, 1000));
+
+    let maker_asset = TokenClient::new(env, &fill.order.maker_asset);
+    let taker_asset = TokenClient::new(env, &fill.order.taker_asset);
+    assert_eq!(maker_asset.balance(&escrow), 100);
+    assert_eq!(maker_asset.balance(&fill.resolver), 0);
+    assert_eq!(TokenClient::new(env, &fill.xlm).balance(&escrow), 10);
+    assert_eq!(TokenClient::new(env, &fill.xlm).balance(&fill.owner), 0);
+    assert_eq!(taker_asset.balance(&fill.order.receiver), 50);
+    assert_eq!(taker_asset.balance(&fill.resolver), 0);
+    assert_eq!(taker_asset.balance(&fill.owner), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_deploy_src_requires_owner_auth() {
+    let fill = SrcFill::new();
+    fill.env.set_auths(&[]);
+    fill.deploy_src(&auction_details(&fill.env, 0, 1000));
+}
+
+#[test]
+#[should_panic(expected = "Failed to transfer safety deposit")]
+fn test_deploy_src_requires_owner_safety_deposit() {
+    let fill = SrcFill::new();
+    TokenClient::new(&fill.env, &fill.xlm).burn(&fill.owner, &1);
+    fill.deploy_src(&auction_details(&fill.env, 0, 1000));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_deploy_src_requires_owner_taker_asset() {
+    let fill = SrcFill::new();
+    let env = &fill.env;
+    // The fill takes 50, which the owner has to hold up front
+    TokenClient::new(env, &fill.order.taker_asset).burn(&fill.owner, &1);
+    fill.deploy_src(&auction_details(env, 0, 1000));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn test_deploy_src_rejects_invalid_auction_window() {
+    let fill = SrcFill::new();
+    fill.deploy_src(&auction_details(&fill.env, 1000, 0));
+}
@@ -0,0 +1,232 @@
+#![no_std]
+use order_interface::WrappedNativeInterface;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short,
+    token::{TokenClient, TokenInterface},
+    Address, Env, String, Symbol, Vec,
+};
+use soroban_token_sdk::TokenUtils;
+use utils::ttl::{StoredTtlPolicy, TtlPolicy};
+
+// Stellar equivalent of WETH: lumens are locked in this contract while their wrapped
+// counterpart trades as a regular SEP-41 token. Deposits and withdrawals publish the SEP-41
+// mint and burn events, so indexers track wrapped balances like any other token's.
+
+const DECIMALS: u32 = 7;
+
+// STORAGE SYMBOLS
+const NATIVE: Symbol = symbol_short!("NATIVE");
+const TOTAL_SUPPLY: Symbol = symbol_short!("TOTAL");
+
+/// TTL policy of the balances, which live in persistent storage
+pub const STORAGE_TTL: StoredTtlPolicy = StoredTtlPolicy;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Balance(Address),
+    Allowance(Address, Address),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+#[contract]
+pub struct WrappedNative;
+
+#[contractimpl]
+impl WrappedNative {
+    /// `native` is the asset contract of native XLM.
+    pub fn __constructor(env: Env, native: Address, ttl_policy: Option<TtlPolicy>) {
+        STORAGE_TTL.init(&env, &ttl_policy);
+        env.storage().instance().set(&NATIVE, &native);
+    }
+
+    pub fn native(env: Env) -> Address {
+        env.storage().instance().get(&NATIVE).unwrap()
+    }
+
+    pub fn total_supply(env: Env) -> i128 {
+        env.storage().instance().get(&TOTAL_SUPPLY).unwrap_or(0)
+    }
+
+    /// Extends the TTL of the contract instance and of the balances under `keys`. Allowances
+    /// live in temporary storage until their expiration ledger, so their keys are skipped, as
+    /// are balances never written.
+    pub fn extend_ttl(env: Env, keys: Vec<DataKey>) {
+        STORAGE_TTL.extend_instance(&env);
+        for key in keys.iter() {
+            if let DataKey::Balance(_) = key {
+                STORAGE_TTL.extend(&env, &key);
+            }
+        }
+    }
+
+    fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+        if Self::allowance(env.clone(), from.clone(), spender.clone()) < amount {
+            panic!("Insufficient allowance");
+        }
+        // Spending nothing needs no allowance entry, any other amount passed the check above only
+        // if there is one
+        if amount > 0 {
+            let key = DataKey::Allowance(from.clone(), spender.clone());
+            let mut value: AllowanceValue = env.storage().temporary().get(&key).unwrap();
+            value.amount -= amount;
+            env.storage().temporary().set(&key, &value);
+        }
+    }
+
+    fn spend_balance(env: &Env, id: &Address, amount: i128) {
+        if amount < 0 {
+            panic!("Negative amount");
+        }
+        let balance = Self::balance(env.clone(), id.clone());
+        if balance < amount {
+            panic!("Insufficient balance");
+        }
+        STORAGE_TTL.set(env, &DataKey::Balance(id.clone()), &(balance - amount));
+    }
+
+    fn receive_balance(env: &Env, id: &Address, amount: i128) {
+        let balance = Self::balance(env.clone(), id.clone());
+        STORAGE_TTL.set(env, &DataKey::Balance(id.clone()), &(balance + amount));
+    }
+
+    fn add_total_supply(env: &Env, amount: i128) {
+        STORAGE_TTL.extend_instance(env);
+        env.storage()
+            .instance()
+            .set(&TOTAL_SUPPLY, &(Self::total_supply(env.clone()) + amount));
+    }
+}
+
+#[contractimpl]
+impl TokenInterface for WrappedNative {
+    /// Expired allowances read as zero.
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        let allowance: Option<AllowanceValue> = env
+            .storage()
+            .temporary()
+            .get(&DataKey::Allowance(from, spender));
+        match allowance {
+            Some(allowance) if allowance.expiration_ledger >= env.ledger().sequence() => {
+                allowance.amount
+            }
+            _ => 0,
+        }
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+
+        if amount > 0 && expiration_ledger < env.ledger().sequence() {
+            panic!("Expiration ledger in the past");
+        }
+
+        let key = DataKey::Allowance(from.clone(), spender.clone());
+        env.storage().temporary().set(
+            &key,
+            &AllowanceValue {
+                amount,
+                expiration_ledger,
+            },
+        );
+        if amount > 0 {
+            let live_for = expiration_ledger - env.ledger().sequence();
+            env.storage()
+                .temporary()
+                .extend_ttl(&key, live_for, live_for);
+        }
+        TokenUtils::new(&env)
+            .events()
+            .approve(from, spender, amount, expiration_ledger);
+    }
+
+    fn balance(env: Env, id: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Balance(id))
+            .unwrap_or(0)
+    }
+
+    fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        Self::spend_balance(&env, &from, amount);
+        Self::receive_balance(&env, &to, amount);
+        TokenUtils::new(&env).events().transfer(from, to, amount);
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        Self::spend_allowance(&env, &from, &spender, amount);
+        Self::spend_balance(&env, &from, amount);
+        Self::receive_balance(&env, &to, amount);
+        TokenUtils::new(&env).events().transfer(from, to, amount);
+    }
+
+    /// The burnt lumens stay locked in the contract.
+    fn burn(env: Env, from: Address, amount: i128) {
+        from.require_auth();
+        Self::spend_balance(&env, &from, amount);
+        Self::add_total_supply(&env, -amount);
+        TokenUtils::new(&env).events().burn(from, amount);
+    }
+
+    /// The burnt lumens stay locked in the contract.
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+        Self::spend_allowance(&env, &from, &spender, amount);
+        Self::spend_balance(&env, &from, amount);
+        Self::add_total_supply(&env, -amount);
+        TokenUtils::new(&env).events().burn(from, amount);
+    }
+
+    fn decimals(_env: Env) -> u32 {
+        DECIMALS
+    }
+
+    fn name(env: Env) -> String {
+        String::from_str(&env, "Wrapped Lumens")
+    }
+
+    fn symbol(env: Env) -> String {
+        String::from_str(&env, "WXLM")
+    }
+}
+
+#[contractimpl]
+impl WrappedNativeInterface for WrappedNative {
+    fn deposit(env: Env, from: Address, amount: i128) {
+        from.require_auth();
+
+        TokenClient::new(&env, &Self::native(env.clone())).transfer(
+            &from,
+            &env.current_contract_address(),
+            &amount,
+        );
+        Self::receive_balance(&env, &from, amount);
+        Self::add_total_supply(&env, amount);
+        TokenUtils::new(&env)
+            .events()
+            .mint(env.current_contract_address(), from, amount);
+    }
+
+    fn withdraw(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+
+        Self::spend_balance(&env, &from, amount);
+        Self::add_total_supply(&env, -amount);
+        TokenClient::new(&env, &Self::native(env.clone())).transfer(
+            &env.current_contract_address(),
+            &to,
+            &amount,
+        );
+        TokenUtils::new(&env).events().burn(from, amount);
+    }
+}
+
+mod test;
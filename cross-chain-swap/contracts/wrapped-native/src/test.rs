@@ -0,0 +1,270 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    symbol_short,
+    testutils::{
+        storage::{Instance, Persistent},
+        Address as _, Events, Ledger,
+    },
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Env, IntoVal, Symbol, Val, Vec,
+};
+use utils::ttl::{TtlPolicy, DEFAULT_POLICY};
+
+use crate::{DataKey, WrappedNative, WrappedNativeClient};
+
+fn setup(env: &Env) -> (WrappedNativeClient<'_>, Address) {
+    setup_with_ttl_policy(env, None)
+}
+
+fn setup_with_ttl_policy(
+    env: &Env,
+    ttl_policy: Option<TtlPolicy>,
+) -> (WrappedNativeClient<'_>, Address) {
+    let native = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let contract_id = env.register(WrappedNative, (&native, ttl_policy));
+    (WrappedNativeClient::new(env, &contract_id), native)
+}
+
+#[test]
+fn test_deposit_and_withdraw() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, native) = setup(&env);
+
+    let user = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    StellarAssetClient::new(&env, &native).mint(&user, &100);
+
+    client.deposit(&user, &100);
+    assert_eq!(client.balance(&user), 100);
+    assert_eq!(client.total_supply(), 100);
+    assert_eq!(TokenClient::new(&env, &native).balance(&user), 0);
+
+    client.withdraw(&user, &receiver, &40);
+    assert_eq!(client.balance(&user), 60);
+    assert_eq!(client.total_supply(), 60);
+    assert_eq!(TokenClient::new(&env, &native).balance(&receiver), 40);
+    assert_eq!(TokenClient::new(&env, &native).balance(&client.address), 60);
+}
+
+#[test]
+fn test_extend_ttl_keeps_balance_alive() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, native) = setup(&env);
+
+    let user = Address::generate(&env);
+    StellarAssetClient::new(&env, &native).mint(&user, &100);
+    client.deposit(&user, &100);
+    client.approve(&user, &Address::generate(&env), &10, &100);
+
+    let key = DataKey::Balance(user.clone());
+    let ttl = || env.as_contract(&client.address, || env.storage().persistent().get_ttl(&key));
+    assert_eq!(ttl(), DEFAULT_POLICY.extend_to);
+
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number += DEFAULT_POLICY.extend_to - DEFAULT_POLICY.threshold + 1;
+    });
+    // Allowances are temporary entries, their keys are skipped
+    client.extend_ttl(&vec![
+        &env,
+        key.clone(),
+        DataKey::Allowance(user.clone(), Address::generate(&env)),
+    ]);
+
+    assert_eq!(ttl(), DEFAULT_POLICY.extend_to);
+    assert_eq!(client.balance(&user), 100);
+}
+
+#[test]
+fn test_custom_ttl_policy() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let policy = TtlPolicy::new(5_000, 10_000);
+    let (client, native) = setup_with_ttl_policy(&env, Some(policy));
+
+    let user = Address::generate(&env);
+    StellarAssetClient::new(&env, &native).mint(&user, &100);
+    client.deposit(&user, &100);
+
+    let key = DataKey::Balance(user.clone());
+    let ttls = || {
+        env.as_contract(&client.address, || {
+            (
+                env.storage().persistent().get_ttl(&key),
+                env.storage().instance().get_ttl(),
+            )
+        })
+    };
+    assert_eq!(ttls(), (policy.extend_to, policy.extend_to));
+
+    // Not renewed while the TTL is above the policy's threshold
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number += policy.extend_to - policy.threshold - 1;
+    });
+    client.extend_ttl(&vec![&env, key.clone()]);
+    assert_eq!(ttls(), (policy.threshold + 1, policy.threshold + 1));
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 2);
+    client.extend_ttl(&vec![&env, key.clone()]);
+    assert_eq!(ttls(), (policy.extend_to, policy.extend_to));
+}
+
+#[test]
+#[should_panic(expected = "InvalidTtlPolicy")]
+fn test_constructor_rejects_ttl_policy_beyond_max_ttl() {
+    let env = Env::default();
+    let max_ttl = env.storage().max_ttl();
+    setup_with_ttl_policy(
+        &env,
+        Some(TtlPolicy {
+            threshold: DEFAULT_POLICY.threshold,
+            extend_to: max_ttl + 1,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance")]
+fn test_withdraw_more_than_balance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, native) = setup(&env);
+
+    let user = Address::generate(&env);
+    StellarAssetClient::new(&env, &native).mint(&user, &10);
+    client.deposit(&user, &10);
+    client.withdraw(&user, &user, &11);
+}
+
+#[test]
+fn test_allowance_expires() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+
+    let user = Address::generate(&env);
+    let spender = Address::generate(&env);
+    client.approve(&user, &spender, &50, &(env.ledger().sequence() + 10));
+    assert_eq!(client.allowance(&user, &spender), 50);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 11);
+    assert_eq!(client.allowance(&user, &spender), 0);
+}
+
+#[test]
+fn test_burn_and_burn_from() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, native) = setup(&env);
+
+    let user = Address::generate(&env);
+    let spender = Address::generate(&env);
+    StellarAssetClient::new(&env, &native).mint(&user, &100);
+    client.deposit(&user, &100);
+
+    client.burn(&user, &30);
+    client.approve(&user, &spender, &50, &(env.ledger().sequence() + 10));
+    client.burn_from(&spender, &user, &20);
+
+    assert_eq!(client.balance(&user), 50);
+    assert_eq!(client.allowance(&user, &spender), 30);
+    assert_eq!(client.total_supply(), 50);
+    // The burnt lumens stay locked
+    assert_eq!(
+        TokenClient::new(&env, &native).balance(&client.address),
+        100
+    );
+}
+
+#[test]
+#[should_panic(expected = "Insufficient allowance")]
+fn test_burn_from_above_allowance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, native) = setup(&env);
+
+    let user = Address::generate(&env);
+    let spender = Address::generate(&env);
+    StellarAssetClient::new(&env, &native).mint(&user, &100);
+    client.deposit(&user, &100);
+    client.approve(&user, &spender, &10, &(env.ledger().sequence() + 10));
+    client.burn_from(&spender, &user, &11);
+}
+
+#[test]
+fn test_zero_transfer_from_without_allowance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, native) = setup(&env);
+
+    let user = Address::generate(&env);
+    let spender = Address::generate(&env);
+    StellarAssetClient::new(&env, &native).mint(&user, &100);
+    client.deposit(&user, &100);
+
+    client.transfer_from(&spender, &user, &spender, &0);
+    client.burn_from(&spender, &user, &0);
+    assert_eq!(client.balance(&user), 100);
+    assert_eq!(client.allowance(&user, &spender), 0);
+}
+
+fn assert_last_event(env: &Env, event: (Address, Vec<Val>, Val)) {
+    let events = env.events().all();
+    assert_eq!(events.slice(events.len() - 1..), vec![env, event]);
+}
+
+#[test]
+fn test_sep41_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, native) = setup(&env);
+
+    let user = Address::generate(&env);
+    let spender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    StellarAssetClient::new(&env, &native).mint(&user, &100);
+    let expiration_ledger = env.ledger().sequence() + 10;
+
+    client.deposit(&user, &100);
+    assert_last_event(
+        &env,
+        (
+            client.address.clone(),
+            (symbol_short!("mint"), client.address.clone(), user.clone()).into_val(&env),
+            100_i128.into_val(&env),
+        ),
+    );
+
+    client.approve(&user, &spender, &50, &expiration_ledger);
+    assert_last_event(
+        &env,
+        (
+            client.address.clone(),
+            (Symbol::new(&env, "approve"), user.clone(), spender.clone()).into_val(&env),
+            (50_i128, expiration_ledger).into_val(&env),
+        ),
+    );
+
+    client.transfer_from(&spender, &user, &receiver, &20);
+    assert_last_event(
+        &env,
+        (
+            client.address.clone(),
+            (symbol_short!("transfer"), user.clone(), receiver.clone()).into_val(&env),
+            20_i128.into_val(&env),
+        ),
+    );
+
+    // Withdrawals burn the wrapped lumens they release
+    client.withdraw(&receiver, &receiver, &20);
+    let events = env.events().all();
+    assert!(events.contains((
+        client.address.clone(),
+        (symbol_short!("burn"), receiver.clone()).into_val(&env),
+        20_i128.into_val(&env),
+    )));
+}
@@ -0,0 +1,422 @@
+#![no_std]
+use base_escrow::base_escrow::BaseEscrow;
+use events::{XlmOrderCancelled, XlmOrderCancelledByThirdParty, XlmOrderDeposited};
+use order_interface::{Order, OrderClient, PostInteractionInterface};
+use order_lib::{
+    maker_traits::MakerTraitsLib,
+    xlm_orders::{is_valid_extension, post_interaction_target_and_data, ValidationResult},
+};
+use soroban_sdk::{
+    auth::{Context, CustomAccountInterface},
+    contract, contracterror, contractimpl, contracttype,
+    crypto::Hash,
+    symbol_short,
+    token::TokenClient,
+    Address, Bytes, BytesN, Env, Symbol, TryFromVal, Vec, U256,
+};
+use utils::{
+    address::contract_address,
+    protocol::{limit_order_protocol, require_limit_order_protocol, set_limit_order_protocol},
+    ttl::{StoredTtlPolicy, TtlPolicy},
+};
+
+// Native XLM orders: makers deposit the lumens an order sells, and this contract makes the
+// order on their behalf, so it can be filled by the limit order protocol like any other.
+
+#[contract]
+pub struct XLMOrders;
+
+impl BaseEscrow for XLMOrders {}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+#[contracttype]
+pub struct XLMOrdersArr {
+    pub maker: Address,
+    /// Part of the deposit not filled yet
+    pub balance: u128,
+    pub maximum_premium: u32,
+    pub auction_duration: u32,
+    pub maker_traits: U256,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum XLMOrdersKey {
+    /// Deposit backing an order, by order hash
+    Order(BytesN<32>),
+}
+
+/// Reasons `__check_auth` refuses to authorize a fill
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum XLMOrdersError {
+    /// Authorization requested by another contract than the limit order protocol
+    UnexpectedContext = 1,
+    /// The order hash is not one of a deposited order
+    UnknownOrder = 2,
+}
+
+// STORAGE SYMBOLS
+const XLM: Symbol = symbol_short!("XLM");
+const ACCESS_TOKEN: Symbol = symbol_short!("ACC_TOK");
+
+/// TTL policy of the deposits, which live in persistent storage
+pub const STORAGE_TTL: StoredTtlPolicy = StoredTtlPolicy;
+
+// Consts
+/// Premium multipliers are expressed in thousandths
+pub const PREMIUM_BASE: u32 = 1_000;
+/// Lower bound of the cost of a cancellation, in units of `BASE_FEE`
+pub const CANCEL_GAS_LOWER_BOUND: u32 = 30_000;
+/// Stroops paid per unit of `CANCEL_GAS_LOWER_BOUND`, the network's minimum inclusion fee
+pub const BASE_FEE: u32 = 100;
+
+#[contractimpl]
+impl XLMOrders {
+    pub fn __constructor(
+        env: Env,
+        xlm: Address,
+        limit_order_protocol: Address,
+        access_token: Address,
+        ttl_policy: Option<TtlPolicy>,
+    ) {
+        STORAGE_TTL.init(&env, &ttl_policy);
+        env.storage().instance().set(&XLM, &xlm);
+        set_limit_order_protocol(&env, &limit_order_protocol);
+        env.storage().instance().set(&ACCESS_TOKEN, &access_token);
+    }
+
+    /// Deposits backing `order_hashes`, skipping the hashes without one.
+    pub fn xlm_orders_batch(env: Env, order_hashes: Vec<BytesN<32>>) -> Vec<XLMOrdersArr> {
+        let mut res: Vec<XLMOrdersArr> = Vec::new(&env);
+        for order_hash in order_hashes.iter() {
+            if let Some(data) = env
+                .storage()
+                .persistent()
+                .get(&XLMOrdersKey::Order(order_hash))
+            {
+                res.push_back(data);
+            }
+        }
+        res
+    }
+
+    /// Extends the TTL of the contract instance and of the given deposits, so long-lived orders
+    /// are not archived before they are filled or cancelled. Anyone may call it, entries that
+    /// do not exist are skipped.
+    pub fn extend_ttl(env: Env, keys: Vec<XLMOrdersKey>) {
+        STORAGE_TTL.extend_instance(&env);
+        for key in keys.iter() {
+            STORAGE_TTL.extend(&env, &key);
+        }
+    }
+
+    /// Deposits the native XLM sold by `order` on behalf of `maker`, who receives what takers
+    /// pay for it. The order is made by this contract and must call it back after each fill,
+    /// so the deposit follows the fills.
+    pub fn xlm_order_deposit(
+        env: Env,
+        maker: Address,
+        order: Order,
+        extension: Bytes,
+        maximum_premium: u32,
+        auction_duration: u32,
+    ) -> BytesN<32> {
+        maker.require_auth();
+        if !MakerTraitsLib::need_post_interaction_call(&env, order.maker_traits.clone()) {
+            panic!("InvalidOrder")
+        }
+        // Every deposit order is made by this contract, so cancelling a nonce would cancel
+        // the orders of other makers sharing it
+        if MakerTraitsLib::use_bit_invalidator(&env, order.maker_traits.clone()) {
+            panic!("InvalidOrder")
+        }
+
+        let (valid, validation_result) =
+            is_valid_extension(env.clone(), order.clone(), extension.clone());
+        if !valid {
+            match validation_result {
+                ValidationResult::MissingOrderExtension => panic!("MissingOrderExtension"),
+                ValidationResult::InvalidExtensionHash => panic!("InvalidExtensionHash"),
+                ValidationResult::UnexpectedOrderExtension => panic!("UnexpectedOrderExtension"),
+                ValidationResult::Success => (),
+            }
+        }
+
+        let this = env.current_contract_address();
+        if order.maker != this || order.receiver != maker {
+            panic!("AccessDenied")
+        }
+
+        let interaction = post_interaction_target_and_data(&env, &order, &extension);
+        if interaction.len() < 32
+            || contract_address(&env, &interaction.slice(..32).try_into().unwrap()) != this
+        {
+            panic!("InvalidOrder")
+        }
+
+        let amount = order
+            .making_amount
+            .to_u128()
+            .filter(|amount| *amount > 0 && *amount <= i128::MAX as u128)
+            .unwrap_or_else(|| panic!("InvalidOrder"));
+
+        // Keyed by the hash the limit order protocol authorizes fills of the order with
+        let limit_order_protocol = limit_order_protocol(&env);
+        let order_hash = OrderClient::new(&env, &limit_order_protocol).order_hash(&order);
+
+        let key = XLMOrdersKey::Order(order_hash.clone());
+        if env.storage().persistent().has(&key) {
+            panic!("ExistingOrder")
+        }
+        STORAGE_TTL.extend_instance(&env);
+        STORAGE_TTL.set(
+            &env,
+            &key,
+            &XLMOrdersArr {
+                maker: maker.clone(),
+                balance: amount,
+                maximum_premium,
+                auction_duration,
+                maker_traits: order.maker_traits.clone(),
+            },
+        );
+
+        // Fills move the deposit out of this contract's allowance to the protocol
+        let xlm = TokenClient::new(&env, &env.storage().instance().get(&XLM).unwrap());
+        xlm.transfer(&maker, &this, &(amount as i128));
+        Self::approve_protocol(
+            &env,
+            &xlm,
+            &limit_order_protocol,
+            xlm.allowance(&this, &limit_order_protocol) + amount as i128,
+        );
+
+        XlmOrderDeposited {
+            order_hash: order_hash.clone(),
+            maker,
+            amount: amount as i128,
+        }
+        .publish(&env);
+        order_hash
+    }
+
+    /// Cancels a deposited order and refunds what is left of its deposit to its maker.
+    pub fn cancel_order(env: Env, maker_traits: U256, order_hash: BytesN<32>) {
+        let order_data = Self::order(&env, &order_hash);
+        order_data.maker.require_auth();
+
+        let refund = Self::close_order(&env, &maker_traits, &order_hash, &order_data);
+        let xlm: Address = env.storage().instance().get(&XLM).unwrap();
+        Self::uni_transfer(env.clone(), xlm, order_data.maker.clone(), refund);
+
+        XlmOrderCancelled {
+            order_hash,
+            maker: order_data.maker,
+            refund,
+        }
+        .publish(&env);
+    }
+
+    /// Cancels an expired order on behalf of its maker. The resolver, who must hold the access
+    /// token, keeps a reward out of the deposit that grows with the time since expiry, and the
+    /// rest is refunded to the maker.
+    pub fn cancel_order_by_resolver(
+        env: Env,
+        resolver: Address,
+        maker_traits: U256,
+        order_hash: BytesN<32>,
+    ) {
+        resolver.require_auth();
+        let access_token: Address = env.storage().instance().get(&ACCESS_TOKEN).unwrap();
+        if TokenClient::new(&env, &access_token).balance(&resolver) == 0 {
+            panic!("AccessDenied")
+        }
+        if !MakerTraitsLib::is_expired(&env, maker_traits.clone()) {
+            panic!("OrderNotExpired")
+        }
+        let order_data = Self::order(&env, &order_hash);
+
+        let deposit = Self::close_order(&env, &maker_traits, &order_hash, &order_data);
+        let mut reward = 0;
+        if order_data.maximum_premium > 0 {
+            let expiration = MakerTraitsLib::expiration(&env, maker_traits);
+            let premium = Self::_get_current_premium_multiplier(
+                env.clone(),
+                order_data.clone(),
+                U256::from_u128(&env, expiration as u128),
+            )
+            .to_u128()
+            .unwrap() as i128;
+            reward = (premium * BASE_FEE as i128 * CANCEL_GAS_LOWER_BOUND as i128
+                / PREMIUM_BASE as i128)
+                .min(deposit);
+        }
+        let refund = deposit - reward;
+
+        let xlm: Address = env.storage().instance().get(&XLM).unwrap();
+        if reward > 0 {
+            Self::uni_transfer(env.clone(), xlm.clone(), resolver.clone(), reward);
+        }
+        Self::uni_transfer(env.clone(), xlm, order_data.maker.clone(), refund);
+
+        XlmOrderCancelledByThirdParty {
+            order_hash,
+            maker: order_data.maker,
+            resolver,
+            refund,
+            reward,
+        }
+        .publish(&env);
+    }
+}
+
+/// Follows the fills of deposit orders, which spend their deposit through the protocol's
+/// allowance.
+#[contractimpl]
+impl PostInteractionInterface for XLMOrders {
+    fn post_interaction(
+        env: Env,
+        _order: Order,
+        _extension: Bytes,
+        order_hash: BytesN<32>,
+        _taker: Address,
+        making_amount: U256,
+        _taking_amount: U256,
+        _remaining_making_amount: U256,
+        _extra_data: Bytes,
+    ) {
+        require_limit_order_protocol(&env);
+
+        let mut order_data = Self::order(&env, &order_hash);
+        order_data.balance = making_amount
+            .to_u128()
+            .and_then(|amount| order_data.balance.checked_sub(amount))
+            .unwrap_or_else(|| panic!("InvalidOrder"));
+        STORAGE_TTL.set(&env, &XLMOrdersKey::Order(order_hash), &order_data);
+    }
+}
+
+impl XLMOrders {
+    fn order(env: &Env, order_hash: &BytesN<32>) -> XLMOrdersArr {
+        env.storage()
+            .persistent()
+            .get(&XLMOrdersKey::Order(order_hash.clone()))
+            .unwrap_or_else(|| panic!("InvalidOrder"))
+    }
+
+    /// Cancels `order_hash` at the protocol and empties its deposit, which the caller pays out.
+    /// Returns the amount that was left.
+    fn close_order(
+        env: &Env,
+        maker_traits: &U256,
+        order_hash: &BytesN<32>,
+        order_data: &XLMOrdersArr,
+    ) -> i128 {
+        if *maker_traits != order_data.maker_traits {
+            panic!("InvalidOrder")
+        }
+
+        let this = env.current_contract_address();
+        let limit_order_protocol = limit_order_protocol(env);
+        OrderClient::new(env, &limit_order_protocol).cancel_order(&this, maker_traits, order_hash);
+
+        let deposit = order_data.balance as i128;
+        let mut closed = order_data.clone();
+        closed.balance = 0;
+        STORAGE_TTL.set(env, &XLMOrdersKey::Order(order_hash.clone()), &closed);
+
+        // The order can no longer be filled, so the protocol may not spend its deposit either
+        let xlm = TokenClient::new(env, &env.storage().instance().get(&XLM).unwrap());
+        let allowance = xlm.allowance(&this, &limit_order_protocol);
+        Self::approve_protocol(
+            env,
+            &xlm,
+            &limit_order_protocol,
+            (allowance - deposit).max(0),
+        );
+        deposit
+    }
+
+    pub fn _get_current_premium_multiplier(
+        env: Env,
+        order: XLMOrdersArr,
+        expiration_time: U256,
+    ) -> U256 {
+        let timestamp = U256::from_u128(&env, env.ledger().timestamp() as u128);
+        if timestamp.le(&expiration_time) {
+            return U256::from_u32(&env, 0);
+        }
+
+        let time_elapsed = timestamp.sub(&expiration_time);
+
+        if time_elapsed.ge(&U256::from_u32(&env, order.auction_duration)) {
+            return U256::from_u32(&env, order.maximum_premium);
+        }
+
+        return time_elapsed
+            .mul(&U256::from_u32(&env, order.maximum_premium))
+            .div(&U256::from_u32(&env, order.auction_duration));
+    }
+
+    /// Sets how much of the deposits the limit order protocol may move on fills.
+    fn approve_protocol(
+        env: &Env,
+        xlm: &TokenClient,
+        limit_order_protocol: &Address,
+        amount: i128,
+    ) {
+        // The asset contract keeps allowances alive one ledger past their expiration
+        xlm.approve(
+            &env.current_contract_address(),
+            limit_order_protocol,
+            &amount,
+            &(env.ledger().max_live_until_ledger() - 1),
+        );
+    }
+}
+
+/// Makes `XLMOrders` the maker of its deposit orders. The limit order protocol authorizes
+/// a fill with the order hash as first argument, which is accepted only for orders with
+/// some deposit left, so no signature is needed.
+#[contractimpl]
+impl CustomAccountInterface for XLMOrders {
+    type Signature = ();
+    type Error = XLMOrdersError;
+
+    fn __check_auth(
+        env: Env,
+        _signature_payload: Hash<32>,
+        _signatures: (),
+        auth_contexts: Vec<Context>,
+    ) -> Result<(), XLMOrdersError> {
+        let limit_order_protocol = limit_order_protocol(&env);
+
+        for context in auth_contexts.iter() {
+            let Context::Contract(context) = context else {
+                return Err(XLMOrdersError::UnexpectedContext);
+            };
+            if context.contract != limit_order_protocol {
+                return Err(XLMOrdersError::UnexpectedContext);
+            }
+
+            let order_hash = context
+                .args
+                .get(0)
+                .and_then(|arg| BytesN::<32>::try_from_val(&env, &arg).ok())
+                .ok_or(XLMOrdersError::UnknownOrder)?;
+            let deposited = env
+                .storage()
+                .persistent()
+                .get::<_, XLMOrdersArr>(&XLMOrdersKey::Order(order_hash))
+                .is_some_and(|order| order.balance > 0);
+            if !deposited {
+                return Err(XLMOrdersError::UnknownOrder);
+            }
+        }
+        Ok(())
+    }
+}
+
+mod test;
@@ -0,0 +1,892 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    symbol_short,
+    testutils::{
+        storage::{Instance, Persistent},
+        Address as _, Events, Ledger,
+    },
+    token::{StellarAssetClient, TokenClient},
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, InvokeError, String, Symbol, TryFromVal, Vec, U256,
+};
+use utils::ttl::{TtlPolicy, DEFAULT_POLICY};
+
+use crate::{
+    XLMOrders, XLMOrdersArr, XLMOrdersClient, XLMOrdersError, XLMOrdersKey, BASE_FEE,
+    CANCEL_GAS_LOWER_BOUND, PREMIUM_BASE,
+};
+use dutch_auction::DutchAuctionCalculatorContract;
+use events::{
+    XlmOrderCancelled, XlmOrderCancelledByThirdParty, XlmOrderDeposited, XLM_ORDER_CANCELLED,
+    XLM_ORDER_CANCELLED_BY_THIRD_PARTY, XLM_ORDER_DEPOSITED,
+};
+use order::{OrderProtocol, OrderProtocolClient};
+use order_interface::{AuctionDetails, AuctionWindow, Order};
+use order_lib::maker_traits::MakerTraitsBuilder;
+
+fn create_test_env() -> Env {
+    let env = Env::default();
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = 1000;
+    });
+    env
+}
+
+fn create_token(env: &Env) -> Address {
+    env.register_stellar_asset_contract_v2(Address::generate(env))
+        .address()
+}
+
+/// Deployed limit order protocol, native asset, access token and `XLMOrders`.
+struct Setup {
+    env: Env,
+    limit_order_protocol: Address,
+    xlm: Address,
+    access_token: Address,
+    contract_id: Address,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let env = create_test_env();
+        env.mock_all_auths_allowing_non_root_auth();
+
+        let limit_order_protocol = env.register(
+            OrderProtocol,
+            (
+                &env.register(DutchAuctionCalculatorContract, ()),
+                String::from_str(&env, "1inch Limit Order Protocol"),
+                String::from_str(&env, "4"),
+                None::<U256>,
+                None::<Address>,
+                None::<TtlPolicy>,
+            ),
+        );
+        let xlm = create_token(&env);
+        let access_token = create_token(&env);
+        let contract_id = env.register(
+            XLMOrders,
+            (
+                &xlm,
+                &limit_order_protocol,
+                &access_token,
+                None::<TtlPolicy>,
+            ),
+        );
+
+        Setup {
+            env,
+            limit_order_protocol,
+            xlm,
+            access_token,
+            contract_id,
+        }
+    }
+
+    fn client(&self) -> XLMOrdersClient<'_> {
+        XLMOrdersClient::new(&self.env, &self.contract_id)
+    }
+
+    fn xlm(&self) -> TokenClient<'_> {
+        TokenClient::new(&self.env, &self.xlm)
+    }
+
+    /// Maker holding `amount` XLM.
+    fn maker(&self, amount: i128) -> Address {
+        let maker = Address::generate(&self.env);
+        StellarAssetClient::new(&self.env, &self.xlm).mint(&maker, &amount);
+        maker
+    }
+
+    fn resolver(&self) -> Address {
+        let resolver = Address::generate(&self.env);
+        StellarAssetClient::new(&self.env, &self.access_token).mint(&resolver, &1);
+        resolver
+    }
+
+    /// Extension calling this contract back after fills.
+    fn extension(&self) -> Bytes {
+        let xdr = self.contract_id.clone().to_xdr(&self.env);
+        let listener_id = xdr.slice(xdr.len() - 32..);
+
+        // Offsets word: end of PostInteractionData in bits 224..256, the other fields are empty
+        let mut extension = Bytes::from_array(&self.env, &listener_id.len().to_be_bytes());
+        extension.extend_from_array(&[0; 28]);
+        extension.append(&listener_id);
+        extension
+    }
+
+    /// Order selling 1000 XLM for 500 of a token to `maker`, made by this contract.
+    fn order(&self, maker: &Address, expiration: u64) -> Order {
+        let env = &self.env;
+        Order {
+            salt: extension_salt(env, &self.extension()),
+            maker: self.contract_id.clone(),
+            maker_evm_address: None,
+            receiver: maker.clone(),
+            maker_asset: self.xlm.clone(),
+            taker_asset: create_token(env),
+            making_amount: U256::from_u32(env, 1000),
+            taking_amount: U256::from_u32(env, 500),
+            maker_traits: MakerTraitsBuilder::new(env.clone())
+                .with_extension()
+                .with_post_interaction_call()
+                .allow_multiple_fills()
+                .with_expiration(expiration)
+                .build(),
+        }
+    }
+
+    fn deposit(&self, maker: &Address, order: &Order, maximum_premium: u32) -> BytesN<32> {
+        self.client()
+            .xlm_order_deposit(maker, order, &self.extension(), &maximum_premium, &3600)
+    }
+
+    /// Fills `taking_amount` of `order`, paid by a new taker.
+    fn fill(&self, order: &Order, taking_amount: u32) {
+        let env = &self.env;
+        let taker = Address::generate(env);
+        StellarAssetClient::new(env, &order.taker_asset).mint(&taker, &(taking_amount as i128));
+
+        let empty = BytesN::from_array(env, &[0; 32]);
+        OrderProtocolClient::new(env, &self.limit_order_protocol).fill_order(
+            &taker,
+            order,
+            &empty,
+            &empty,
+            &U256::from_u32(env, taking_amount),
+            &U256::from_u32(env, 0),
+            &taker,
+            &self.extension(),
+            &Bytes::new(env),
+            &AuctionDetails {
+                auction_window: AuctionWindow {
+                    start: 0,
+                    end: 1000,
+                },
+                taking_amount_start: order.taking_amount.clone(),
+                taking_amount_end: order.taking_amount.clone(),
+            },
+        );
+    }
+
+    fn stored(&self, order_hash: &BytesN<32>) -> XLMOrdersArr {
+        self.env.as_contract(&self.contract_id, || {
+            self.env
+                .storage()
+                .persistent()
+                .get(&XLMOrdersKey::Order(order_hash.clone()))
+                .unwrap()
+        })
+    }
+
+    fn store(&self, order_hash: &BytesN<32>, order_data: &XLMOrdersArr) {
+        self.env.as_contract(&self.contract_id, || {
+            self.env
+                .storage()
+                .persistent()
+                .set(&XLMOrdersKey::Order(order_hash.clone()), order_data);
+        });
+    }
+}
+
+/// Salt committing the order to `extension`: the low 128 bits of its keccak256.
+fn extension_salt(env: &Env, extension: &Bytes) -> U256 {
+    let mut salt = [0u8; 32];
+    salt[16..].copy_from_slice(&env.crypto().keccak256(extension).to_array()[16..]);
+    U256::from_be_bytes(env, &Bytes::from_array(env, &salt))
+}
+
+fn order_data(env: &Env, balance: u128, maximum_premium: u32) -> XLMOrdersArr {
+    XLMOrdersArr {
+        maker: Address::generate(env),
+        balance,
+        maximum_premium,
+        auction_duration: 3600,
+        maker_traits: U256::from_u32(env, 0),
+    }
+}
+
+#[test]
+fn test_constructor() {
+    let setup = Setup::new();
+    let env = &setup.env;
+
+    env.as_contract(&setup.contract_id, || {
+        assert_eq!(
+            env.storage()
+                .instance()
+                .get::<_, Address>(&symbol_short!("XLM"))
+                .unwrap(),
+            setup.xlm
+        );
+        assert_eq!(
+            env.storage()
+                .instance()
+                .get::<_, Address>(&symbol_short!("LIM_ORP"))
+                .unwrap(),
+            setup.limit_order_protocol
+        );
+        assert_eq!(
+            env.storage()
+                .instance()
+                .get::<_, Address>(&symbol_short!("ACC_TOK"))
+                .unwrap(),
+            setup.access_token
+        );
+    });
+}
+
+#[test]
+fn test_xlm_order_deposit_takes_maker_xlm() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1500);
+    let order = setup.order(&maker, 0);
+
+    let order_hash = setup.deposit(&maker, &order, 100);
+    let deposited = env.events().all().last().unwrap();
+
+    assert_eq!(
+        order_hash,
+        OrderProtocolClient::new(env, &setup.limit_order_protocol).order_hash(&order)
+    );
+    assert_eq!(setup.xlm().balance(&maker), 500);
+    assert_eq!(setup.xlm().balance(&setup.contract_id), 1000);
+    assert_eq!(
+        setup
+            .xlm()
+            .allowance(&setup.contract_id, &setup.limit_order_protocol),
+        1000
+    );
+    assert_eq!(
+        setup.stored(&order_hash),
+        XLMOrdersArr {
+            maker: maker.clone(),
+            balance: 1000,
+            maximum_premium: 100,
+            auction_duration: 3600,
+            maker_traits: order.maker_traits.clone(),
+        }
+    );
+
+    let (_, topics, data) = deposited;
+    assert_eq!(
+        topics,
+        (XLM_ORDER_DEPOSITED, order_hash.clone()).into_val(env)
+    );
+    assert_eq!(
+        XlmOrderDeposited::try_from_val(env, &data).unwrap(),
+        XlmOrderDeposited {
+            order_hash,
+            maker,
+            amount: 1000,
+        }
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_xlm_order_deposit_requires_maker_auth() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 0);
+    setup.env.set_auths(&[]);
+
+    setup.deposit(&maker, &order, 100);
+}
+
+#[test]
+#[should_panic(expected = "InvalidOrder")]
+fn test_xlm_order_deposit_no_post_interaction() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let mut order = setup.order(&maker, 0);
+    order.maker_traits = MakerTraitsBuilder::new(setup.env.clone())
+        .with_extension()
+        .allow_multiple_fills()
+        .build();
+
+    setup.deposit(&maker, &order, 100);
+}
+
+#[test]
+#[should_panic(expected = "InvalidOrder")]
+fn test_xlm_order_deposit_rejects_bit_invalidator() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let mut order = setup.order(&maker, 0);
+    // Single fill orders are invalidated through the nonce shared by every deposit order
+    order.maker_traits = MakerTraitsBuilder::new(setup.env.clone())
+        .with_extension()
+        .with_post_interaction_call()
+        .build();
+
+    setup.deposit(&maker, &order, 100);
+}
+
+#[test]
+#[should_panic(expected = "InvalidExtensionHash")]
+fn test_xlm_order_deposit_rejects_extension_of_other_order() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let mut order = setup.order(&maker, 0);
+    order.salt = U256::from_u32(&setup.env, 12345);
+
+    setup.deposit(&maker, &order, 100);
+}
+
+#[test]
+#[should_panic(expected = "AccessDenied")]
+fn test_xlm_order_deposit_wrong_maker() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let mut order = setup.order(&maker, 0);
+    order.maker = maker.clone();
+
+    setup.deposit(&maker, &order, 100);
+}
+
+#[test]
+#[should_panic(expected = "AccessDenied")]
+fn test_xlm_order_deposit_wrong_receiver() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let mut order = setup.order(&maker, 0);
+    order.receiver = Address::generate(&setup.env);
+
+    setup.deposit(&maker, &order, 100);
+}
+
+#[test]
+#[should_panic(expected = "InvalidOrder")]
+fn test_xlm_order_deposit_rejects_other_listener() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let mut order = setup.order(&maker, 0);
+
+    let mut extension = Bytes::from_array(env, &32u32.to_be_bytes());
+    extension.extend_from_array(&[0; 28]);
+    extension.extend_from_array(&[7; 32]);
+    order.salt = extension_salt(env, &extension);
+
+    setup
+        .client()
+        .xlm_order_deposit(&maker, &order, &extension, &100, &3600);
+}
+
+#[test]
+#[should_panic(expected = "ExistingOrder")]
+fn test_xlm_order_deposit_existing_order() {
+    let setup = Setup::new();
+    let maker = setup.maker(2000);
+    let order = setup.order(&maker, 0);
+
+    setup.deposit(&maker, &order, 100);
+    setup.deposit(&maker, &order, 100);
+}
+
+#[test]
+fn test_fills_spend_deposit() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 0);
+    let order_hash = setup.deposit(&maker, &order, 100);
+
+    // 200 of the taker asset buy 400 XLM
+    setup.fill(&order, 200);
+
+    assert_eq!(setup.stored(&order_hash).balance, 600);
+    assert_eq!(setup.xlm().balance(&setup.contract_id), 600);
+    assert_eq!(
+        TokenClient::new(&setup.env, &order.taker_asset).balance(&maker),
+        200
+    );
+}
+
+#[test]
+fn test_deposit_extends_order_ttl() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order_hash = setup.deposit(&maker, &setup.order(&maker, 0), 0);
+
+    env.as_contract(&setup.contract_id, || {
+        assert_eq!(
+            env.storage()
+                .persistent()
+                .get_ttl(&XLMOrdersKey::Order(order_hash.clone())),
+            DEFAULT_POLICY.extend_to
+        );
+        assert_eq!(env.storage().instance().get_ttl(), DEFAULT_POLICY.extend_to);
+    });
+}
+
+#[test]
+fn test_extend_ttl_keeps_order_alive() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order_hash = setup.deposit(&maker, &setup.order(&maker, 0), 0);
+    let key = XLMOrdersKey::Order(order_hash.clone());
+
+    // Let the deposit get close to archival
+    let elapsed = DEFAULT_POLICY.extend_to - DEFAULT_POLICY.threshold + 1;
+    env.ledger().with_mut(|ledger| {
+        ledger.sequence_number += elapsed;
+    });
+    let ttl = || {
+        env.as_contract(&setup.contract_id, || {
+            env.storage().persistent().get_ttl(&key)
+        })
+    };
+    assert_eq!(ttl(), DEFAULT_POLICY.threshold - 1);
+
+    // Keys without an entry are skipped
+    let unknown = XLMOrdersKey::Order(BytesN::from_array(env, &[7; 32]));
+    setup
+        .client()
+        .extend_ttl(&Vec::from_array(env, [key.clone(), unknown]));
+
+    assert_eq!(ttl(), DEFAULT_POLICY.extend_to);
+    assert_eq!(setup.stored(&order_hash).balance, 1000);
+}
+
+#[test]
+#[should_panic]
+fn test_post_interaction_only_from_protocol() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 0);
+    let order_hash = setup.deposit(&maker, &order, 100);
+    env.set_auths(&[]);
+
+    setup.client().post_interaction(
+        &order,
+        &setup.extension(),
+        &order_hash,
+        &Address::generate(env),
+        &U256::from_u32(env, 1000),
+        &U256::from_u32(env, 500),
+        &U256::from_u32(env, 0),
+        &Bytes::new(env),
+    );
+}
+
+#[test]
+fn test_cancel_order_refunds_remaining_deposit() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 0);
+    let order_hash = setup.deposit(&maker, &order, 100);
+    setup.fill(&order, 200);
+
+    setup
+        .client()
+        .cancel_order(&order.maker_traits, &order_hash);
+    let cancelled = env.events().all().last().unwrap();
+
+    assert_eq!(setup.xlm().balance(&maker), 600);
+    assert_eq!(setup.xlm().balance(&setup.contract_id), 0);
+    assert_eq!(
+        setup
+            .xlm()
+            .allowance(&setup.contract_id, &setup.limit_order_protocol),
+        0
+    );
+    assert_eq!(setup.stored(&order_hash).balance, 0);
+
+    let (_, topics, data) = cancelled;
+    assert_eq!(
+        topics,
+        (XLM_ORDER_CANCELLED, order_hash.clone()).into_val(env)
+    );
+    assert_eq!(
+        XlmOrderCancelled::try_from_val(env, &data).unwrap(),
+        XlmOrderCancelled {
+            order_hash,
+            maker,
+            refund: 600,
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalidated order")]
+fn test_cancelled_order_cannot_be_filled() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 0);
+    let order_hash = setup.deposit(&maker, &order, 100);
+
+    setup
+        .client()
+        .cancel_order(&order.maker_traits, &order_hash);
+    setup.fill(&order, 200);
+}
+
+#[test]
+#[should_panic]
+fn test_cancel_order_requires_maker_auth() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 0);
+    let order_hash = setup.deposit(&maker, &order, 100);
+    setup.env.set_auths(&[]);
+
+    setup
+        .client()
+        .cancel_order(&order.maker_traits, &order_hash);
+}
+
+#[test]
+#[should_panic(expected = "InvalidOrder")]
+fn test_cancel_order_rejects_other_maker_traits() {
+    let setup = Setup::new();
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 0);
+    let order_hash = setup.deposit(&maker, &order, 100);
+
+    // Would cancel the nonce shared by every deposit order
+    setup
+        .client()
+        .cancel_order(&U256::from_u32(&setup.env, 0), &order_hash);
+}
+
+#[test]
+#[should_panic(expected = "InvalidOrder")]
+fn test_cancel_order_unknown_order() {
+    let setup = Setup::new();
+
+    setup.client().cancel_order(
+        &U256::from_u32(&setup.env, 0),
+        &BytesN::from_array(&setup.env, &[1u8; 32]),
+    );
+}
+
+#[test]
+fn test_xlm_orders_batch_empty() {
+    let setup = Setup::new();
+
+    let result = setup.client().xlm_orders_batch(&Vec::new(&setup.env));
+
+    assert_eq!(result.len(), 0);
+}
+
+#[test]
+fn test_xlm_orders_batch_with_orders() {
+    let setup = Setup::new();
+    let env = &setup.env;
+
+    let mut order_hashes = Vec::new(env);
+    let mut orders_data = Vec::new(env);
+    for i in 0..3 {
+        let hash = BytesN::from_array(env, &[i as u8; 32]);
+        order_hashes.push_back(hash.clone());
+
+        let order_data = order_data(env, 1000 * (i + 1) as u128, 100 * (i + 1) as u32);
+        setup.store(&hash, &order_data);
+        orders_data.push_back(order_data);
+    }
+
+    let result = setup.client().xlm_orders_batch(&order_hashes);
+
+    assert_eq!(result, orders_data);
+}
+
+#[test]
+fn test_xlm_orders_batch_skips_unknown_orders() {
+    let setup = Setup::new();
+    let env = &setup.env;
+
+    let zero_hash = BytesN::from_array(env, &[0u8; 32]);
+    let zero_order = order_data(env, 0, 0);
+    setup.store(&zero_hash, &zero_order);
+
+    let max_hash = BytesN::from_array(env, &[255u8; 32]);
+    let mut max_order = order_data(env, u128::MAX, u32::MAX);
+    max_order.auction_duration = u32::MAX;
+    setup.store(&max_hash, &max_order);
+
+    let mut mixed_hashes = Vec::new(env);
+    mixed_hashes.push_back(zero_hash);
+    mixed_hashes.push_back(max_hash);
+    mixed_hashes.push_back(BytesN::from_array(env, &[99u8; 32]));
+
+    let result = setup.client().xlm_orders_batch(&mixed_hashes);
+
+    assert_eq!(result, Vec::from_array(env, [zero_order, max_order]));
+}
+
+#[test]
+fn test_get_current_premium_multiplier_not_expired() {
+    let env = create_test_env();
+    let order = order_data(&env, 1000, 100);
+
+    // Set expiration time in the future
+    let expiration_time = U256::from_u128(&env, 2000); // Future time
+
+    let result = XLMOrders::_get_current_premium_multiplier(env.clone(), order, expiration_time);
+
+    assert_eq!(result, U256::from_u32(&env, 0));
+}
+
+#[test]
+fn test_get_current_premium_multiplier_fully_expired() {
+    let env = create_test_env();
+    let order = order_data(&env, 1000, 100);
+
+    // Set expiration time in the past, beyond auction duration
+    // Current timestamp is 1000, so set expiration to 500 (500 seconds ago)
+    // Time elapsed = 1000 - 500 = 500, which is less than auction_duration (3600)
+    // So it should be proportional: (500 * 100) / 3600 ≈ 13.89
+    let expiration_time = U256::from_u128(&env, 500);
+
+    let result = XLMOrders::_get_current_premium_multiplier(env.clone(), order, expiration_time);
+
+    // Should be proportional: (500 * 100) / 3600 ≈ 13.89
+    assert_eq!(result, U256::from_u32(&env, 13));
+}
+
+#[test]
+fn test_get_current_premium_multiplier_partially_expired() {
+    let env = create_test_env();
+    let order = order_data(&env, 1000, 100);
+
+    // Set expiration time in the past, within auction duration
+    // Current timestamp is 1000, so set expiration to 800 (200 seconds ago)
+    // Time elapsed = 1000 - 800 = 200, which is less than auction_duration (3600)
+    // So it should be proportional: (200 * 100) / 3600 ≈ 5.56
+    let expiration_time = U256::from_u128(&env, 800);
+
+    let result = XLMOrders::_get_current_premium_multiplier(env.clone(), order, expiration_time);
+
+    // Should be proportional: (200 * 100) / 3600 ≈ 5.56
+    // The result should be 5 (integer division)
+    assert_eq!(result, U256::from_u32(&env, 5));
+}
+
+fn fill_context(env: &Env, contract: &Address, order_hash: &BytesN<32>) -> Context {
+    Context::Contract(ContractContext {
+        contract: contract.clone(),
+        fn_name: Symbol::new(env, "fill_order"),
+        args: (order_hash.clone(),).into_val(env),
+    })
+}
+
+fn check_auth(
+    env: &Env,
+    contract_id: &Address,
+    contexts: Vec<Context>,
+) -> Result<(), Result<XLMOrdersError, InvokeError>> {
+    env.try_invoke_contract_check_auth::<XLMOrdersError>(
+        contract_id,
+        &BytesN::from_array(env, &[0u8; 32]),
+        ().into_val(env),
+        &contexts,
+    )
+}
+
+#[test]
+fn test_check_auth_accepts_fill_of_deposited_order() {
+    let setup = Setup::new();
+    let env = &setup.env;
+
+    let order_hash = BytesN::from_array(env, &[1u8; 32]);
+    setup.store(&order_hash, &order_data(env, 1000, 100));
+
+    let contexts = Vec::from_array(
+        env,
+        [fill_context(env, &setup.limit_order_protocol, &order_hash)],
+    );
+    assert_eq!(check_auth(env, &setup.contract_id, contexts), Ok(()));
+
+    // Orders without a deposit are not authorized
+    let unknown = BytesN::from_array(env, &[2u8; 32]);
+    let contexts = Vec::from_array(
+        env,
+        [fill_context(env, &setup.limit_order_protocol, &unknown)],
+    );
+    assert_eq!(
+        check_auth(env, &setup.contract_id, contexts),
+        Err(Ok(XLMOrdersError::UnknownOrder))
+    );
+
+    // Nor are orders whose deposit was spent or refunded
+    setup.store(&unknown, &order_data(env, 0, 100));
+    let contexts = Vec::from_array(
+        env,
+        [fill_context(env, &setup.limit_order_protocol, &unknown)],
+    );
+    assert_eq!(
+        check_auth(env, &setup.contract_id, contexts),
+        Err(Ok(XLMOrdersError::UnknownOrder))
+    );
+}
+
+#[test]
+fn test_check_auth_rejects_other_contracts() {
+    let setup = Setup::new();
+    let env = &setup.env;
+
+    let order_hash = BytesN::from_array(env, &[1u8; 32]);
+    setup.store(&order_hash, &order_data(env, 1000, 100));
+
+    // A token asking the contract to approve or transfer its deposits
+    let contexts = Vec::from_array(
+        env,
+        [fill_context(env, &Address::generate(env), &order_hash)],
+    );
+    assert_eq!(
+        check_auth(env, &setup.contract_id, contexts),
+        Err(Ok(XLMOrdersError::UnexpectedContext))
+    );
+}
+
+#[test]
+fn test_cancel_order_by_resolver_rewards_resolver_with_premium() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1_000_000_000);
+    let mut order = setup.order(&maker, 500);
+    order.making_amount = U256::from_u32(env, 1_000_000_000);
+    env.ledger().with_mut(|ledger| ledger.timestamp = 400);
+    let order_hash = setup.deposit(&maker, &order, PREMIUM_BASE);
+    let resolver = setup.resolver();
+
+    // Halfway through the premium auction
+    env.ledger()
+        .with_mut(|ledger| ledger.timestamp = 500 + 1800);
+    setup
+        .client()
+        .cancel_order_by_resolver(&resolver, &order.maker_traits, &order_hash);
+    let cancelled = env.events().all().last().unwrap();
+
+    let reward = (PREMIUM_BASE / 2 * BASE_FEE * CANCEL_GAS_LOWER_BOUND / PREMIUM_BASE) as i128;
+    assert_eq!(setup.xlm().balance(&resolver), reward);
+    assert_eq!(setup.xlm().balance(&maker), 1_000_000_000 - reward);
+    assert_eq!(setup.xlm().balance(&setup.contract_id), 0);
+    assert_eq!(setup.stored(&order_hash).balance, 0);
+    assert_eq!(
+        setup
+            .xlm()
+            .allowance(&setup.contract_id, &setup.limit_order_protocol),
+        0
+    );
+
+    let (_, topics, data) = cancelled;
+    assert_eq!(
+        topics,
+        (XLM_ORDER_CANCELLED_BY_THIRD_PARTY, order_hash.clone()).into_val(env)
+    );
+    assert_eq!(
+        XlmOrderCancelledByThirdParty::try_from_val(env, &data).unwrap(),
+        XlmOrderCancelledByThirdParty {
+            order_hash,
+            maker,
+            resolver,
+            refund: 1_000_000_000 - reward,
+            reward,
+        }
+    );
+}
+
+#[test]
+fn test_cancel_order_by_resolver_caps_reward_at_deposit() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 500);
+    env.ledger().with_mut(|ledger| ledger.timestamp = 400);
+    let order_hash = setup.deposit(&maker, &order, PREMIUM_BASE);
+    let resolver = setup.resolver();
+
+    env.ledger()
+        .with_mut(|ledger| ledger.timestamp = 500 + 3600);
+    setup
+        .client()
+        .cancel_order_by_resolver(&resolver, &order.maker_traits, &order_hash);
+
+    assert_eq!(setup.xlm().balance(&resolver), 1000);
+    assert_eq!(setup.xlm().balance(&maker), 0);
+}
+
+#[test]
+fn test_cancel_order_by_resolver_without_premium_refunds_everything() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 500);
+    env.ledger().with_mut(|ledger| ledger.timestamp = 400);
+    let order_hash = setup.deposit(&maker, &order, 0);
+    let resolver = setup.resolver();
+
+    env.ledger()
+        .with_mut(|ledger| ledger.timestamp = 500 + 3600);
+    setup
+        .client()
+        .cancel_order_by_resolver(&resolver, &order.maker_traits, &order_hash);
+
+    assert_eq!(setup.xlm().balance(&resolver), 0);
+    assert_eq!(setup.xlm().balance(&maker), 1000);
+}
+
+#[test]
+#[should_panic(expected = "OrderNotExpired")]
+fn test_cancel_order_by_resolver_rejects_live_order() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 500);
+    env.ledger().with_mut(|ledger| ledger.timestamp = 400);
+    let order_hash = setup.deposit(&maker, &order, PREMIUM_BASE);
+
+    setup
+        .client()
+        .cancel_order_by_resolver(&setup.resolver(), &order.maker_traits, &order_hash);
+}
+
+#[test]
+#[should_panic(expected = "InvalidOrder")]
+fn test_cancel_order_by_resolver_rejects_forged_expiration() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 0);
+    let order_hash = setup.deposit(&maker, &order, PREMIUM_BASE);
+
+    // The order never expires, the traits claim it expired at 500
+    let forged = MakerTraitsBuilder::new(env.clone())
+        .with_extension()
+        .with_post_interaction_call()
+        .allow_multiple_fills()
+        .with_expiration(500)
+        .build();
+    setup
+        .client()
+        .cancel_order_by_resolver(&setup.resolver(), &forged, &order_hash);
+}
+
+#[test]
+#[should_panic(expected = "AccessDenied")]
+fn test_cancel_order_by_resolver_requires_access_token() {
+    let setup = Setup::new();
+    let env = &setup.env;
+    let maker = setup.maker(1000);
+    let order = setup.order(&maker, 500);
+    env.ledger().with_mut(|ledger| ledger.timestamp = 400);
+    let order_hash = setup.deposit(&maker, &order, PREMIUM_BASE);
+
+    env.ledger()
+        .with_mut(|ledger| ledger.timestamp = 500 + 3600);
+    setup.client().cancel_order_by_resolver(
+        &Address::generate(env),
+        &order.maker_traits,
+        &order_hash,
+    );
+}
@@ -0,0 +1,97 @@
+use soroban_sdk::{
+    contracttype,
+    xdr::{FromXdr, ToXdr},
+    Address, Bytes, BytesN, Env,
+};
+
+// Canonical cross-chain address encoding: a one byte tag followed by the raw address.
+//
+//   0x00 || 20-byte EVM address
+//   0x01 || 32-byte Stellar ed25519 account key
+//   0x02 || 32-byte Stellar contract id
+//
+// The tag fixes both the chain and the length, so no two addresses share an encoding.
+
+pub const EVM_ADDRESS_TAG: u8 = 0x00;
+pub const STELLAR_ACCOUNT_TAG: u8 = 0x01;
+pub const STELLAR_CONTRACT_TAG: u8 = 0x02;
+
+// ScVal::Address XDR layout: 4-byte ScVal type, 4-byte ScAddress type, then for accounts
+// a 4-byte PublicKey type before the key, for contracts the id directly.
+const SC_ADDRESS_TYPE_CONTRACT: u8 = 1;
+
+/// Address of a swap party or asset on either chain
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CrossChainAddress {
+    Evm(BytesN<20>),
+    Stellar(Address),
+}
+
+impl CrossChainAddress {
+    /// Tagged canonical encoding of the address.
+    pub fn encode(&self, env: &Env) -> Bytes {
+        match self {
+            CrossChainAddress::Evm(address) => encode_evm_address(env, address),
+            CrossChainAddress::Stellar(address) => encode_stellar_address(env, address),
+        }
+    }
+
+    /// keccak256 of the canonical encoding, the word an EIP-712 `bytes` field hashes to.
+    pub fn hash(&self, env: &Env) -> BytesN<32> {
+        env.crypto().keccak256(&self.encode(env)).into()
+    }
+}
+
+pub fn encode_evm_address(env: &Env, address: &BytesN<20>) -> Bytes {
+    let mut encoded = Bytes::from_array(env, &[EVM_ADDRESS_TAG]);
+    encoded.extend_from_array(&address.to_array());
+    encoded
+}
+
+pub fn encode_stellar_address(env: &Env, address: &Address) -> Bytes {
+    let xdr = address.clone().to_xdr(env);
+    let tag = if xdr.get(7) == Some(SC_ADDRESS_TYPE_CONTRACT) {
+        STELLAR_CONTRACT_TAG
+    } else {
+        STELLAR_ACCOUNT_TAG
+    };
+
+    let mut encoded = Bytes::from_array(env, &[tag]);
+    encoded.append(&xdr.slice(xdr.len() - 32..));
+    encoded
+}
+
+/// Inverse of `encode_stellar_address`, `None` unless `encoded` is a tagged Stellar address.
+pub fn decode_stellar_address(env: &Env, encoded: &Bytes) -> Option<Address> {
+    if encoded.len() != 33 {
+        return None;
+    }
+    let key: BytesN<32> = encoded.slice(1..).try_into().ok()?;
+    match encoded.get(0)? {
+        STELLAR_ACCOUNT_TAG => Some(account_address(env, &key)),
+        STELLAR_CONTRACT_TAG => Some(contract_address(env, &key)),
+        _ => None,
+    }
+}
+
+/// Whether `address` is a contract rather than an ed25519 account.
+pub fn is_contract_address(env: &Env, address: &Address) -> bool {
+    address.clone().to_xdr(env).get(7) == Some(SC_ADDRESS_TYPE_CONTRACT)
+}
+
+/// Stellar account address of the given ed25519 public key.
+pub fn account_address(env: &Env, key: &BytesN<32>) -> Address {
+    // ScVal::Address(ScAddress::Account(PublicKey::Ed25519(key)))
+    let mut xdr = Bytes::from_array(env, &[0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0]);
+    xdr.extend_from_array(&key.to_array());
+    Address::from_xdr(env, &xdr).unwrap()
+}
+
+/// Contract address with the given 32-byte contract id.
+pub fn contract_address(env: &Env, id: &BytesN<32>) -> Address {
+    // ScVal::Address(ScAddress::Contract(id))
+    let mut xdr = Bytes::from_array(env, &[0, 0, 0, 18, 0, 0, 0, SC_ADDRESS_TYPE_CONTRACT]);
+    xdr.extend_from_array(&id.to_array());
+    Address::from_xdr(env, &xdr).unwrap()
+}
@@ -1,3 +1,6 @@
 #![no_std]
 
+pub mod address;
 pub mod math;
+pub mod protocol;
+pub mod ttl;
@@ -0,0 +1,23 @@
+use soroban_sdk::{symbol_short, Address, Env, Symbol};
+
+// Contracts the limit order protocol calls into keep its address in instance storage, set
+// by their constructor.
+
+/// Instance storage key of the limit order protocol address
+const LIMIT_ORDER_PROTOCOL: Symbol = symbol_short!("LIM_ORP");
+
+pub fn set_limit_order_protocol(env: &Env, limit_order_protocol: &Address) {
+    env.storage()
+        .instance()
+        .set(&LIMIT_ORDER_PROTOCOL, limit_order_protocol);
+}
+
+pub fn limit_order_protocol(env: &Env) -> Address {
+    env.storage().instance().get(&LIMIT_ORDER_PROTOCOL).unwrap()
+}
+
+/// Requires the authorization of the limit order protocol. The protocol never signs for
+/// its interaction calls, so this only passes when the protocol is the direct caller.
+pub fn require_limit_order_protocol(env: &Env) {
+    limit_order_protocol(env).require_auth();
+}
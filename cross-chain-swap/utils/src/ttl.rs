@@ -0,0 +1,117 @@
+use soroban_sdk::{contracttype, symbol_short, Env, IntoVal, Symbol, Val};
+
+// Persistent entries are archived once their TTL runs out, and an archived invalidator or
+// deposit would stall a swap until someone restores it. Contracts go through a `TtlPolicy`
+// for their per-order and per-maker entries, so every write keeps them alive, and anyone can
+// extend them through the contracts' `extend_ttl`. Reads leave the TTL alone, so views such
+// as `quote_fill` write nothing.
+// Each contract is deployed with its policy, kept in instance storage by `StoredTtlPolicy`.
+
+/// Instance storage key of the policy a contract was deployed with
+const TTL_POLICY: Symbol = symbol_short!("TTL_POL");
+
+/// Ledgers closed in a day, at 5 seconds per ledger
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Keeps entries alive for 30 days, renewed once they have less than 7 days left
+pub const DEFAULT_POLICY: TtlPolicy = TtlPolicy::new(7 * DAY_IN_LEDGERS, 30 * DAY_IN_LEDGERS);
+
+/// When and how far the TTL of an entry is extended.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TtlPolicy {
+    /// Entries with a TTL below this many ledgers get extended
+    pub threshold: u32,
+    /// TTL the extended entries get, in ledgers
+    pub extend_to: u32,
+}
+
+impl TtlPolicy {
+    pub const fn new(threshold: u32, extend_to: u32) -> Self {
+        if threshold > extend_to {
+            panic!("InvalidTtlPolicy");
+        }
+        TtlPolicy {
+            threshold,
+            extend_to,
+        }
+    }
+
+    /// Writes a persistent entry and extends its TTL.
+    pub fn set<K, V>(&self, env: &Env, key: &K, value: &V)
+    where
+        K: IntoVal<Env, Val>,
+        V: IntoVal<Env, Val>,
+    {
+        env.storage().persistent().set(key, value);
+        env.storage()
+            .persistent()
+            .extend_ttl(key, self.threshold, self.extend_to);
+    }
+
+    /// Extends the TTL of a persistent entry. Returns false if there is no such entry.
+    pub fn extend<K>(&self, env: &Env, key: &K) -> bool
+    where
+        K: IntoVal<Env, Val>,
+    {
+        if !env.storage().persistent().has(key) {
+            return false;
+        }
+        env.storage()
+            .persistent()
+            .extend_ttl(key, self.threshold, self.extend_to);
+        true
+    }
+
+    /// Extends the TTL of the contract instance, along with its code and instance storage.
+    pub fn extend_instance(&self, env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(self.threshold, self.extend_to);
+    }
+}
+
+/// TTL policy read from the instance storage of the current contract, `DEFAULT_POLICY` if its
+/// constructor did not set one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StoredTtlPolicy;
+
+impl StoredTtlPolicy {
+    /// Stores the policy of the contract, `DEFAULT_POLICY` if `None`. Called by constructors.
+    pub fn init(&self, env: &Env, policy: &Option<TtlPolicy>) {
+        let policy = policy.unwrap_or(DEFAULT_POLICY);
+        if policy.threshold > policy.extend_to || policy.extend_to > env.storage().max_ttl() {
+            panic!("InvalidTtlPolicy");
+        }
+        env.storage().instance().set(&TTL_POLICY, &policy);
+    }
+
+    pub fn policy(&self, env: &Env) -> TtlPolicy {
+        env.storage()
+            .instance()
+            .get(&TTL_POLICY)
+            .unwrap_or(DEFAULT_POLICY)
+    }
+
+    /// Writes a persistent entry and extends its TTL.
+    pub fn set<K, V>(&self, env: &Env, key: &K, value: &V)
+    where
+        K: IntoVal<Env, Val>,
+        V: IntoVal<Env, Val>,
+    {
+        self.policy(env).set(env, key, value)
+    }
+
+    /// Extends the TTL of a persistent entry. Returns false if there is no such entry.
+    pub fn extend<K>(&self, env: &Env, key: &K) -> bool
+    where
+        K: IntoVal<Env, Val>,
+    {
+        self.policy(env).extend(env, key)
+    }
+
+    /// Extends the TTL of the contract instance, along with its code and instance storage.
+    pub fn extend_instance(&self, env: &Env) {
+        self.policy(env).extend_instance(env)
+    }
+}